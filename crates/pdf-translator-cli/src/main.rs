@@ -1,9 +1,17 @@
 //! PDF Translator CLI - Command line tool for translating PDF documents.
 
 use anyhow::{Context, Result, bail};
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use indicatif::{ProgressBar, ProgressStyle};
-use pdf_translator_core::{AppConfig, Lang, PdfDocument, PdfTranslator, TextColor};
+use pdf_translator_core::{
+    AppConfig, AzureOpenAiConfig, BackendConfig, DEFAULT_AZURE_API_VERSION, Lang, OverlayOptions,
+    PdfDocument, PdfTranslator, TextColor, TranslationMemory, TranslationProject,
+    TranslatorBackend, UsageSummary,
+    interchange::{
+        tmx,
+        xliff::{self, XliffDocument},
+    },
+};
 use std::ffi::OsString;
 use std::fs::OpenOptions;
 use std::io::Write;
@@ -34,13 +42,45 @@ impl From<ColorOption> for TextColor {
     }
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum BackendOption {
+    #[value(name = "openai")]
+    OpenAi,
+    #[value(name = "deepl")]
+    DeepL,
+    #[value(name = "libretranslate")]
+    LibreTranslate,
+    Anthropic,
+    Ollama,
+    Command,
+    Mock,
+}
+
+impl From<BackendOption> for TranslatorBackend {
+    fn from(opt: BackendOption) -> Self {
+        match opt {
+            BackendOption::OpenAi => Self::OpenAi,
+            BackendOption::DeepL => Self::DeepL,
+            BackendOption::LibreTranslate => Self::LibreTranslate,
+            BackendOption::Anthropic => Self::Anthropic,
+            BackendOption::Ollama => Self::Ollama,
+            BackendOption::Command => Self::Command,
+            BackendOption::Mock => Self::Mock,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(name = "pdf-translate")]
 #[command(author, version, about = "Translate PDF documents", long_about = None)]
+#[command(subcommand_negates_reqs = true)]
 struct Args {
+    #[command(subcommand)]
+    command: Option<Command>,
+
     /// Input PDF file
     #[arg(required = true)]
-    input: PathBuf,
+    input: Option<PathBuf>,
 
     /// Output PDF file (default: `input-<target>.pdf`)
     #[arg(short, long)]
@@ -54,6 +94,10 @@ struct Args {
     #[arg(short = 't', long)]
     target: Option<String>,
 
+    /// Translation backend (default: from config, otherwise openai)
+    #[arg(long, value_enum)]
+    backend: Option<BackendOption>,
+
     /// OpenAI API base URL
     #[arg(long, env = "OPENAI_API_BASE")]
     api_base: Option<String>,
@@ -66,6 +110,42 @@ struct Args {
     #[arg(long, env = "OPENAI_MODEL")]
     model: Option<String>,
 
+    /// Azure OpenAI deployment name (switches --api-base to Azure's URL layout)
+    #[arg(long, env = "AZURE_OPENAI_DEPLOYMENT")]
+    azure_deployment: Option<String>,
+
+    /// Azure OpenAI API version
+    #[arg(long, env = "AZURE_OPENAI_API_VERSION")]
+    azure_api_version: Option<String>,
+
+    /// DeepL API key
+    #[arg(long, env = "DEEPL_API_KEY")]
+    deepl_api_key: Option<String>,
+
+    /// LibreTranslate server URL
+    #[arg(long, env = "LIBRETRANSLATE_URL")]
+    libretranslate_url: Option<String>,
+
+    /// LibreTranslate API key
+    #[arg(long, env = "LIBRETRANSLATE_API_KEY")]
+    libretranslate_api_key: Option<String>,
+
+    /// Anthropic API key
+    #[arg(long, env = "ANTHROPIC_API_KEY")]
+    anthropic_api_key: Option<String>,
+
+    /// Model name for the Anthropic API
+    #[arg(long, env = "ANTHROPIC_MODEL")]
+    anthropic_model: Option<String>,
+
+    /// Ollama server URL
+    #[arg(long, env = "OLLAMA_URL")]
+    ollama_url: Option<String>,
+
+    /// Model name for Ollama
+    #[arg(long, env = "OLLAMA_MODEL")]
+    ollama_model: Option<String>,
+
     /// Translation text color
     #[arg(long, value_enum)]
     color: Option<ColorOption>,
@@ -82,9 +162,193 @@ struct Args {
     #[arg(long)]
     pages: Option<String>,
 
+    /// Glossary file (TOML or CSV) of fixed term translations
+    #[arg(long)]
+    glossary: Option<PathBuf>,
+
+    /// Number of pages to translate concurrently
+    #[arg(long)]
+    max_concurrent_pages: Option<usize>,
+
     /// Disable caching
     #[arg(long, action = clap::ArgAction::SetTrue)]
     no_cache: Option<bool>,
+
+    /// Reuse and store translated blocks in the translation memory
+    #[arg(long, action = clap::ArgAction::SetTrue)]
+    translation_memory: Option<bool>,
+
+    /// Also write the blocks and their translations to an XLIFF 2.0 file for review
+    #[arg(long, value_name = "FILE")]
+    xliff: Option<PathBuf>,
+
+    /// Also write each page's blocks and translations to a JSON project that
+    /// can be edited and rendered again with `render`
+    #[arg(long, value_name = "FILE")]
+    emit_project: Option<PathBuf>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Manage the translation memory
+    #[command(subcommand)]
+    Tm(TmCommand),
+    /// Exchange translations with reviewers as XLIFF 2.0
+    #[command(subcommand)]
+    Xliff(XliffCommand),
+    /// Render the PDF again from a translation project, without translating
+    Render {
+        /// Project file written with --emit-project
+        project: PathBuf,
+
+        /// Source PDF (default: the file named in the project, next to it)
+        #[arg(long)]
+        pdf: Option<PathBuf>,
+
+        /// Output PDF file (default: `<pdf>-<target>.pdf`)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum TmCommand {
+    /// Add the translation units of a TMX file to the translation memory
+    Import {
+        /// TMX file to read
+        file: PathBuf,
+    },
+    /// Write the translation memory to a TMX file
+    Export {
+        /// TMX file to write
+        file: PathBuf,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum XliffCommand {
+    /// Render the PDF again from a reviewed XLIFF file, without translating
+    Import {
+        /// XLIFF file written with --xliff
+        file: PathBuf,
+
+        /// Source PDF (default: the file named in the XLIFF, next to it)
+        #[arg(long)]
+        pdf: Option<PathBuf>,
+
+        /// Output PDF file (default: `<pdf>-<target>.pdf`)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+}
+
+/// Overlay the translations of an exported file on its source PDF again.
+fn rerender(
+    file: &Path,
+    original: &str,
+    pdf: Option<PathBuf>,
+    output: Option<PathBuf>,
+    target_lang: &Lang,
+    config: &AppConfig,
+    render: impl FnOnce(&PdfDocument, &OverlayOptions) -> pdf_translator_core::Result<Vec<u8>>,
+) -> Result<()> {
+    let pdf = match pdf {
+        Some(pdf) => pdf,
+        None if !original.is_empty() => file.with_file_name(original),
+        None => bail!("{} does not name its PDF; pass --pdf", file.display()),
+    };
+    let output_path = resolved_output_path(&pdf, output, target_lang);
+    reject_input_alias(&pdf, &output_path)?;
+    let doc = PdfDocument::from_file(&pdf)
+        .with_context(|| format!("Failed to load PDF: {}", pdf.display()))?;
+
+    let options = OverlayOptions {
+        text_color: config.text_color,
+        ..Default::default()
+    };
+    let output_bytes = render(&doc, &options).context("Failed to render the translations")?;
+    write_atomic(&output_path, &output_bytes)?;
+
+    // CLI output is intentional
+    #[allow(clippy::print_stdout)]
+    {
+        println!("Translated PDF saved to: {}", output_path.display());
+    }
+    Ok(())
+}
+
+fn run_xliff(command: XliffCommand, config: &AppConfig) -> Result<()> {
+    let XliffCommand::Import { file, pdf, output } = command;
+    let xml = std::fs::read_to_string(&file)
+        .with_context(|| format!("Failed to read XLIFF file: {}", file.display()))?;
+    let document = xliff::read(&xml)
+        .with_context(|| format!("Failed to parse XLIFF file: {}", file.display()))?;
+    rerender(
+        &file,
+        &document.original,
+        pdf,
+        output,
+        &document.target_lang,
+        config,
+        |doc, options| document.render(doc, options),
+    )
+}
+
+fn run_render(
+    file: &Path,
+    pdf: Option<PathBuf>,
+    output: Option<PathBuf>,
+    config: &AppConfig,
+) -> Result<()> {
+    let json = std::fs::read_to_string(file)
+        .with_context(|| format!("Failed to read project file: {}", file.display()))?;
+    let project = TranslationProject::from_json(&json)
+        .with_context(|| format!("Failed to parse project file: {}", file.display()))?;
+    rerender(
+        file,
+        &project.source_pdf,
+        pdf,
+        output,
+        &project.target_lang,
+        config,
+        |doc, options| project.render(doc, options),
+    )
+}
+
+fn run_tm(command: TmCommand, config: &AppConfig) -> Result<()> {
+    let memory = TranslationMemory::from_config(&config.translation_memory)
+        .context("Failed to open the translation memory")?
+        .context(
+            "The translation memory is off; pass --translation-memory or set \
+             `enabled = true` under [translation_memory]",
+        )?;
+
+    // CLI output is intentional
+    #[allow(clippy::print_stdout)]
+    match command {
+        TmCommand::Import { file } => {
+            let xml = std::fs::read_to_string(&file)
+                .with_context(|| format!("Failed to read TMX file: {}", file.display()))?;
+            let units = tmx::read(&xml)
+                .with_context(|| format!("Failed to parse TMX file: {}", file.display()))?;
+            let added = memory.import(&units)?;
+            println!(
+                "Imported {added} of {} translation units into {}",
+                units.len(),
+                memory.path().display()
+            );
+        }
+        TmCommand::Export { file } => {
+            let units = memory.units();
+            write_atomic(&file, tmx::write(&units).as_bytes())?;
+            println!(
+                "Exported {} translation units to {}",
+                units.len(),
+                file.display()
+            );
+        }
+    }
+    Ok(())
 }
 
 fn parse_page_range(pages: &str, total: usize) -> Result<Vec<usize>> {
@@ -297,26 +561,105 @@ async fn main() -> Result<()> {
     if let Some(color) = args.color {
         config.text_color = color.into();
     }
-    if let Some(api_base) = args.api_base {
-        config.translator.api_base = api_base;
+    if let Some(backend) = args.backend.map(TranslatorBackend::from)
+        && config.translator.backend.kind() != backend
+    {
+        config.translator.backend = BackendConfig::default_for(backend);
     }
-    if let Some(api_key) = args.api_key {
-        config.translator.api_key = Some(api_key);
+    // Credentials only reach the backend they belong to.
+    match &mut config.translator.backend {
+        BackendConfig::OpenAi(openai) => {
+            if let Some(api_base) = args.api_base {
+                openai.api_base = api_base;
+            }
+            if let Some(api_key) = args.api_key {
+                openai.api_key = Some(api_key);
+            }
+            if let Some(model) = args.model {
+                openai.model = model;
+            }
+            if let Some(deployment) = args.azure_deployment {
+                let api_version = openai.azure.take().map_or_else(
+                    || DEFAULT_AZURE_API_VERSION.to_string(),
+                    |azure| azure.api_version,
+                );
+                openai.azure = Some(AzureOpenAiConfig {
+                    deployment,
+                    api_version,
+                });
+            }
+            if let (Some(api_version), Some(azure)) = (args.azure_api_version, &mut openai.azure) {
+                azure.api_version = api_version;
+            }
+        }
+        BackendConfig::DeepL(deepl) => {
+            if let Some(api_key) = args.deepl_api_key {
+                deepl.api_key = Some(api_key);
+            }
+        }
+        BackendConfig::LibreTranslate(libre) => {
+            if let Some(api_base) = args.libretranslate_url {
+                libre.api_base = api_base;
+            }
+            if let Some(api_key) = args.libretranslate_api_key {
+                libre.api_key = Some(api_key);
+            }
+        }
+        BackendConfig::Anthropic(anthropic) => {
+            if let Some(api_key) = args.anthropic_api_key {
+                anthropic.api_key = Some(api_key);
+            }
+            if let Some(model) = args.anthropic_model {
+                anthropic.model = model;
+            }
+        }
+        BackendConfig::Ollama(ollama) => {
+            if let Some(api_base) = args.ollama_url {
+                ollama.api_base = api_base;
+            }
+            if let Some(model) = args.ollama_model {
+                ollama.model = model;
+            }
+        }
+        BackendConfig::Command(_) | BackendConfig::Mock => {}
     }
-    if let Some(model) = args.model {
-        config.translator.model = model;
+    if let Some(glossary) = args.glossary {
+        config.glossary = Some(glossary);
+    }
+    if let Some(max_concurrent_pages) = args.max_concurrent_pages {
+        config.max_concurrent_pages = max_concurrent_pages.max(1);
     }
     if args.no_cache == Some(true) {
         config.cache.memory_enabled = false;
         config.cache.disk_enabled = false;
     }
+    if args.translation_memory == Some(true) {
+        config.translation_memory.enabled = true;
+    }
+
+    let input = match args.command {
+        Some(Command::Tm(command)) => return run_tm(command, &config),
+        Some(Command::Xliff(command)) => return run_xliff(command, &config),
+        Some(Command::Render {
+            project,
+            pdf,
+            output,
+        }) => return run_render(&project, pdf, output, &config),
+        None => args.input.context("An input PDF is required")?,
+    };
+    let exports_blocks = args.xliff.is_some() || args.emit_project.is_some();
+    if exports_blocks && config.cache.pages_enabled {
+        // Cached pages keep only the rendered PDF; their blocks come from the block cache.
+        info!("Bypassing the page cache to export every block");
+        config.cache.pages_enabled = false;
+    }
 
-    let output_path = resolved_output_path(&args.input, args.output, &config.target_lang);
-    reject_input_alias(&args.input, &output_path)?;
+    let output_path = resolved_output_path(&input, args.output, &config.target_lang);
+    reject_input_alias(&input, &output_path)?;
     // Load input PDF
-    info!("Loading PDF: {}", args.input.display());
-    let doc = PdfDocument::from_file(&args.input)
-        .context(format!("Failed to load PDF: {}", args.input.display()))?;
+    info!("Loading PDF: {}", input.display());
+    let doc = PdfDocument::from_file(&input)
+        .context(format!("Failed to load PDF: {}", input.display()))?;
 
     let total_pages = doc.page_count();
     info!("Document has {} pages", total_pages);
@@ -353,23 +696,50 @@ async fn main() -> Result<()> {
     );
 
     // Translate pages
-    let mut translated_pages = Vec::with_capacity(pages.len());
-
-    for &page_num in &pages {
-        pb.set_message(format!("Page {}", page_num + 1));
-
-        let result = translator
-            .translate_page(&doc, page_num)
-            .await
-            .context(format!("Failed to translate page {}", page_num + 1))?;
-
-        if result.from_cache {
-            pb.println(format!("Page {} (cached)", page_num + 1));
-        }
-
-        translated_pages.push(result.pdf_bytes);
-        pb.inc(1);
-    }
+    let translated = translator
+        .translate_pages(&doc, &pages, |result| {
+            if result.from_cache {
+                pb.println(format!("Page {} (cached)", result.page_num + 1));
+            } else if result.memory.hits() > 0 {
+                pb.println(format!(
+                    "Page {} ({} of {} blocks from translation memory, {} fuzzy)",
+                    result.page_num + 1,
+                    result.memory.hits(),
+                    result.memory.lookups,
+                    result.memory.fuzzy
+                ));
+            }
+            pb.inc(1);
+        })
+        .await
+        .context("Failed to translate pages")?;
+    let summary: UsageSummary = translated.iter().collect();
+    let original = input
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let review = args.xliff.map(|path| {
+        let document = XliffDocument::from_pages(
+            original.clone(),
+            config.source_lang.clone(),
+            config.target_lang.clone(),
+            &translated,
+        );
+        (path, document)
+    });
+    let project = args.emit_project.map(|path| {
+        let project = TranslationProject::from_pages(
+            original.clone(),
+            config.source_lang.clone(),
+            config.target_lang.clone(),
+            &translated,
+        );
+        (path, project)
+    });
+    let translated_pages: Vec<Vec<u8>> = translated
+        .into_iter()
+        .map(|result| result.pdf_bytes)
+        .collect();
 
     pb.finish_with_message("Translation complete");
 
@@ -378,11 +748,24 @@ async fn main() -> Result<()> {
         .context("Failed to combine translated pages")?;
 
     write_atomic(&output_path, &output_bytes)?;
+    if let Some((path, document)) = &review {
+        write_atomic(path, xliff::write(document).as_bytes())?;
+    }
+    if let Some((path, project)) = &project {
+        write_atomic(path, project.to_json()?.as_bytes())?;
+    }
 
     // CLI output is intentional
     #[allow(clippy::print_stdout)]
     {
         println!("Translated PDF saved to: {}", output_path.display());
+        if let Some((path, _)) = &review {
+            println!("Review file saved to: {}", path.display());
+        }
+        if let Some((path, _)) = &project {
+            println!("Translation project saved to: {}", path.display());
+        }
+        println!("Usage: {}", summary.describe(&config.pricing));
     }
 
     Ok(())
@@ -7,7 +7,7 @@ const fn usize_as_u64(value: usize) -> u64 {
     value as u64
 }
 
-/// Cache key for translated PDF pages.
+/// Cache key for translated PDF pages and blocks.
 ///
 /// Keys are opaque MD5 hashes of all relevant inputs, ensuring:
 /// - Same document + page + content + settings = same key
@@ -28,19 +28,12 @@ impl CacheKey {
         target_lang: &Lang,
         text_color: TextColor,
     ) -> Self {
-        fn consume_field(context: &mut md5::Context, value: &[u8]) {
-            context.consume(usize_as_u64(value.len()).to_be_bytes());
-            context.consume(value);
-        }
-
         let mut context = md5::Context::new();
         consume_field(&mut context, b"pdf-translator-cache-key-v2");
         consume_field(&mut context, doc_id.as_ref().as_bytes());
         context.consume(usize_as_u64(page_num).to_be_bytes());
         consume_field(&mut context, text_content.as_bytes());
-        consume_field(&mut context, translator.backend().as_bytes());
-        consume_field(&mut context, translator.endpoint().as_bytes());
-        consume_field(&mut context, translator.model().as_bytes());
+        consume_identity(&mut context, translator);
         consume_field(&mut context, source_lang.as_str().as_bytes());
         consume_field(&mut context, target_lang.as_str().as_bytes());
         context.consume(text_color.r.to_bits().to_be_bytes());
@@ -72,11 +65,47 @@ impl CacheKey {
         )
     }
 
+    /// Key for the translated text of one block, independent of the document,
+    /// page, and rendering, so a block reads the same wherever it appears.
+    pub fn for_block(
+        text: &str,
+        translator: &TranslatorCacheIdentity,
+        source_lang: &Lang,
+        target_lang: &Lang,
+    ) -> Self {
+        let mut context = md5::Context::new();
+        consume_field(&mut context, b"pdf-translator-block-key-v1");
+        consume_field(&mut context, text.as_bytes());
+        consume_identity(&mut context, translator);
+        consume_field(&mut context, source_lang.as_str().as_bytes());
+        consume_field(&mut context, target_lang.as_str().as_bytes());
+
+        Self {
+            hash: format!("{:x}", context.compute()),
+        }
+    }
+
     pub fn as_str(&self) -> &str {
         &self.hash
     }
 }
 
+fn consume_field(context: &mut md5::Context, value: &[u8]) {
+    context.consume(usize_as_u64(value.len()).to_be_bytes());
+    context.consume(value);
+}
+
+fn consume_identity(context: &mut md5::Context, translator: &TranslatorCacheIdentity) {
+    consume_field(context, translator.backend().as_bytes());
+    consume_field(context, translator.endpoint().as_bytes());
+    consume_field(context, translator.model().as_bytes());
+    // Options are only hashed when present so existing entries stay addressable.
+    for (key, value) in translator.options() {
+        consume_field(context, key.as_bytes());
+        consume_field(context, value.as_bytes());
+    }
+}
+
 impl std::fmt::Display for CacheKey {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         // Just output the hash - no need for human-readable format
@@ -175,6 +204,57 @@ mod tests {
         assert_ne!(k1, k2);
     }
 
+    #[test]
+    fn test_cache_key_differs_by_translator_options() {
+        let identity = TranslatorCacheIdentity::new("OpenAI", "https://example.test/v1", "model");
+        let key_for = |identity: &TranslatorCacheIdentity| {
+            CacheKey::new(
+                "doc",
+                0,
+                "Hello",
+                identity,
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                BLACK,
+            )
+        };
+
+        let glossary_a = identity.clone().with_option("glossary", "a");
+        let glossary_b = identity.clone().with_option("glossary", "b");
+        assert_ne!(key_for(&identity), key_for(&glossary_a));
+        assert_ne!(key_for(&glossary_a), key_for(&glossary_b));
+    }
+
+    #[test]
+    fn test_block_key_ignores_page_and_differs_from_page_key() {
+        let identity = TranslatorCacheIdentity::new("OpenAI", "https://example.test/v1", "model");
+        let block = |text: &str, identity: &TranslatorCacheIdentity| {
+            CacheKey::for_block(text, identity, &Lang::new("fr"), &Lang::new("en"))
+        };
+
+        assert_eq!(block("Hello", &identity), block("Hello", &identity));
+        assert_ne!(block("Hello", &identity), block("World", &identity));
+        assert_ne!(
+            block("Hello", &identity),
+            block(
+                "Hello",
+                &identity.clone().with_option("prompt.system", "Be terse.")
+            )
+        );
+        assert_ne!(
+            block("Hello", &identity),
+            CacheKey::new(
+                "",
+                0,
+                "Hello",
+                &identity,
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                BLACK
+            )
+        );
+    }
+
     #[test]
     fn test_cache_key_same_inputs_same_key() {
         assert_eq!(
@@ -0,0 +1,175 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, PoisonError};
+
+use crate::UsageSummary;
+use crate::error::Result;
+use crate::translator::TokenUsage;
+
+/// Usage spent on each document, keyed by document cache id.
+///
+/// Shared through [`super::TranslationCache`], so every translator over the
+/// same cache (such as the one the web server builds per request) draws on one
+/// budget per document. Requests reserve their estimated usage before they are
+/// sent and settle it once the backend reports the actual usage, so pages
+/// translated concurrently see each other's spending.
+#[derive(Default)]
+pub struct UsageLedger {
+    documents: Mutex<HashMap<String, Spending>>,
+}
+
+#[derive(Default)]
+struct Spending {
+    spent: UsageSummary,
+    reserved: TokenUsage,
+    reserved_cost: f64,
+}
+
+impl Spending {
+    /// Usage spent so far plus the usage reserved by requests in flight.
+    fn committed(&self) -> UsageSummary {
+        let mut committed = self.spent;
+        committed.add(self.reserved, Some(self.reserved_cost));
+        committed
+    }
+
+    fn release(&mut self, usage: TokenUsage, cost: f64) {
+        self.reserved -= usage;
+        self.reserved_cost = (self.reserved_cost - cost).max(0.0);
+    }
+}
+
+/// Usage reserved for one request until it is settled.
+///
+/// Dropping it unsettled releases the reservation without charging anything.
+#[must_use]
+pub struct Reservation<'a> {
+    ledger: &'a UsageLedger,
+    document: String,
+    usage: TokenUsage,
+    cost: f64,
+    settled: bool,
+}
+
+impl UsageLedger {
+    /// Usage settled for `document` so far.
+    pub fn usage(&self, document: &str) -> UsageSummary {
+        self.lock()
+            .get(document)
+            .map(|spending| spending.spent)
+            .unwrap_or_default()
+    }
+
+    /// Reserve `usage` and `cost` for a request on `document`, once `check`
+    /// accepts the usage already spent or reserved for it.
+    pub fn reserve(
+        &self,
+        document: &str,
+        usage: TokenUsage,
+        cost: f64,
+        check: impl FnOnce(&UsageSummary) -> Result<()>,
+    ) -> Result<Reservation<'_>> {
+        let mut documents = self.lock();
+        let spending = documents.entry(document.to_string()).or_default();
+        check(&spending.committed())?;
+        spending.reserved += usage;
+        spending.reserved_cost += cost;
+        drop(documents);
+        Ok(Reservation {
+            ledger: self,
+            document: document.to_string(),
+            usage,
+            cost,
+            settled: false,
+        })
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, HashMap<String, Spending>> {
+        self.documents
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl Reservation<'_> {
+    /// Replace the reservation with the usage and cost actually billed.
+    pub fn settle(mut self, usage: TokenUsage, cost: Option<f64>) {
+        let mut documents = self.ledger.lock();
+        let spending = documents.entry(self.document.clone()).or_default();
+        spending.release(self.usage, self.cost);
+        spending.spent.add(usage, cost);
+        drop(documents);
+        self.settled = true;
+    }
+}
+
+impl Drop for Reservation<'_> {
+    fn drop(&mut self) {
+        if !self.settled
+            && let Some(spending) = self.ledger.lock().get_mut(&self.document)
+        {
+            spending.release(self.usage, self.cost);
+        }
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+
+    fn under_one_request(committed: &UsageSummary) -> Result<()> {
+        if committed.usage.requests >= 1 {
+            return Err(Error::TranslationBudgetExceeded("one request".to_string()));
+        }
+        Ok(())
+    }
+
+    fn one_request() -> TokenUsage {
+        TokenUsage {
+            requests: 1,
+            ..TokenUsage::default()
+        }
+    }
+
+    #[test]
+    fn requests_in_flight_count_against_the_budget() {
+        let ledger = UsageLedger::default();
+        let first = ledger
+            .reserve("doc", one_request(), 0.0, under_one_request)
+            .unwrap();
+        assert!(matches!(
+            ledger.reserve("doc", one_request(), 0.0, under_one_request),
+            Err(Error::TranslationBudgetExceeded(_))
+        ));
+        assert!(
+            ledger
+                .reserve("other", one_request(), 0.0, under_one_request)
+                .is_ok()
+        );
+
+        first.settle(one_request(), Some(0.0));
+        assert_eq!(ledger.usage("doc").usage.requests, 1);
+        assert!(
+            ledger
+                .reserve("doc", one_request(), 0.0, |_| Ok(()))
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn dropped_reservations_are_released_unbilled() {
+        let ledger = UsageLedger::default();
+        drop(
+            ledger
+                .reserve("doc", one_request(), 0.5, under_one_request)
+                .unwrap(),
+        );
+        assert_eq!(ledger.usage("doc"), UsageSummary::default());
+        assert!(
+            ledger
+                .reserve("doc", one_request(), 0.0, under_one_request)
+                .is_ok()
+        );
+    }
+}
@@ -1,28 +1,39 @@
 mod disk;
 mod key;
+mod ledger;
 mod memory;
+mod translation_memory;
 
 pub use disk::DiskCache;
 pub use key::CacheKey;
+pub use ledger::{Reservation, UsageLedger};
 pub use memory::MemoryCache;
+pub use translation_memory::{MemoryHits, MemoryMatch, TranslationMemory, TranslationUnit};
 
 use std::sync::Arc;
 
-use crate::config::CacheConfig;
+use crate::config::{AppConfig, CacheConfig};
 use crate::error::Result;
 
 /// Combined cache with memory and disk layers.
 ///
+/// Holds rendered pages and, under separate keys, the translated text of
+/// single blocks, so a page can be rendered again without its translations.
+///
 /// This is cheaply cloneable via internal `Arc`, allowing a single cache
-/// to be shared across multiple `PdfTranslator` instances.
+/// to be shared across multiple `PdfTranslator` instances. A translation
+/// memory attached with [`TranslationCache::with_memory`] is shared the same way,
+/// and so is the [`UsageLedger`] that enforces per-document budgets.
 #[derive(Clone)]
 pub struct TranslationCache {
     inner: Arc<TranslationCacheInner>,
+    memory: Option<TranslationMemory>,
 }
 
 struct TranslationCacheInner {
     memory: Option<MemoryCache>,
     disk: Option<DiskCache>,
+    ledger: UsageLedger,
 }
 
 impl TranslationCache {
@@ -48,10 +59,44 @@ impl TranslationCache {
         };
 
         Ok(Self {
-            inner: Arc::new(TranslationCacheInner { memory, disk }),
+            inner: Arc::new(TranslationCacheInner {
+                memory,
+                disk,
+                ledger: UsageLedger::default(),
+            }),
+            memory: None,
         })
     }
 
+    /// Create the page cache and the translation memory configured in `config`.
+    pub fn from_app_config(config: &AppConfig) -> Result<Self> {
+        config.translation_memory.validate()?;
+        let cache = Self::new(&config.cache)?;
+        Ok(
+            match TranslationMemory::from_config(&config.translation_memory)? {
+                Some(memory) => cache.with_memory(memory),
+                None => cache,
+            },
+        )
+    }
+
+    /// Attach a segment-level translation memory.
+    #[must_use]
+    pub fn with_memory(mut self, memory: TranslationMemory) -> Self {
+        self.memory = Some(memory);
+        self
+    }
+
+    /// Translation memory consulted before blocks are translated, if any.
+    pub const fn memory(&self) -> Option<&TranslationMemory> {
+        self.memory.as_ref()
+    }
+
+    /// Usage spent on each document by every translator sharing this cache.
+    pub fn ledger(&self) -> &UsageLedger {
+        &self.inner.ledger
+    }
+
     pub async fn get(&self, key: &CacheKey) -> Option<Vec<u8>> {
         let key_str = key.to_string();
 
@@ -103,6 +148,17 @@ impl TranslationCache {
         }
     }
 
+    /// Translated text of a block stored under a [`CacheKey::for_block`] key.
+    pub async fn get_text(&self, key: &CacheKey) -> Option<String> {
+        self.get(key)
+            .await
+            .and_then(|bytes| String::from_utf8(bytes).ok())
+    }
+
+    pub async fn insert_text(&self, key: &CacheKey, text: &str) {
+        self.insert(key, text.as_bytes().to_vec()).await;
+    }
+
     pub async fn contains(&self, key: &CacheKey) -> bool {
         self.get(key).await.is_some()
     }
@@ -0,0 +1,657 @@
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, PoisonError, RwLock};
+
+use serde::{Deserialize, Serialize};
+use tracing::{debug, warn};
+
+use crate::config::{Lang, TranslationMemoryConfig, TranslatorCacheIdentity};
+use crate::error::{Error, Result};
+
+/// Segment-level store of past translations, shared across documents.
+///
+/// Segments are keyed by their normalized source text, language pair, and
+/// translator identity, so a paragraph repeated in another document is reused
+/// instead of translated again. Lookups also accept near-identical sources
+/// (by edit distance) when they contain the same numbers.
+///
+/// Entries live in memory and are appended to a JSON Lines file; a later line
+/// for the same segment replaces an earlier one when the file is loaded.
+#[derive(Clone)]
+pub struct TranslationMemory {
+    inner: Arc<TranslationMemoryInner>,
+}
+
+struct TranslationMemoryInner {
+    path: PathBuf,
+    min_similarity: f64,
+    /// Partition → normalized source → translation
+    segments: RwLock<HashMap<Partition, HashMap<String, String>>>,
+    /// Serializes appends so lines from concurrent pages never interleave.
+    writer: Mutex<()>,
+}
+
+/// Translator recorded for segments imported from elsewhere.
+const IMPORTED: &str = "imported";
+
+/// Source language, target language, and translator fingerprint (or [`IMPORTED`]).
+type Partition = (String, String, String);
+
+/// One stored segment, as written to the memory file.
+#[derive(Debug, Serialize, Deserialize)]
+struct Entry {
+    source_lang: String,
+    target_lang: String,
+    translator: String,
+    source: String,
+    target: String,
+}
+
+/// A source segment and its translation, as imported or exported.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslationUnit {
+    pub source_lang: Lang,
+    pub target_lang: Lang,
+    pub source: String,
+    pub target: String,
+}
+
+/// Translation found in the memory for a block.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MemoryMatch {
+    /// Stored for the same normalized source text
+    Exact(String),
+    /// Stored for a similar source text with the same numbers
+    Fuzzy {
+        translation: String,
+        /// Share of characters the sources have in common (0.0–1.0)
+        similarity: f64,
+    },
+}
+
+impl MemoryMatch {
+    pub fn into_translation(self) -> String {
+        match self {
+            Self::Exact(translation) | Self::Fuzzy { translation, .. } => translation,
+        }
+    }
+
+    pub const fn is_exact(&self) -> bool {
+        matches!(self, Self::Exact(_))
+    }
+}
+
+/// Blocks of one page served from the translation memory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MemoryHits {
+    /// Blocks looked up
+    pub lookups: usize,
+    /// Blocks matched exactly
+    pub exact: usize,
+    /// Blocks matched by a similar source
+    pub fuzzy: usize,
+}
+
+impl MemoryHits {
+    /// Record the outcome of one lookup.
+    pub const fn record(&mut self, found: Option<&MemoryMatch>) {
+        self.lookups += 1;
+        match found {
+            Some(MemoryMatch::Exact(_)) => self.exact += 1,
+            Some(MemoryMatch::Fuzzy { .. }) => self.fuzzy += 1,
+            None => {}
+        }
+    }
+
+    /// Blocks matched either way.
+    pub const fn hits(&self) -> usize {
+        self.exact + self.fuzzy
+    }
+}
+
+impl TranslationMemory {
+    /// Open the memory described by `config`, or `None` when it is disabled.
+    pub fn from_config(config: &TranslationMemoryConfig) -> Result<Option<Self>> {
+        if !config.enabled {
+            return Ok(None);
+        }
+        let path = config
+            .path
+            .clone()
+            .unwrap_or_else(crate::util::translation_memory_path);
+        Self::open(path, config.min_similarity).map(Some)
+    }
+
+    /// Load the memory file at `path`, creating it on the first insert.
+    ///
+    /// Fuzzy matches need at least `min_similarity`; `1.0` only accepts
+    /// exact matches.
+    pub fn open(path: impl AsRef<Path>, min_similarity: f32) -> Result<Self> {
+        let path = path.as_ref();
+        if let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            fs::create_dir_all(parent).map_err(|error| {
+                Error::CacheInit(format!(
+                    "Failed to create translation memory directory {}: {error}",
+                    parent.display()
+                ))
+            })?;
+        }
+
+        let mut segments: HashMap<Partition, HashMap<String, String>> = HashMap::new();
+        match fs::File::open(path) {
+            Ok(file) => {
+                let mut skipped = 0usize;
+                for line in BufReader::new(file).lines() {
+                    let line = line.map_err(|error| {
+                        Error::CacheRead(format!(
+                            "Failed to read translation memory {}: {error}",
+                            path.display()
+                        ))
+                    })?;
+                    if line.trim().is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<Entry>(&line) {
+                        Ok(entry) => {
+                            segments
+                                .entry(partition(
+                                    &entry.source_lang,
+                                    &entry.target_lang,
+                                    &entry.translator,
+                                ))
+                                .or_default()
+                                .insert(entry.source, entry.target);
+                        }
+                        Err(_) => skipped += 1,
+                    }
+                }
+                if skipped > 0 {
+                    warn!(
+                        "Skipped {} unreadable entries in translation memory {}",
+                        skipped,
+                        path.display()
+                    );
+                }
+            }
+            Err(error) if error.kind() == io::ErrorKind::NotFound => {}
+            Err(error) => {
+                return Err(Error::CacheInit(format!(
+                    "Failed to open translation memory {}: {error}",
+                    path.display()
+                )));
+            }
+        }
+
+        debug!(
+            "Opened translation memory at {} ({} segments)",
+            path.display(),
+            segments.values().map(HashMap::len).sum::<usize>()
+        );
+
+        Ok(Self {
+            inner: Arc::new(TranslationMemoryInner {
+                path: path.to_path_buf(),
+                min_similarity: f64::from(min_similarity),
+                segments: RwLock::new(segments),
+                writer: Mutex::new(()),
+            }),
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.inner.path
+    }
+
+    /// Number of stored segments.
+    pub fn len(&self) -> usize {
+        self.inner
+            .segments
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .values()
+            .map(HashMap::len)
+            .sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Find a stored translation of `text`.
+    ///
+    /// Imported translations are preferred over machine translations. Fuzzy
+    /// matching scans every segment of the language pair, so call this off the
+    /// async runtime.
+    pub fn lookup(
+        &self,
+        text: &str,
+        source_lang: &Lang,
+        target_lang: &Lang,
+        translator: &TranslatorCacheIdentity,
+    ) -> Option<MemoryMatch> {
+        let source = normalize(text);
+        if source.is_empty() {
+            return None;
+        }
+        let segments = self
+            .inner
+            .segments
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        let machine = partition(
+            source_lang.as_str(),
+            target_lang.as_str(),
+            &identity_fingerprint(translator),
+        );
+        let candidates: Vec<&HashMap<String, String>> = segments
+            .iter()
+            .filter(|((source, target, stored_by), _)| {
+                stored_by == IMPORTED
+                    && covers(source_lang.as_str(), source)
+                    && covers(target_lang.as_str(), target)
+            })
+            .map(|(_, stored)| stored)
+            .chain(segments.get(&machine))
+            .collect();
+        if let Some(translation) = candidates.iter().find_map(|stored| stored.get(&source)) {
+            return Some(MemoryMatch::Exact(translation.clone()));
+        }
+        if self.inner.min_similarity >= 1.0 {
+            return None;
+        }
+
+        let query: Vec<char> = source.chars().collect();
+        let query_numbers = numbers(&source);
+        let mut best: Option<(f64, &String)> = None;
+        for (candidate, translation) in candidates.into_iter().flatten() {
+            let candidate_len = candidate.chars().count();
+            let longest = query.len().max(candidate_len);
+            // The edit distance is at least the length difference.
+            if !within_similarity(
+                query.len().abs_diff(candidate_len),
+                longest,
+                self.inner.min_similarity,
+            ) {
+                continue;
+            }
+            // A changed figure needs a new translation, however small the edit.
+            if numbers(candidate) != query_numbers {
+                continue;
+            }
+            let candidate: Vec<char> = candidate.chars().collect();
+            let similarity = similarity(&query, &candidate);
+            if similarity >= self.inner.min_similarity
+                && best.is_none_or(|(best_similarity, _)| similarity > best_similarity)
+            {
+                best = Some((similarity, translation));
+            }
+        }
+        best.map(|(similarity, translation)| MemoryMatch::Fuzzy {
+            translation: translation.clone(),
+            similarity,
+        })
+    }
+
+    /// Store translated segments and append them to the memory file.
+    ///
+    /// Segments already stored with the same translation are not written again.
+    pub fn insert(
+        &self,
+        source_lang: &Lang,
+        target_lang: &Lang,
+        translator: &TranslatorCacheIdentity,
+        segments: &[(&str, &str)],
+    ) -> Result<()> {
+        let translator = identity_fingerprint(translator);
+        let entries = segments.iter().map(|(source, target)| Entry {
+            source_lang: source_lang.as_str().to_string(),
+            target_lang: target_lang.as_str().to_string(),
+            translator: translator.clone(),
+            source: normalize(source),
+            target: (*target).to_string(),
+        });
+        self.store(entries).map(|_| ())
+    }
+
+    /// Store translations approved elsewhere, such as units of a TMX file.
+    ///
+    /// Imported segments serve every translator and take precedence over
+    /// machine translations. Returns the number of segments added or changed.
+    pub fn import(&self, units: &[TranslationUnit]) -> Result<usize> {
+        self.store(units.iter().map(|unit| Entry {
+            source_lang: unit.source_lang.as_str().to_string(),
+            target_lang: unit.target_lang.as_str().to_string(),
+            translator: IMPORTED.to_string(),
+            source: normalize(&unit.source),
+            target: unit.target.clone(),
+        }))
+    }
+
+    /// Every stored segment, imported or translated, ordered by language pair
+    /// and source text.
+    pub fn units(&self) -> Vec<TranslationUnit> {
+        let segments = self
+            .inner
+            .segments
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut units: Vec<TranslationUnit> = segments
+            .iter()
+            .flat_map(|((source_lang, target_lang, _), stored)| {
+                stored.iter().map(move |(source, target)| TranslationUnit {
+                    source_lang: Lang::new(source_lang),
+                    target_lang: Lang::new(target_lang),
+                    source: source.clone(),
+                    target: target.clone(),
+                })
+            })
+            .collect();
+        drop(segments);
+        units.sort_by(|a, b| {
+            (
+                a.source_lang.as_str(),
+                a.target_lang.as_str(),
+                &a.source,
+                &a.target,
+            )
+                .cmp(&(
+                    b.source_lang.as_str(),
+                    b.target_lang.as_str(),
+                    &b.source,
+                    &b.target,
+                ))
+        });
+        // A segment stored both as imported and as translated is listed once
+        // when both translations agree.
+        units.dedup();
+        units
+    }
+
+    /// Add `entries` to the index and append the new ones to the memory file.
+    fn store(&self, entries: impl IntoIterator<Item = Entry>) -> Result<usize> {
+        let _writer = self
+            .inner
+            .writer
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        let mut lines = String::new();
+        let mut stored_count = 0;
+        let mut segments = self
+            .inner
+            .segments
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        for entry in entries {
+            if entry.source.is_empty() {
+                continue;
+            }
+            let stored = segments
+                .entry(partition(
+                    &entry.source_lang,
+                    &entry.target_lang,
+                    &entry.translator,
+                ))
+                .or_default();
+            if stored.get(&entry.source) == Some(&entry.target) {
+                continue;
+            }
+            let line = serde_json::to_string(&entry).map_err(|error| {
+                Error::CacheWrite(format!(
+                    "Failed to encode translation memory entry: {error}"
+                ))
+            })?;
+            lines.push_str(&line);
+            lines.push('\n');
+            stored.insert(entry.source, entry.target);
+            stored_count += 1;
+        }
+        drop(segments);
+        if lines.is_empty() {
+            return Ok(0);
+        }
+
+        let write_result = (|| -> io::Result<()> {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.inner.path)?;
+            file.write_all(lines.as_bytes())?;
+            file.sync_data()
+        })();
+        write_result.map_err(|error| {
+            Error::CacheWrite(format!(
+                "Failed to append to translation memory {}: {error}",
+                self.inner.path.display()
+            ))
+        })?;
+        Ok(stored_count)
+    }
+}
+
+/// Source text as stored: soft hyphens removed and whitespace collapsed.
+pub fn normalize(text: &str) -> String {
+    text.split_whitespace()
+        .map(|word| word.replace('\u{AD}', ""))
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+fn partition(source_lang: &str, target_lang: &str, translator: &str) -> Partition {
+    (
+        source_lang.to_string(),
+        target_lang.to_string(),
+        translator.to_string(),
+    )
+}
+
+/// Whether segments stored for `stored` serve the `configured` language:
+/// the same code ignoring case, or a regional variant of a plain code
+/// (`en-US` for `en`), as CAT tools usually tag their memories.
+fn covers(configured: &str, stored: &str) -> bool {
+    configured.eq_ignore_ascii_case(stored)
+        || (!configured.contains(['-', '_'])
+            && stored
+                .split(['-', '_'])
+                .next()
+                .is_some_and(|primary| primary.eq_ignore_ascii_case(configured)))
+}
+
+/// Stable hash of a translator identity, stored with each entry.
+fn identity_fingerprint(identity: &TranslatorCacheIdentity) -> String {
+    let mut context = md5::Context::new();
+    context.consume(b"pdf-translator-memory-v1");
+    let fields = [identity.backend(), identity.endpoint(), identity.model()]
+        .into_iter()
+        .chain(identity.options().flat_map(<[&str; 2]>::from));
+    for field in fields {
+        context.consume((field.len() as u64).to_be_bytes());
+        context.consume(field.as_bytes());
+    }
+    format!("{:x}", context.compute())
+}
+
+/// Runs of digits in `text`, in order.
+fn numbers(text: &str) -> Vec<&str> {
+    text.split(|c: char| !c.is_ascii_digit())
+        .filter(|run| !run.is_empty())
+        .collect()
+}
+
+#[allow(clippy::cast_precision_loss)]
+fn within_similarity(distance: usize, longest: usize, min_similarity: f64) -> bool {
+    longest == 0 || 1.0 - distance as f64 / longest as f64 >= min_similarity
+}
+
+/// Share of characters `a` and `b` have in common, by Levenshtein distance.
+#[allow(clippy::cast_precision_loss)]
+fn similarity(a: &[char], b: &[char]) -> f64 {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return 1.0;
+    }
+    1.0 - edit_distance(a, b) as f64 / longest as f64
+}
+
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+#[allow(clippy::expect_used)]
+mod tests {
+    use super::*;
+
+    fn identity() -> TranslatorCacheIdentity {
+        TranslatorCacheIdentity::new("OpenAI", "https://example.test/v1", "model")
+    }
+
+    fn lookup(memory: &TranslationMemory, text: &str) -> Option<MemoryMatch> {
+        memory.lookup(text, &Lang::new("fr"), &Lang::new("en"), &identity())
+    }
+
+    #[test]
+    fn segments_persist_and_match_exactly_after_normalization() {
+        let directory = tempfile::tempdir().expect("temp directory");
+        let path = directory.path().join("memory.jsonl");
+        let memory = TranslationMemory::open(&path, 0.9).expect("memory");
+        memory
+            .insert(
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &identity(),
+                &[("Le chiffre d'affaires a augmenté.", "Revenue increased.")],
+            )
+            .expect("insert");
+
+        let reopened = TranslationMemory::open(&path, 0.9).expect("reopen");
+        assert_eq!(reopened.len(), 1);
+        assert_eq!(
+            lookup(&reopened, "Le chiffre  d'af\u{AD}faires\na augmenté."),
+            Some(MemoryMatch::Exact("Revenue increased.".to_string()))
+        );
+        assert_eq!(
+            reopened.lookup(
+                "Le chiffre d'affaires a augmenté.",
+                &Lang::new("fr"),
+                &Lang::new("de"),
+                &identity()
+            ),
+            None
+        );
+        assert_eq!(
+            reopened.lookup(
+                "Le chiffre d'affaires a augmenté.",
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &identity().with_option("glossary", "abc")
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn fuzzy_matches_need_similar_text_and_the_same_numbers() {
+        let directory = tempfile::tempdir().expect("temp directory");
+        let memory =
+            TranslationMemory::open(directory.path().join("memory.jsonl"), 0.9).expect("memory");
+        memory
+            .insert(
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &identity(),
+                &[(
+                    "Le chiffre d'affaires du trimestre a augmenté de 12 %.",
+                    "Quarterly revenue increased by 12%.",
+                )],
+            )
+            .expect("insert");
+
+        let found = lookup(
+            &memory,
+            "Le chiffre d'affaires du trimestre a augmente de 12 %",
+        )
+        .expect("fuzzy match");
+        assert!(!found.is_exact());
+        assert_eq!(
+            found.into_translation(),
+            "Quarterly revenue increased by 12%."
+        );
+
+        assert_eq!(
+            lookup(
+                &memory,
+                "Le chiffre d'affaires du trimestre a augmenté de 15 %."
+            ),
+            None
+        );
+        assert_eq!(lookup(&memory, "Le résultat net a diminué de 12 %."), None);
+    }
+
+    #[test]
+    fn imported_units_serve_every_translator_and_take_precedence() {
+        let directory = tempfile::tempdir().expect("temp directory");
+        let memory =
+            TranslationMemory::open(directory.path().join("memory.jsonl"), 1.0).expect("memory");
+        memory
+            .insert(
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &identity(),
+                &[("Bonjour à tous.", "Hello all.")],
+            )
+            .expect("insert");
+        let unit = TranslationUnit {
+            source_lang: Lang::new("fr-FR"),
+            target_lang: Lang::new("en-GB"),
+            source: "Bonjour à tous.".to_string(),
+            target: "Hello everyone.".to_string(),
+        };
+        assert_eq!(
+            memory.import(std::slice::from_ref(&unit)).expect("import"),
+            1
+        );
+        assert_eq!(memory.import(&[unit]).expect("repeated import"), 0);
+
+        assert_eq!(
+            lookup(&memory, "Bonjour à tous."),
+            Some(MemoryMatch::Exact("Hello everyone.".to_string()))
+        );
+        let other = TranslatorCacheIdentity::new("DeepL", "https://api.deepl.com", "");
+        assert!(
+            memory
+                .lookup(
+                    "Bonjour à tous.",
+                    &Lang::new("fr"),
+                    &Lang::new("en"),
+                    &other
+                )
+                .is_some()
+        );
+        assert_eq!(memory.units().len(), 2);
+    }
+
+    #[test]
+    fn edit_distance_counts_insertions_deletions_and_substitutions() {
+        let chars = |text: &str| text.chars().collect::<Vec<_>>();
+        assert_eq!(edit_distance(&chars("kitten"), &chars("sitting")), 3);
+        assert_eq!(edit_distance(&chars(""), &chars("abc")), 3);
+        assert_eq!(edit_distance(&chars("é"), &chars("e")), 1);
+    }
+}
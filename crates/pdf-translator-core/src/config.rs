@@ -1,6 +1,9 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::PathBuf;
 
+use crate::translator::TokenUsage;
+
 /// Language codes following ISO 639-1 with regional variants
 #[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 #[serde(transparent)]
@@ -120,6 +123,7 @@ pub struct TranslatorCacheIdentity {
     backend: String,
     endpoint: String,
     model: String,
+    options: BTreeMap<String, String>,
 }
 
 impl TranslatorCacheIdentity {
@@ -132,9 +136,24 @@ impl TranslatorCacheIdentity {
             backend: backend.into(),
             endpoint: endpoint.into(),
             model: model.into(),
+            options: BTreeMap::new(),
         }
     }
 
+    /// Add another output-affecting setting (e.g. a glossary fingerprint).
+    #[must_use]
+    pub fn with_option(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+        self.options.insert(key.into(), value.into());
+        self
+    }
+
+    /// Remove a setting added with [`Self::with_option`].
+    #[must_use]
+    pub fn without_option(mut self, key: &str) -> Self {
+        self.options.remove(key);
+        self
+    }
+
     pub fn backend(&self) -> &str {
         &self.backend
     }
@@ -146,39 +165,732 @@ impl TranslatorCacheIdentity {
     pub fn model(&self) -> &str {
         &self.model
     }
+
+    /// Additional settings, in key order.
+    pub fn options(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.options
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
 }
 
-/// Translator backend configuration for OpenAI-compatible APIs.
+/// Kind of translation backend, without its options.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TranslatorBackend {
+    /// OpenAI-compatible chat completions API
+    #[default]
+    #[serde(rename = "openai")]
+    OpenAi,
+    /// DeepL REST API
+    #[serde(rename = "deepl")]
+    DeepL,
+    /// LibreTranslate (Argos Translate) REST API, e.g. for offline deployments
+    #[serde(rename = "libretranslate")]
+    LibreTranslate,
+    /// Anthropic Messages API
+    #[serde(rename = "anthropic")]
+    Anthropic,
+    /// Ollama's native chat API
+    #[serde(rename = "ollama")]
+    Ollama,
+    /// External program reading JSON on stdin and printing the translation
+    #[serde(rename = "command")]
+    Command,
+    /// Offline stand-in that tags text instead of translating it
+    #[serde(rename = "mock")]
+    Mock,
+}
+
+impl TranslatorBackend {
+    /// Name used for the `backend` field in config files
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::OpenAi => "openai",
+            Self::DeepL => "deepl",
+            Self::LibreTranslate => "libretranslate",
+            Self::Anthropic => "anthropic",
+            Self::Ollama => "ollama",
+            Self::Command => "command",
+            Self::Mock => "mock",
+        }
+    }
+}
+
+impl std::fmt::Display for TranslatorBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Options for OpenAI-compatible APIs.
 ///
 /// Supports llama.cpp, Ollama, DeepSeek, OpenAI, and any other OpenAI-compatible API.
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct TranslatorConfig {
+pub struct OpenAiConfig {
+    #[serde(default = "default_api_base")]
     pub api_base: String,
+    #[serde(default)]
     pub api_key: Option<String>,
+    #[serde(default = "default_model")]
     pub model: String,
-    #[serde(default = "default_retry_count")]
+    /// Prompt templates and sampling parameters (`[translator.prompt]`)
+    #[serde(default)]
+    pub prompt: PromptConfig,
+    /// Azure OpenAI deployment (`[translator.azure]`); switches to Azure's
+    /// URL layout and `api-key` header
+    #[serde(default)]
+    pub azure: Option<AzureOpenAiConfig>,
+}
+
+impl Default for OpenAiConfig {
+    fn default() -> Self {
+        Self {
+            api_base: default_api_base(),
+            api_key: None,
+            model: default_model(),
+            prompt: PromptConfig::default(),
+            azure: None,
+        }
+    }
+}
+
+/// Azure OpenAI deployment addressed through the OpenAI-compatible backend.
+///
+/// `api_base` is then the resource endpoint (e.g.
+/// `https://my-resource.openai.azure.com`) and `model` names the model behind
+/// the deployment, used for pricing.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AzureOpenAiConfig {
+    /// Deployment name chosen when the model was deployed
+    pub deployment: String,
+    /// REST API version sent as the `api-version` query parameter
+    #[serde(default = "default_azure_api_version")]
+    pub api_version: String,
+}
+
+/// Azure OpenAI API version used when none is configured
+pub const DEFAULT_AZURE_API_VERSION: &str = "2024-10-21";
+
+fn default_azure_api_version() -> String {
+    DEFAULT_AZURE_API_VERSION.to_string()
+}
+
+impl AzureOpenAiConfig {
+    /// Check that the deployment can be addressed
+    pub fn validate(&self, field: &str) -> Result<(), crate::error::Error> {
+        for (name, value) in [
+            ("deployment", &self.deployment),
+            ("api_version", &self.api_version),
+        ] {
+            if value.trim().is_empty() {
+                return Err(crate::error::Error::ConfigInvalid {
+                    field: format!("{field}.{name}"),
+                    reason: "must not be empty".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Default sampling temperature for chat-completion backends
+pub const DEFAULT_TEMPERATURE: f32 = 0.3;
+
+/// Prompt templates and sampling parameters for chat-completion backends.
+///
+/// Templates may use `{source}` and `{target}` (language names), `{text}`
+/// (the text to translate), and `{context}` (glossary instructions, empty
+/// when none apply). Every setting is part of the cache identity.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PromptConfig {
+    /// System message sent before each request
+    #[serde(default)]
+    pub system: Option<String>,
+    /// User message template replacing the built-in prompt; must contain `{text}`
+    #[serde(default)]
+    pub user: Option<String>,
+    /// Sampling temperature (0 to 2)
+    #[serde(default = "default_temperature")]
+    pub temperature: Option<f32>,
+    /// Maximum number of tokens to generate
+    #[serde(default)]
+    pub max_tokens: Option<u32>,
+    /// Nucleus sampling probability mass (0 to 1)
+    #[serde(default)]
+    pub top_p: Option<f32>,
+    /// Additional fields merged into the request body (e.g. `repeat_penalty`)
+    #[serde(default)]
+    pub extra_body: serde_json::Map<String, serde_json::Value>,
+}
+
+#[allow(clippy::unnecessary_wraps)] // serde default for an optional field
+const fn default_temperature() -> Option<f32> {
+    Some(DEFAULT_TEMPERATURE)
+}
+
+impl Default for PromptConfig {
+    fn default() -> Self {
+        Self {
+            system: None,
+            user: None,
+            temperature: default_temperature(),
+            max_tokens: None,
+            top_p: None,
+            extra_body: serde_json::Map::new(),
+        }
+    }
+}
+
+/// Request body fields that `extra_body` must not override.
+const RESERVED_BODY_FIELDS: &[&str] = &["model", "messages", "stream"];
+
+impl PromptConfig {
+    /// Check templates, sampling ranges, and extra body fields.
+    pub fn validate(&self, field: &str) -> Result<(), crate::error::Error> {
+        let invalid = |name: &str, reason: &str| crate::error::Error::ConfigInvalid {
+            field: format!("{field}.{name}"),
+            reason: reason.to_string(),
+        };
+        if let Some(user) = &self.user
+            && !user.contains("{text}")
+        {
+            return Err(invalid("user", "must contain the {text} placeholder"));
+        }
+        if self
+            .temperature
+            .is_some_and(|temperature| !(0.0..=2.0).contains(&temperature))
+        {
+            return Err(invalid("temperature", "must be between 0 and 2"));
+        }
+        if self
+            .top_p
+            .is_some_and(|top_p| !(0.0..=1.0).contains(&top_p))
+        {
+            return Err(invalid("top_p", "must be between 0 and 1"));
+        }
+        if self.max_tokens == Some(0) {
+            return Err(invalid("max_tokens", "must be greater than 0"));
+        }
+        if let Some(reserved) = RESERVED_BODY_FIELDS
+            .iter()
+            .find(|reserved| self.extra_body.contains_key(**reserved))
+        {
+            return Err(invalid(
+                "extra_body",
+                &format!("must not override the \"{reserved}\" field"),
+            ));
+        }
+        Ok(())
+    }
+
+    /// Add every non-default setting to a cache identity.
+    #[must_use]
+    pub fn apply_to_identity(&self, identity: TranslatorCacheIdentity) -> TranslatorCacheIdentity {
+        let mut identity = identity;
+        if let Some(system) = &self.system {
+            identity = identity.with_option("prompt.system", system.as_str());
+        }
+        if let Some(user) = &self.user {
+            identity = identity.with_option("prompt.user", user.as_str());
+        }
+        if self.temperature != default_temperature() {
+            let value = self
+                .temperature
+                .map_or_else(|| "unset".to_string(), |value| value.to_string());
+            identity = identity.with_option("prompt.temperature", value);
+        }
+        if let Some(max_tokens) = self.max_tokens {
+            identity = identity.with_option("prompt.max_tokens", max_tokens.to_string());
+        }
+        if let Some(top_p) = self.top_p {
+            identity = identity.with_option("prompt.top_p", top_p.to_string());
+        }
+        if !self.extra_body.is_empty() {
+            let extra = serde_json::Value::Object(self.extra_body.clone()).to_string();
+            identity = identity.with_option("prompt.extra_body", extra);
+        }
+        identity
+    }
+}
+
+/// DeepL formality preference for target languages that support it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeepLFormality {
+    Default,
+    More,
+    Less,
+    PreferMore,
+    PreferLess,
+}
+
+impl DeepLFormality {
+    /// Value sent in the API `formality` field
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Default => "default",
+            Self::More => "more",
+            Self::Less => "less",
+            Self::PreferMore => "prefer_more",
+            Self::PreferLess => "prefer_less",
+        }
+    }
+}
+
+/// Options for the DeepL API.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct DeepLConfig {
+    /// DeepL authentication key
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// API base override; by default keys ending in `:fx` use the free
+    /// endpoint and all others the pro endpoint
+    #[serde(default)]
+    pub api_base: Option<String>,
+    /// Formality preference
+    #[serde(default)]
+    pub formality: Option<DeepLFormality>,
+    /// ID of a glossary stored on the DeepL account
+    #[serde(default)]
+    pub glossary_id: Option<String>,
+}
+
+/// Options for a LibreTranslate server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibreTranslateConfig {
+    #[serde(default = "default_libretranslate_api_base")]
+    pub api_base: String,
+    /// Only needed for servers started with API keys enabled
+    #[serde(default)]
+    pub api_key: Option<String>,
+}
+
+impl Default for LibreTranslateConfig {
+    fn default() -> Self {
+        Self {
+            api_base: default_libretranslate_api_base(),
+            api_key: None,
+        }
+    }
+}
+
+/// Anthropic API version sent in the `anthropic-version` header when none is configured
+pub const DEFAULT_ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Options for the Anthropic Messages API.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AnthropicConfig {
+    #[serde(default = "default_anthropic_api_base")]
+    pub api_base: String,
+    #[serde(default)]
+    pub api_key: Option<String>,
+    /// Model identifier; required, as there is no sensible default
+    #[serde(default)]
+    pub model: String,
+    /// Value of the `anthropic-version` header
+    #[serde(default = "default_anthropic_version")]
+    pub anthropic_version: String,
+    /// Prompt templates and sampling parameters (`[translator.prompt]`)
+    #[serde(default)]
+    pub prompt: PromptConfig,
+}
+
+impl Default for AnthropicConfig {
+    fn default() -> Self {
+        Self {
+            api_base: default_anthropic_api_base(),
+            api_key: None,
+            model: String::new(),
+            anthropic_version: default_anthropic_version(),
+            prompt: PromptConfig::default(),
+        }
+    }
+}
+
+/// Options for Ollama's native `/api/chat` endpoint.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OllamaConfig {
+    #[serde(default = "default_ollama_api_base")]
+    pub api_base: String,
+    /// Model name as listed by `ollama list`
+    #[serde(default)]
+    pub model: String,
+    /// How long the model stays loaded after a request (e.g. `10m`, or `-1m`
+    /// to keep it loaded); the server default applies when unset
+    #[serde(default)]
+    pub keep_alive: Option<String>,
+    /// Context window size in tokens (`options.num_ctx`)
+    #[serde(default)]
+    pub num_ctx: Option<u32>,
+    /// Prompt templates and sampling parameters (`[translator.prompt]`)
+    #[serde(default)]
+    pub prompt: PromptConfig,
+}
+
+impl Default for OllamaConfig {
+    fn default() -> Self {
+        Self {
+            api_base: default_ollama_api_base(),
+            model: String::new(),
+            keep_alive: None,
+            num_ctx: None,
+            prompt: PromptConfig::default(),
+        }
+    }
+}
+
+/// Options for an external translation program.
+///
+/// The program is started once per block with `{"text", "source", "target"}`
+/// as JSON on stdin and must print the translation on stdout.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommandConfig {
+    /// Program to run, looked up on `PATH` unless it contains a path separator
+    #[serde(default)]
+    pub program: PathBuf,
+    /// Arguments passed to the program
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Seconds a single invocation may run before it is killed
+    #[serde(default = "default_command_timeout_seconds")]
+    pub timeout_seconds: u64,
+    /// Maximum number of invocations running at once
+    #[serde(default = "default_command_max_concurrent")]
+    pub max_concurrent: usize,
+}
+
+const fn default_command_timeout_seconds() -> u64 {
+    60
+}
+
+const fn default_command_max_concurrent() -> usize {
+    4
+}
+
+impl Default for CommandConfig {
+    fn default() -> Self {
+        Self {
+            program: PathBuf::new(),
+            args: Vec::new(),
+            timeout_seconds: default_command_timeout_seconds(),
+            max_concurrent: default_command_max_concurrent(),
+        }
+    }
+}
+
+/// Backend selection, tagged by the `backend` field, with that backend's options.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "backend")]
+pub enum BackendConfig {
+    #[serde(rename = "openai")]
+    OpenAi(OpenAiConfig),
+    #[serde(rename = "deepl")]
+    DeepL(DeepLConfig),
+    #[serde(rename = "libretranslate")]
+    LibreTranslate(LibreTranslateConfig),
+    #[serde(rename = "anthropic")]
+    Anthropic(AnthropicConfig),
+    #[serde(rename = "ollama")]
+    Ollama(OllamaConfig),
+    #[serde(rename = "command")]
+    Command(CommandConfig),
+    #[serde(rename = "mock")]
+    Mock,
+}
+
+impl BackendConfig {
+    /// Default options for a backend kind
+    pub fn default_for(kind: TranslatorBackend) -> Self {
+        match kind {
+            TranslatorBackend::OpenAi => Self::OpenAi(OpenAiConfig::default()),
+            TranslatorBackend::DeepL => Self::DeepL(DeepLConfig::default()),
+            TranslatorBackend::LibreTranslate => {
+                Self::LibreTranslate(LibreTranslateConfig::default())
+            }
+            TranslatorBackend::Anthropic => Self::Anthropic(AnthropicConfig::default()),
+            TranslatorBackend::Ollama => Self::Ollama(OllamaConfig::default()),
+            TranslatorBackend::Command => Self::Command(CommandConfig::default()),
+            TranslatorBackend::Mock => Self::Mock,
+        }
+    }
+
+    pub const fn kind(&self) -> TranslatorBackend {
+        match self {
+            Self::OpenAi(_) => TranslatorBackend::OpenAi,
+            Self::DeepL(_) => TranslatorBackend::DeepL,
+            Self::LibreTranslate(_) => TranslatorBackend::LibreTranslate,
+            Self::Anthropic(_) => TranslatorBackend::Anthropic,
+            Self::Ollama(_) => TranslatorBackend::Ollama,
+            Self::Command(_) => TranslatorBackend::Command,
+            Self::Mock => TranslatorBackend::Mock,
+        }
+    }
+
+    /// Check the options before a translator is built from them
+    pub fn validate(&self) -> Result<(), crate::error::Error> {
+        match self {
+            Self::OpenAi(openai) => {
+                validate_url("translator.api_base", &openai.api_base)?;
+                validate_model(&openai.model)?;
+                openai.prompt.validate("translator.prompt")?;
+                if let Some(azure) = &openai.azure {
+                    azure.validate("translator.azure")?;
+                }
+            }
+            Self::DeepL(deepl) => {
+                if deepl
+                    .api_key
+                    .as_deref()
+                    .is_none_or(|key| key.trim().is_empty())
+                {
+                    return Err(crate::error::Error::TranslationMissingApiKey);
+                }
+                if let Some(api_base) = &deepl.api_base {
+                    validate_url("translator.api_base", api_base)?;
+                }
+            }
+            Self::LibreTranslate(libre) => {
+                validate_url("translator.api_base", &libre.api_base)?;
+            }
+            Self::Anthropic(anthropic) => {
+                if anthropic
+                    .api_key
+                    .as_deref()
+                    .is_none_or(|key| key.trim().is_empty())
+                {
+                    return Err(crate::error::Error::TranslationMissingApiKey);
+                }
+                validate_url("translator.api_base", &anthropic.api_base)?;
+                validate_model(&anthropic.model)?;
+                if anthropic.anthropic_version.trim().is_empty() {
+                    return Err(crate::error::Error::ConfigInvalid {
+                        field: "translator.anthropic_version".to_string(),
+                        reason: "must not be empty".to_string(),
+                    });
+                }
+                anthropic.prompt.validate("translator.prompt")?;
+                // The Messages API samples at temperatures up to 1 only.
+                if anthropic
+                    .prompt
+                    .temperature
+                    .is_some_and(|temperature| temperature > 1.0)
+                {
+                    return Err(crate::error::Error::ConfigInvalid {
+                        field: "translator.prompt.temperature".to_string(),
+                        reason: "must be between 0 and 1".to_string(),
+                    });
+                }
+            }
+            Self::Ollama(ollama) => {
+                validate_url("translator.api_base", &ollama.api_base)?;
+                validate_model(&ollama.model)?;
+                if ollama.num_ctx == Some(0) {
+                    return Err(crate::error::Error::ConfigInvalid {
+                        field: "translator.num_ctx".to_string(),
+                        reason: "must be greater than 0".to_string(),
+                    });
+                }
+                ollama.prompt.validate("translator.prompt")?;
+            }
+            Self::Command(command) => {
+                let invalid = |name: &str, reason: &str| crate::error::Error::ConfigInvalid {
+                    field: format!("translator.{name}"),
+                    reason: reason.to_string(),
+                };
+                if command.program.as_os_str().is_empty() {
+                    return Err(invalid("program", "must not be empty"));
+                }
+                if command.timeout_seconds == 0 {
+                    return Err(invalid("timeout_seconds", "must be greater than 0"));
+                }
+                if command.max_concurrent == 0 {
+                    return Err(invalid("max_concurrent", "must be greater than 0"));
+                }
+            }
+            Self::Mock => {}
+        }
+        Ok(())
+    }
+}
+
+impl Default for BackendConfig {
+    fn default() -> Self {
+        Self::OpenAi(OpenAiConfig::default())
+    }
+}
+
+fn validate_model(model: &str) -> Result<(), crate::error::Error> {
+    if model.trim().is_empty() {
+        return Err(crate::error::Error::ConfigInvalid {
+            field: "translator.model".to_string(),
+            reason: "must not be empty".to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn validate_url(field: &str, value: &str) -> Result<(), crate::error::Error> {
+    let invalid = |reason: &str| crate::error::Error::ConfigInvalid {
+        field: field.to_string(),
+        reason: reason.to_string(),
+    };
+    let url = reqwest::Url::parse(value.trim()).map_err(|_| invalid("must be a valid URL"))?;
+    if !matches!(url.scheme(), "http" | "https") {
+        return Err(invalid("must be an http or https URL"));
+    }
+    Ok(())
+}
+
+/// HTTP client options for the network backends (`[translator.http]`).
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HttpClientConfig {
+    /// Seconds allowed to establish a connection
+    #[serde(default = "default_connect_timeout_seconds")]
+    pub connect_timeout_seconds: u64,
+
+    /// Seconds allowed between two reads of a response (unset = no limit)
+    #[serde(default)]
+    pub read_timeout_seconds: Option<u64>,
+
+    /// Seconds allowed for a whole request, including the response body
+    #[serde(default = "default_timeout_seconds")]
+    pub timeout_seconds: u64,
+
+    /// Proxy URL used for every request (e.g. `http://proxy:3128`)
+    #[serde(default)]
+    pub proxy: Option<String>,
+
+    /// Extra PEM files of root certificates to trust
+    #[serde(default)]
+    pub ca_certificates: Vec<PathBuf>,
+
+    /// PEM client certificate for mutual TLS, used with `client_key`
+    #[serde(default)]
+    pub client_certificate: Option<PathBuf>,
+
+    /// PEM (PKCS #8) private key of `client_certificate`
+    #[serde(default)]
+    pub client_key: Option<PathBuf>,
+
+    /// Headers sent with every request (e.g. `api-version`, organization IDs)
+    #[serde(default)]
+    pub headers: BTreeMap<String, String>,
+}
+
+const fn default_connect_timeout_seconds() -> u64 {
+    10
+}
+
+const fn default_timeout_seconds() -> u64 {
+    60
+}
+
+impl Default for HttpClientConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout_seconds: default_connect_timeout_seconds(),
+            read_timeout_seconds: None,
+            timeout_seconds: default_timeout_seconds(),
+            proxy: None,
+            ca_certificates: Vec::new(),
+            client_certificate: None,
+            client_key: None,
+            headers: BTreeMap::new(),
+        }
+    }
+}
+
+/// Translator backend configuration.
+///
+/// The backend is chosen by the `backend` field (`openai`, `deepl`,
+/// `libretranslate`, `anthropic`, `ollama`, `command`, or `mock`); files
+/// without one use `openai`.
+#[derive(Debug, Clone, Serialize)]
+pub struct TranslatorConfig {
+    #[serde(flatten)]
+    pub backend: BackendConfig,
     pub retry_count: u32,
-    #[serde(default = "default_retry_delay_ms")]
     pub retry_delay_ms: u64,
+    pub http: HttpClientConfig,
 }
 
 impl TranslatorConfig {
-    /// Create a new translator config
+    /// Create a new OpenAI-compatible translator config
     pub fn new(
         api_base: impl Into<String>,
         api_key: Option<String>,
         model: impl Into<String>,
     ) -> Self {
         Self {
-            api_base: api_base.into(),
-            api_key,
-            model: model.into(),
+            backend: BackendConfig::OpenAi(OpenAiConfig {
+                api_base: api_base.into(),
+                api_key,
+                model: model.into(),
+                prompt: PromptConfig::default(),
+                azure: None,
+            }),
             retry_count: default_retry_count(),
             retry_delay_ms: default_retry_delay_ms(),
+            http: HttpClientConfig::default(),
         }
     }
 }
 
+impl<'de> Deserialize<'de> for TranslatorConfig {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        struct Tagged {
+            #[serde(flatten)]
+            backend: BackendConfig,
+            #[serde(default = "default_retry_count")]
+            retry_count: u32,
+            #[serde(default = "default_retry_delay_ms")]
+            retry_delay_ms: u64,
+            #[serde(default)]
+            http: HttpClientConfig,
+        }
+
+        // Configs written before backends were selectable have no tag.
+        let mut table = toml::Table::deserialize(deserializer)?;
+        table
+            .entry("backend")
+            .or_insert_with(|| TranslatorBackend::OpenAi.as_str().into());
+        let tagged =
+            Tagged::deserialize(toml::Value::Table(table)).map_err(serde::de::Error::custom)?;
+
+        Ok(Self {
+            backend: tagged.backend,
+            retry_count: tagged.retry_count,
+            retry_delay_ms: tagged.retry_delay_ms,
+            http: tagged.http,
+        })
+    }
+}
+
+fn default_api_base() -> String {
+    "http://localhost:8080/v1".to_string()
+}
+
+fn default_model() -> String {
+    "default_model".to_string()
+}
+
+fn default_libretranslate_api_base() -> String {
+    "http://localhost:5000".to_string()
+}
+
+fn default_anthropic_api_base() -> String {
+    "https://api.anthropic.com".to_string()
+}
+
+fn default_anthropic_version() -> String {
+    DEFAULT_ANTHROPIC_VERSION.to_string()
+}
+
+fn default_ollama_api_base() -> String {
+    "http://localhost:11434".to_string()
+}
+
 const fn default_retry_count() -> u32 {
     3
 }
@@ -190,11 +902,10 @@ const fn default_retry_delay_ms() -> u64 {
 impl Default for TranslatorConfig {
     fn default() -> Self {
         Self {
-            api_base: "http://localhost:8080/v1".to_string(),
-            api_key: None,
-            model: "default_model".to_string(),
+            backend: BackendConfig::default(),
             retry_count: default_retry_count(),
             retry_delay_ms: default_retry_delay_ms(),
+            http: HttpClientConfig::default(),
         }
     }
 }
@@ -220,6 +931,11 @@ pub struct CacheConfig {
 
     /// Disk cache directory (defaults to .cache/pdf-translator)
     pub disk_path: Option<PathBuf>,
+
+    /// Cache rendered pages; when disabled, only the translated text of each
+    /// block is cached and pages are rendered again from it
+    #[serde(default = "default_true")]
+    pub pages_enabled: bool,
 }
 
 const fn default_true() -> bool {
@@ -238,10 +954,342 @@ impl Default for CacheConfig {
             memory_ttl_seconds: 0,
             disk_enabled: true,
             disk_path: None,
+            pages_enabled: true,
+        }
+    }
+}
+
+/// Backends tried in order when the primary translator fails
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FallbackConfig {
+    /// Fallback backends, tried after `[translator]` in the order listed
+    #[serde(default)]
+    pub backends: Vec<TranslatorConfig>,
+
+    /// Consecutive failures before a backend is skipped
+    #[serde(default = "default_failure_threshold")]
+    pub failure_threshold: u32,
+
+    /// Seconds a failing backend is skipped before a probe request is allowed
+    #[serde(default = "default_cooldown_seconds")]
+    pub cooldown_seconds: u64,
+}
+
+const fn default_failure_threshold() -> u32 {
+    3
+}
+
+const fn default_cooldown_seconds() -> u64 {
+    60
+}
+
+impl Default for FallbackConfig {
+    fn default() -> Self {
+        Self {
+            backends: Vec::new(),
+            failure_threshold: default_failure_threshold(),
+            cooldown_seconds: default_cooldown_seconds(),
+        }
+    }
+}
+
+/// Price of one model, per million tokens
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ModelPrice {
+    /// Price per million prompt tokens
+    #[serde(default)]
+    pub prompt_per_million: f64,
+    /// Price per million completion tokens
+    #[serde(default)]
+    pub completion_per_million: f64,
+}
+
+impl ModelPrice {
+    /// Cost of the given token usage
+    #[allow(clippy::cast_precision_loss)] // Token counts stay far below 2^52.
+    pub const fn cost(&self, usage: TokenUsage) -> f64 {
+        (usage.prompt_tokens as f64).mul_add(
+            self.prompt_per_million,
+            usage.completion_tokens as f64 * self.completion_per_million,
+        ) / 1_000_000.0
+    }
+}
+
+/// Price table used to estimate the cost of translations
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PricingConfig {
+    /// Currency the prices are given in, shown next to estimates
+    #[serde(default = "default_currency")]
+    pub currency: String,
+
+    /// Prices keyed by model name (e.g. `[pricing.models."gpt-4o-mini"]`)
+    #[serde(default)]
+    pub models: BTreeMap<String, ModelPrice>,
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+impl Default for PricingConfig {
+    fn default() -> Self {
+        Self {
+            currency: default_currency(),
+            models: BTreeMap::new(),
+        }
+    }
+}
+
+impl PricingConfig {
+    /// Estimated cost of `usage` on `model`, or `None` when the model has no price
+    pub fn estimate(&self, model: &str, usage: TokenUsage) -> Option<f64> {
+        self.models.get(model).map(|price| price.cost(usage))
+    }
+
+    /// Format a cost with the configured currency
+    pub fn format_cost(&self, cost: f64) -> String {
+        format!("{cost:.4} {}", self.currency)
+    }
+}
+
+/// Per-document limits that stop translation once reached.
+///
+/// Limits are checked before each page is sent, counting the estimated usage
+/// of pages still in flight, and hold across every translator sharing one
+/// cache. A page may still finish past a limit when it costs more than
+/// estimated. Pages translated before the stop stay cached.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    /// Maximum prompt and completion tokens billed for one document
+    #[serde(default)]
+    pub max_tokens_per_document: Option<u64>,
+
+    /// Maximum translator requests sent for one document
+    #[serde(default)]
+    pub max_requests_per_document: Option<u64>,
+
+    /// Maximum estimated cost of one document, in the `[pricing]` currency
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+}
+
+impl BudgetConfig {
+    /// Check the limits against the price table they rely on
+    pub fn validate(&self, pricing: &PricingConfig) -> Result<(), crate::error::Error> {
+        if let Some(max_cost) = self.max_cost {
+            let invalid = |reason: &str| crate::error::Error::ConfigInvalid {
+                field: "budget.max_cost".to_string(),
+                reason: reason.to_string(),
+            };
+            if !max_cost.is_finite() || max_cost < 0.0 {
+                return Err(invalid("must be a non-negative number"));
+            }
+            if pricing.models.is_empty() {
+                return Err(invalid("requires model prices under [pricing.models]"));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Client-side pacing of translator requests.
+///
+/// One limiter is built per configuration and, in the web server, shared by
+/// every session and batch job, so they stay under the upstream's quota
+/// together instead of each reacting to HTTP 429 on its own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitConfig {
+    /// Maximum requests sent per minute
+    #[serde(default)]
+    pub requests_per_minute: Option<u32>,
+
+    /// Maximum prompt and completion tokens per minute, estimated before
+    /// each request and corrected from the reported usage
+    #[serde(default)]
+    pub tokens_per_minute: Option<u32>,
+}
+
+impl RateLimitConfig {
+    /// Check that every configured limit allows some traffic
+    pub fn validate(&self) -> Result<(), crate::error::Error> {
+        for (field, limit) in [
+            ("rate_limit.requests_per_minute", self.requests_per_minute),
+            ("rate_limit.tokens_per_minute", self.tokens_per_minute),
+        ] {
+            if limit == Some(0) {
+                return Err(crate::error::Error::ConfigInvalid {
+                    field: field.to_string(),
+                    reason: "must be greater than 0".to_string(),
+                });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Checks run on every translated block (`[quality]`).
+///
+/// Chat-model backends re-ask a block that fails an enabled check, telling the
+/// model what was wrong, up to `max_reasks` times; whatever still fails is
+/// logged with the page number.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[allow(clippy::struct_excessive_bools)] // Each check is toggled independently.
+pub struct QualityConfig {
+    /// Flag answers identical to the source, not written in the target script,
+    /// or detected as another language than the target
+    #[serde(default = "default_true")]
+    pub untranslated: bool,
+
+    /// Flag answers opening with chatter such as "Here is the translation:"
+    #[serde(default = "default_true")]
+    pub preamble: bool,
+
+    /// Flag answers missing numbers, URLs, or email addresses of the source
+    #[serde(default = "default_true")]
+    pub preserved_tokens: bool,
+
+    /// Flag answers much shorter or longer than the source
+    #[serde(default = "default_true")]
+    pub length_ratio: bool,
+
+    /// Smallest accepted answer length relative to the source
+    #[serde(default = "default_min_length_ratio")]
+    pub min_length_ratio: f32,
+
+    /// Largest accepted answer length relative to the source
+    #[serde(default = "default_max_length_ratio")]
+    pub max_length_ratio: f32,
+
+    /// Extra requests allowed per block to fix a failed check (0 = only flag)
+    #[serde(default = "default_max_reasks")]
+    pub max_reasks: u32,
+}
+
+const fn default_min_length_ratio() -> f32 {
+    0.25
+}
+
+const fn default_max_length_ratio() -> f32 {
+    4.0
+}
+
+const fn default_max_reasks() -> u32 {
+    1
+}
+
+impl Default for QualityConfig {
+    fn default() -> Self {
+        Self {
+            untranslated: true,
+            preamble: true,
+            preserved_tokens: true,
+            length_ratio: true,
+            min_length_ratio: default_min_length_ratio(),
+            max_length_ratio: default_max_length_ratio(),
+            max_reasks: default_max_reasks(),
+        }
+    }
+}
+
+impl QualityConfig {
+    /// Check that the length bounds describe a non-empty range
+    pub fn validate(&self) -> Result<(), crate::error::Error> {
+        if !(self.min_length_ratio > 0.0 && self.min_length_ratio.is_finite()) {
+            return Err(crate::error::Error::ConfigInvalid {
+                field: "quality.min_length_ratio".to_string(),
+                reason: "must be greater than 0".to_string(),
+            });
+        }
+        if !(self.max_length_ratio > self.min_length_ratio && self.max_length_ratio.is_finite()) {
+            return Err(crate::error::Error::ConfigInvalid {
+                field: "quality.max_length_ratio".to_string(),
+                reason: "must be greater than min_length_ratio".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Spans kept verbatim through translation (`[protect]`).
+///
+/// Matches are replaced by numbered placeholders before a block is sent and
+/// restored in the answer; a translation that loses a placeholder is rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProtectConfig {
+    /// Mask spans at all
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+
+    /// Include the built-in patterns for URLs, email addresses, ISBNs, inline
+    /// code, code identifiers, and numbered references such as "Art. 12.3"
+    #[serde(default = "default_true")]
+    pub builtin: bool,
+
+    /// Additional regular expressions; when a pattern has a capture group,
+    /// only the first group is masked
+    #[serde(default)]
+    pub patterns: Vec<String>,
+}
+
+impl Default for ProtectConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            builtin: true,
+            patterns: Vec::new(),
         }
     }
 }
 
+/// Segment-level memory of past translations (`[translation_memory]`).
+///
+/// Blocks found in the memory are reused without calling the translator,
+/// across documents and sessions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranslationMemoryConfig {
+    /// Look up and store translated blocks. Off by default, so nothing is
+    /// written to the memory file unless asked for.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Memory file (defaults to ~/.local/share/pdf-translator/translation-memory.jsonl)
+    #[serde(default)]
+    pub path: Option<PathBuf>,
+
+    /// Smallest edit-distance similarity accepted for a fuzzy match. Fuzzy
+    /// matches are reused as is, so they are opt-in: the default 1.0 only
+    /// reuses exact matches.
+    #[serde(default = "default_min_similarity")]
+    pub min_similarity: f32,
+}
+
+const fn default_min_similarity() -> f32 {
+    1.0
+}
+
+impl Default for TranslationMemoryConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: None,
+            min_similarity: default_min_similarity(),
+        }
+    }
+}
+
+impl TranslationMemoryConfig {
+    /// Check that the similarity threshold is a share between 0 and 1
+    pub fn validate(&self) -> Result<(), crate::error::Error> {
+        if !(self.min_similarity > 0.0 && self.min_similarity <= 1.0) {
+            return Err(crate::error::Error::ConfigInvalid {
+                field: "translation_memory.min_similarity".to_string(),
+                reason: "must be greater than 0 and at most 1".to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
 /// Application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AppConfig {
@@ -272,6 +1320,46 @@ pub struct AppConfig {
     /// Pages to load per batch in web UI
     #[serde(default = "default_pages_per_load")]
     pub pages_per_load: usize,
+
+    /// Maximum number of pages translated concurrently (1 = sequential)
+    #[serde(default = "default_max_concurrent_pages")]
+    pub max_concurrent_pages: usize,
+
+    /// Maximum number of translator requests in flight across concurrent pages
+    #[serde(default = "default_max_in_flight_requests")]
+    pub max_in_flight_requests: usize,
+
+    /// Glossary file (TOML or CSV) of fixed term translations
+    #[serde(default)]
+    pub glossary: Option<PathBuf>,
+
+    /// Fallback translators used when the primary backend is unavailable
+    #[serde(default)]
+    pub fallback: FallbackConfig,
+
+    /// Model prices used to estimate translation cost
+    #[serde(default)]
+    pub pricing: PricingConfig,
+
+    /// Per-document token, request, and cost limits
+    #[serde(default)]
+    pub budget: BudgetConfig,
+
+    /// Request and token rate limits for translator backends
+    #[serde(default)]
+    pub rate_limit: RateLimitConfig,
+
+    /// Checks run on translated blocks
+    #[serde(default)]
+    pub quality: QualityConfig,
+
+    /// Spans masked with placeholders before translation
+    #[serde(default)]
+    pub protect: ProtectConfig,
+
+    /// Translated blocks reused across documents
+    #[serde(default)]
+    pub translation_memory: TranslationMemoryConfig,
 }
 
 const fn default_render_scale() -> f32 {
@@ -282,6 +1370,14 @@ const fn default_pages_per_load() -> usize {
     2
 }
 
+const fn default_max_concurrent_pages() -> usize {
+    1
+}
+
+const fn default_max_in_flight_requests() -> usize {
+    4
+}
+
 impl Default for AppConfig {
     fn default() -> Self {
         Self {
@@ -292,6 +1388,16 @@ impl Default for AppConfig {
             cache: CacheConfig::default(),
             render_scale: default_render_scale(),
             pages_per_load: default_pages_per_load(),
+            max_concurrent_pages: default_max_concurrent_pages(),
+            max_in_flight_requests: default_max_in_flight_requests(),
+            glossary: None,
+            fallback: FallbackConfig::default(),
+            pricing: PricingConfig::default(),
+            budget: BudgetConfig::default(),
+            rate_limit: RateLimitConfig::default(),
+            quality: QualityConfig::default(),
+            protect: ProtectConfig::default(),
+            translation_memory: TranslationMemoryConfig::default(),
         }
     }
 }
@@ -311,6 +1417,14 @@ impl AppConfig {
             .map_err(|e| crate::error::Error::ConfigLoad(format!("Failed to parse config: {e}")))
     }
 
+    /// Load the configured glossary, or an empty one when none is set
+    pub fn load_glossary(&self) -> Result<crate::glossary::Glossary, crate::error::Error> {
+        self.glossary.as_ref().map_or_else(
+            || Ok(crate::glossary::Glossary::default()),
+            crate::glossary::Glossary::from_file,
+        )
+    }
+
     /// Load from default locations (~/.config/pdf-translator/config.toml, ./config.toml)
     pub fn load() -> Self {
         // Try user config
@@ -485,3 +1599,386 @@ pub fn color_options() -> Vec<(&'static str, TextColor)> {
         ("Purple", TextColor::purple()),
     ]
 }
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn untagged_translator_config_defaults_to_openai() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [translator]
+            api_base = "https://api.openai.com/v1"
+            model = "gpt-4o-mini"
+            retry_count = 5
+            "#,
+        )
+        .unwrap();
+
+        let BackendConfig::OpenAi(openai) = &config.translator.backend else {
+            unreachable!("expected the openai backend");
+        };
+        assert_eq!(openai.api_base, "https://api.openai.com/v1");
+        assert_eq!(openai.model, "gpt-4o-mini");
+        assert_eq!(config.translator.retry_count, 5);
+        assert_eq!(config.translator.retry_delay_ms, default_retry_delay_ms());
+    }
+
+    #[test]
+    fn fallback_backends_parse_in_order() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [fallback]
+            failure_threshold = 5
+
+            [[fallback.backends]]
+            backend = "libretranslate"
+
+            [[fallback.backends]]
+            api_base = "https://api.openai.com/v1"
+            "#,
+        )
+        .unwrap();
+
+        let kinds: Vec<_> = config
+            .fallback
+            .backends
+            .iter()
+            .map(|backend| backend.backend.kind())
+            .collect();
+        assert_eq!(
+            kinds,
+            vec![TranslatorBackend::LibreTranslate, TranslatorBackend::OpenAi]
+        );
+        assert_eq!(config.fallback.failure_threshold, 5);
+        assert_eq!(config.fallback.cooldown_seconds, default_cooldown_seconds());
+        assert!(AppConfig::default().fallback.backends.is_empty());
+    }
+
+    #[test]
+    fn tagged_translator_config_selects_backend_options() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [translator]
+            backend = "deepl"
+            api_key = "key:fx"
+            formality = "prefer_less"
+            "#,
+        )
+        .unwrap();
+
+        let BackendConfig::DeepL(deepl) = &config.translator.backend else {
+            unreachable!("expected the deepl backend");
+        };
+        assert_eq!(deepl.api_key.as_deref(), Some("key:fx"));
+        assert_eq!(deepl.formality, Some(DeepLFormality::PreferLess));
+
+        let config: AppConfig = toml::from_str("[translator]\nbackend = \"mock\"\n").unwrap();
+        assert_eq!(config.translator.backend.kind(), TranslatorBackend::Mock);
+    }
+
+    #[test]
+    fn prompt_table_parses_and_validates() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [translator]
+            model = "gpt-4o-mini"
+
+            [translator.prompt]
+            system = "You translate into {target}."
+            user = "{text}"
+            temperature = 0.0
+            max_tokens = 512
+
+            [translator.prompt.extra_body]
+            repeat_penalty = 1.1
+            "#,
+        )
+        .unwrap();
+
+        let BackendConfig::OpenAi(openai) = &config.translator.backend else {
+            unreachable!("expected the openai backend");
+        };
+        assert_eq!(openai.prompt.temperature, Some(0.0));
+        assert_eq!(openai.prompt.max_tokens, Some(512));
+        assert!(openai.prompt.extra_body.contains_key("repeat_penalty"));
+        assert!(config.translator.backend.validate().is_ok());
+
+        let missing_text = PromptConfig {
+            user: Some("Translate to {target}".to_string()),
+            ..PromptConfig::default()
+        };
+        assert!(missing_text.validate("translator.prompt").is_err());
+
+        let mut overriding = PromptConfig::default();
+        overriding
+            .extra_body
+            .insert("model".to_string(), "other".into());
+        assert!(overriding.validate("translator.prompt").is_err());
+    }
+
+    #[test]
+    fn pricing_table_estimates_cost_per_model() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [pricing.models."gpt-4o-mini"]
+            prompt_per_million = 0.15
+            completion_per_million = 0.6
+            "#,
+        )
+        .unwrap();
+
+        let usage = TokenUsage {
+            prompt_tokens: 2_000_000,
+            completion_tokens: 500_000,
+            requests: 1,
+        };
+        let cost = config.pricing.estimate("gpt-4o-mini", usage).unwrap();
+        assert!((cost - 0.6).abs() < 1e-9);
+        assert_eq!(config.pricing.currency, "USD");
+        assert!(config.pricing.estimate("other", usage).is_none());
+    }
+
+    #[test]
+    fn budget_cost_limit_requires_prices() {
+        let config: AppConfig = toml::from_str(
+            r"
+            [budget]
+            max_tokens_per_document = 100000
+            max_cost = 2.5
+            ",
+        )
+        .unwrap();
+        assert_eq!(config.budget.max_tokens_per_document, Some(100_000));
+        assert!(matches!(
+            config.budget.validate(&config.pricing),
+            Err(crate::error::Error::ConfigInvalid { .. })
+        ));
+
+        let mut pricing = PricingConfig::default();
+        pricing
+            .models
+            .insert("gpt-4o-mini".to_string(), ModelPrice::default());
+        assert!(config.budget.validate(&pricing).is_ok());
+    }
+
+    #[test]
+    fn azure_table_selects_a_deployment() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [translator]
+            api_base = "https://my-resource.openai.azure.com"
+            model = "gpt-4o-mini"
+
+            [translator.azure]
+            deployment = "team-mini"
+            "#,
+        )
+        .unwrap();
+        let BackendConfig::OpenAi(openai) = &config.translator.backend else {
+            unreachable!("expected the openai backend");
+        };
+        let azure = openai.azure.as_ref().unwrap();
+        assert_eq!(azure.deployment, "team-mini");
+        assert_eq!(azure.api_version, DEFAULT_AZURE_API_VERSION);
+        assert!(config.translator.backend.validate().is_ok());
+
+        let blank = BackendConfig::OpenAi(OpenAiConfig {
+            azure: Some(AzureOpenAiConfig {
+                deployment: " ".to_string(),
+                api_version: DEFAULT_AZURE_API_VERSION.to_string(),
+            }),
+            ..OpenAiConfig::default()
+        });
+        assert!(matches!(
+            blank.validate(),
+            Err(crate::error::Error::ConfigInvalid { field, .. })
+                if field == "translator.azure.deployment"
+        ));
+    }
+
+    #[test]
+    fn anthropic_and_ollama_backends_parse_and_validate() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [translator]
+            backend = "ollama"
+            model = "llama3"
+            keep_alive = "10m"
+            num_ctx = 8192
+            "#,
+        )
+        .unwrap();
+        let BackendConfig::Ollama(ollama) = &config.translator.backend else {
+            unreachable!("expected the ollama backend");
+        };
+        assert_eq!(ollama.api_base, "http://localhost:11434");
+        assert_eq!(ollama.num_ctx, Some(8192));
+        assert!(config.translator.backend.validate().is_ok());
+
+        let anthropic = AnthropicConfig {
+            api_key: Some("key".to_string()),
+            model: "model".to_string(),
+            ..AnthropicConfig::default()
+        };
+        assert_eq!(anthropic.anthropic_version, DEFAULT_ANTHROPIC_VERSION);
+        assert!(
+            BackendConfig::Anthropic(anthropic.clone())
+                .validate()
+                .is_ok()
+        );
+        assert!(matches!(
+            BackendConfig::Anthropic(AnthropicConfig {
+                api_key: None,
+                ..anthropic.clone()
+            })
+            .validate(),
+            Err(crate::error::Error::TranslationMissingApiKey)
+        ));
+        let hot = BackendConfig::Anthropic(AnthropicConfig {
+            prompt: PromptConfig {
+                temperature: Some(1.5),
+                ..PromptConfig::default()
+            },
+            ..anthropic
+        });
+        assert!(matches!(
+            hot.validate(),
+            Err(crate::error::Error::ConfigInvalid { field, .. })
+                if field == "translator.prompt.temperature"
+        ));
+        assert!(
+            BackendConfig::default_for(TranslatorBackend::Ollama)
+                .validate()
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn command_backend_requires_a_program() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [translator]
+            backend = "command"
+            program = "/opt/mt/bin/translate"
+            args = ["--engine", "v2"]
+            "#,
+        )
+        .unwrap();
+        let BackendConfig::Command(command) = &config.translator.backend else {
+            unreachable!("expected the command backend");
+        };
+        assert_eq!(command.args, ["--engine", "v2"]);
+        assert_eq!(command.max_concurrent, 4);
+        assert!(config.translator.backend.validate().is_ok());
+
+        assert!(matches!(
+            BackendConfig::default_for(TranslatorBackend::Command).validate(),
+            Err(crate::error::Error::ConfigInvalid { field, .. }) if field == "translator.program"
+        ));
+    }
+
+    #[test]
+    fn quality_checks_toggle_per_rule() {
+        let config: AppConfig = toml::from_str(
+            r"
+            [quality]
+            preamble = false
+            max_reasks = 0
+            ",
+        )
+        .unwrap();
+        assert!(!config.quality.preamble);
+        assert!(config.quality.untranslated);
+        assert_eq!(config.quality.max_reasks, 0);
+        assert!(config.quality.validate().is_ok());
+
+        let inverted = QualityConfig {
+            min_length_ratio: 2.0,
+            max_length_ratio: 1.0,
+            ..QualityConfig::default()
+        };
+        assert!(matches!(
+            inverted.validate(),
+            Err(crate::error::Error::ConfigInvalid { field, .. })
+                if field == "quality.max_length_ratio"
+        ));
+    }
+
+    #[test]
+    fn http_client_table_parses_per_backend() {
+        let config: AppConfig = toml::from_str(
+            r#"
+            [translator]
+            api_base = "https://api.example.com/v1"
+
+            [translator.http]
+            read_timeout_seconds = 30
+            proxy = "http://proxy:3128"
+            ca_certificates = ["/etc/ssl/corp.pem"]
+
+            [translator.http.headers]
+            api-version = "2024-06-01"
+
+            [[fallback.backends]]
+            backend = "libretranslate"
+            "#,
+        )
+        .unwrap();
+
+        let http = &config.translator.http;
+        assert_eq!(http.connect_timeout_seconds, 10);
+        assert_eq!(http.read_timeout_seconds, Some(30));
+        assert_eq!(http.timeout_seconds, 60);
+        assert_eq!(http.proxy.as_deref(), Some("http://proxy:3128"));
+        assert_eq!(http.ca_certificates, [PathBuf::from("/etc/ssl/corp.pem")]);
+        assert_eq!(http.headers["api-version"], "2024-06-01");
+        assert_eq!(
+            config.fallback.backends[0].http,
+            HttpClientConfig::default()
+        );
+    }
+
+    #[test]
+    fn rate_limits_must_allow_traffic() {
+        let config: AppConfig = toml::from_str(
+            r"
+            [rate_limit]
+            requests_per_minute = 60
+            tokens_per_minute = 0
+            ",
+        )
+        .unwrap();
+        assert_eq!(config.rate_limit.requests_per_minute, Some(60));
+        assert!(matches!(
+            config.rate_limit.validate(),
+            Err(crate::error::Error::ConfigInvalid { field, .. })
+                if field == "rate_limit.tokens_per_minute"
+        ));
+        assert!(RateLimitConfig::default().validate().is_ok());
+    }
+
+    #[test]
+    fn unknown_backend_is_rejected() {
+        assert!(toml::from_str::<AppConfig>("[translator]\nbackend = \"babelfish\"\n").is_err());
+    }
+
+    #[test]
+    fn backend_options_are_validated() {
+        assert!(BackendConfig::default().validate().is_ok());
+        assert!(matches!(
+            BackendConfig::default_for(TranslatorBackend::DeepL).validate(),
+            Err(crate::error::Error::TranslationMissingApiKey)
+        ));
+        assert!(matches!(
+            BackendConfig::LibreTranslate(LibreTranslateConfig {
+                api_base: "localhost:5000".to_string(),
+                api_key: None,
+            })
+            .validate(),
+            Err(crate::error::Error::ConfigInvalid { .. })
+        ));
+    }
+}
@@ -1,5 +1,7 @@
 use thiserror::Error;
 
+use crate::translator::TokenUsage;
+
 #[derive(Error, Debug)]
 pub enum Error {
     // ==========================================================================
@@ -52,6 +54,10 @@ pub enum Error {
     #[error("translation rate limited{}", retry_after.map(|s| format!(", retry after {s} seconds")).unwrap_or_default())]
     TranslationRateLimited { retry_after: Option<u64> },
 
+    /// Translation quota or character allowance exhausted
+    #[error("translation quota exceeded")]
+    TranslationQuotaExceeded,
+
     /// API key not configured for translation service
     #[error("translation API key not configured")]
     TranslationMissingApiKey,
@@ -68,6 +74,14 @@ pub enum Error {
     #[error("translation failed after maximum retries")]
     TranslationMaxRetriesExceeded,
 
+    /// A per-document budget limit was reached; pages translated so far stay cached
+    #[error("translation budget exceeded: {0}")]
+    TranslationBudgetExceeded(String),
+
+    /// A translation failed after some of its requests were already billed
+    #[error("{error}")]
+    TranslationBilled { error: Box<Self>, usage: TokenUsage },
+
     // ==========================================================================
     // Cache Errors
     // ==========================================================================
@@ -102,6 +116,16 @@ pub enum Error {
     #[error("missing required config field: {0}")]
     ConfigMissing(String),
 
+    // ==========================================================================
+    // Interchange Errors
+    // ==========================================================================
+    /// Failed to read a translation interchange file
+    #[error("invalid {format} file: {reason}")]
+    InterchangeFormat {
+        format: &'static str,
+        reason: String,
+    },
+
     // ==========================================================================
     // I/O Errors
     // ==========================================================================
@@ -110,4 +134,55 @@ pub enum Error {
     Io(#[from] std::io::Error),
 }
 
+impl Error {
+    /// Attach `usage` billed before this error, so the caller can still
+    /// charge it. Usage already attached is kept and added to.
+    #[must_use]
+    pub fn with_usage(self, usage: TokenUsage) -> Self {
+        if usage.is_empty() {
+            return self;
+        }
+        match self {
+            Self::TranslationBilled {
+                error,
+                usage: mut billed,
+            } => {
+                billed += usage;
+                Self::TranslationBilled {
+                    error,
+                    usage: billed,
+                }
+            }
+            error => Self::TranslationBilled {
+                error: Box::new(error),
+                usage,
+            },
+        }
+    }
+
+    /// Usage billed before this error.
+    pub fn billed_usage(&self) -> TokenUsage {
+        match self {
+            Self::TranslationBilled { usage, .. } => *usage,
+            _ => TokenUsage::default(),
+        }
+    }
+
+    /// The error behind any billed usage.
+    pub fn cause(&self) -> &Self {
+        match self {
+            Self::TranslationBilled { error, .. } => error,
+            error => error,
+        }
+    }
+
+    /// Split off the billed usage, returning the error behind it.
+    pub fn into_unbilled(self) -> (Self, TokenUsage) {
+        match self {
+            Self::TranslationBilled { error, usage } => (*error, usage),
+            error => (error, TokenUsage::default()),
+        }
+    }
+}
+
 pub type Result<T> = std::result::Result<T, Error>;
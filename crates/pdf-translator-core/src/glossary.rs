@@ -0,0 +1,361 @@
+//! Terminology glossary applied to every translated block.
+//!
+//! A glossary pins the translation of recurring terms (and lists terms that must
+//! stay untranslated) so a long document renders them consistently. It can be
+//! loaded from TOML:
+//!
+//! ```toml
+//! do_not_translate = ["ACME Corp"]
+//!
+//! [terms]
+//! "contrat de travail" = "employment contract"
+//! ```
+//!
+//! or from CSV with one `source,target` row per term, where an empty target
+//! marks a do-not-translate entry.
+
+use crate::error::{Error, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::path::Path;
+
+/// A glossary term and the rendering the translation must use.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlossaryEntry<'a> {
+    /// Term as it appears in the source text
+    pub source: &'a str,
+    /// Required rendering, or `None` when the term must be kept as written
+    pub target: Option<&'a str>,
+}
+
+impl GlossaryEntry<'_> {
+    /// Text the translation is expected to contain for this term.
+    pub fn expected(&self) -> &str {
+        self.target.unwrap_or(self.source)
+    }
+}
+
+/// A glossary term that was present in the source but not honoured.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GlossaryViolation {
+    /// Term found in the source text
+    pub source: String,
+    /// Rendering that was missing from the translation
+    pub expected: String,
+}
+
+/// Source→target term mapping with do-not-translate entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Glossary {
+    /// Terms keyed by source text; `None` targets are kept untranslated.
+    terms: BTreeMap<String, Option<String>>,
+    fingerprint: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct GlossaryFile {
+    #[serde(default)]
+    terms: BTreeMap<String, String>,
+    #[serde(default)]
+    do_not_translate: Vec<String>,
+}
+
+impl Glossary {
+    /// Build a glossary from source→target pairs; `None` targets are kept untranslated.
+    ///
+    /// Later duplicates of a source term replace earlier ones.
+    pub fn new<I, S>(terms: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = (S, Option<S>)>,
+        S: Into<String>,
+    {
+        let mut map = BTreeMap::new();
+        for (source, target) in terms {
+            let source = source.into().trim().to_string();
+            if source.is_empty() {
+                return Err(Error::ConfigInvalid {
+                    field: "glossary".to_string(),
+                    reason: "glossary terms must not be empty".to_string(),
+                });
+            }
+            let target = target
+                .map(|target| target.into().trim().to_string())
+                .filter(|target| !target.is_empty());
+            map.insert(source, target);
+        }
+
+        let fingerprint = Self::compute_fingerprint(&map);
+        Ok(Self {
+            terms: map,
+            fingerprint,
+        })
+    }
+
+    /// Load a glossary file, choosing the format from its extension (`.csv` or TOML).
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let content = std::fs::read_to_string(path).map_err(|e| {
+            Error::ConfigLoad(format!("Failed to read glossary {}: {}", path.display(), e))
+        })?;
+
+        let is_csv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+        if is_csv {
+            Self::from_csv_str(&content)
+        } else {
+            Self::from_toml_str(&content)
+        }
+    }
+
+    /// Parse a TOML glossary with a `[terms]` table and a `do_not_translate` list.
+    pub fn from_toml_str(content: &str) -> Result<Self> {
+        let file: GlossaryFile = toml::from_str(content)
+            .map_err(|e| Error::ConfigLoad(format!("Failed to parse glossary: {e}")))?;
+
+        let keep = file.do_not_translate.into_iter().map(|term| (term, None));
+        Self::new(
+            file.terms
+                .into_iter()
+                .map(|(source, target)| (source, Some(target)))
+                .chain(keep),
+        )
+    }
+
+    /// Parse a CSV glossary of `source,target` rows.
+    ///
+    /// A row with an empty or missing target is a do-not-translate entry. Lines
+    /// starting with `#` and an optional `source,target` header are skipped.
+    pub fn from_csv_str(content: &str) -> Result<Self> {
+        let mut reader = csv::ReaderBuilder::new()
+            .has_headers(false)
+            .flexible(true)
+            .comment(Some(b'#'))
+            .trim(csv::Trim::All)
+            .from_reader(content.as_bytes());
+
+        let mut terms = Vec::new();
+        for (index, record) in reader.records().enumerate() {
+            let record =
+                record.map_err(|e| Error::ConfigLoad(format!("Failed to parse glossary: {e}")))?;
+            let source = record.get(0).unwrap_or_default();
+            let target = record.get(1).unwrap_or_default();
+            if index == 0
+                && source.eq_ignore_ascii_case("source")
+                && target.eq_ignore_ascii_case("target")
+            {
+                continue;
+            }
+            if source.is_empty() && target.is_empty() {
+                continue;
+            }
+            terms.push((source.to_string(), Some(target.to_string())));
+        }
+        Self::new(terms)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.terms.is_empty()
+    }
+
+    pub fn len(&self) -> usize {
+        self.terms.len()
+    }
+
+    /// Stable hash of the glossary contents, used to partition cache entries.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Iterate over all entries in source-term order.
+    pub fn entries(&self) -> impl Iterator<Item = GlossaryEntry<'_>> {
+        self.terms.iter().map(|(source, target)| GlossaryEntry {
+            source,
+            target: target.as_deref(),
+        })
+    }
+
+    /// Entries whose source term occurs in any of the given texts.
+    pub fn relevant_entries<'a>(&'a self, texts: &[&str]) -> Vec<GlossaryEntry<'a>> {
+        self.entries()
+            .filter(|entry| texts.iter().any(|text| contains_term(text, entry.source)))
+            .collect()
+    }
+
+    /// Prompt instructions covering the terms used in `texts`, or an empty string.
+    pub fn prompt_instructions(&self, texts: &[&str]) -> String {
+        let entries = self.relevant_entries(texts);
+        if entries.is_empty() {
+            return String::new();
+        }
+
+        let mut instructions =
+            String::from("Use this glossary and always render these terms exactly as given:\n");
+        for entry in entries {
+            let _ = match entry.target {
+                Some(target) => writeln!(instructions, "- \"{}\" => \"{target}\"", entry.source),
+                None => writeln!(instructions, "- \"{}\" => keep untranslated", entry.source),
+            };
+        }
+        instructions
+    }
+
+    /// Glossary terms present in `source` whose rendering is missing from `translated`.
+    pub fn violations(&self, source: &str, translated: &str) -> Vec<GlossaryViolation> {
+        self.relevant_entries(&[source])
+            .into_iter()
+            .filter(|entry| !contains_term(translated, entry.expected()))
+            .map(|entry| GlossaryViolation {
+                source: entry.source.to_string(),
+                expected: entry.expected().to_string(),
+            })
+            .collect()
+    }
+
+    fn compute_fingerprint(terms: &BTreeMap<String, Option<String>>) -> String {
+        fn consume_field(context: &mut md5::Context, value: &[u8]) {
+            context.consume((value.len() as u64).to_be_bytes());
+            context.consume(value);
+        }
+
+        let mut context = md5::Context::new();
+        consume_field(&mut context, b"pdf-translator-glossary-v1");
+        for (source, target) in terms {
+            consume_field(&mut context, source.as_bytes());
+            match target {
+                Some(target) => {
+                    context.consume([1]);
+                    consume_field(&mut context, target.as_bytes());
+                }
+                None => context.consume([0]),
+            }
+        }
+        format!("{:x}", context.compute())
+    }
+}
+
+/// Case-insensitive whole-word search for `term` in `text`.
+fn contains_term(text: &str, term: &str) -> bool {
+    let text = text.to_lowercase();
+    let term = term.to_lowercase();
+    if term.is_empty() {
+        return false;
+    }
+
+    text.match_indices(&term).any(|(start, matched)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + matched.len()..].chars().next();
+        !before.is_some_and(char::is_alphanumeric) && !after.is_some_and(char::is_alphanumeric)
+    })
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toml_glossary_loads_terms_and_do_not_translate() {
+        let glossary = Glossary::from_toml_str(
+            r#"
+            do_not_translate = ["ACME Corp"]
+
+            [terms]
+            "contrat de travail" = "employment contract"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            glossary.entries().collect::<Vec<_>>(),
+            vec![
+                GlossaryEntry {
+                    source: "ACME Corp",
+                    target: None,
+                },
+                GlossaryEntry {
+                    source: "contrat de travail",
+                    target: Some("employment contract"),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn csv_glossary_skips_header_and_comments() {
+        let glossary = Glossary::from_csv_str(
+            "source,target\n# legal terms\n\"préavis, légal\",statutory notice\nSIRET,\n",
+        )
+        .unwrap();
+
+        assert_eq!(glossary.len(), 2);
+        assert!(glossary.entries().any(|entry| entry
+            == GlossaryEntry {
+                source: "préavis, légal",
+                target: Some("statutory notice"),
+            }));
+        assert!(glossary.entries().any(|entry| entry
+            == GlossaryEntry {
+                source: "SIRET",
+                target: None,
+            }));
+    }
+
+    #[test]
+    fn empty_terms_are_rejected() {
+        assert!(Glossary::from_csv_str(",target\n").is_err());
+    }
+
+    #[test]
+    fn terms_match_whole_words_case_insensitively() {
+        assert!(contains_term("Le Contrat est signé", "contrat"));
+        assert!(!contains_term("Les contrats sont signés", "contrat"));
+        assert!(contains_term("(contrat)", "contrat"));
+    }
+
+    #[test]
+    fn prompt_lists_only_terms_present_in_the_text() {
+        let glossary = Glossary::new([
+            ("contrat", Some("agreement")),
+            ("ACME", None),
+            ("bail", Some("lease")),
+        ])
+        .unwrap();
+
+        let prompt = glossary.prompt_instructions(&["Le contrat d'ACME"]);
+        assert!(prompt.contains("\"contrat\" => \"agreement\""));
+        assert!(prompt.contains("\"ACME\" => keep untranslated"));
+        assert!(!prompt.contains("lease"));
+        assert!(glossary.prompt_instructions(&["Rien"]).is_empty());
+    }
+
+    #[test]
+    fn violations_report_terms_missing_from_the_translation() {
+        let glossary = Glossary::new([("contrat", Some("agreement")), ("ACME", None)]).unwrap();
+
+        assert!(
+            glossary
+                .violations("Le contrat d'ACME", "The ACME agreement")
+                .is_empty()
+        );
+        assert_eq!(
+            glossary.violations("Le contrat d'ACME", "The Acme contract"),
+            vec![GlossaryViolation {
+                source: "contrat".to_string(),
+                expected: "agreement".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn fingerprint_tracks_contents() {
+        let a = Glossary::new([("contrat", Some("agreement"))]).unwrap();
+        let b = Glossary::new([("contrat", Some("contract"))]).unwrap();
+        let c = Glossary::new([("contrat", None)]).unwrap();
+
+        assert_eq!(a.fingerprint(), a.clone().fingerprint());
+        assert_ne!(a.fingerprint(), b.fingerprint());
+        assert_ne!(a.fingerprint(), c.fingerprint());
+    }
+}
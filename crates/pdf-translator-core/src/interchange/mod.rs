@@ -0,0 +1,56 @@
+//! Exchange of translations with CAT tools and human reviewers.
+
+pub mod project;
+pub mod tmx;
+pub mod xliff;
+
+use quick_xml::events::BytesStart;
+
+use crate::error::{Error, Result};
+use crate::pdf::{OverlayOptions, PdfDocument, PdfOverlay, TranslationOverlay, combine_pdfs};
+
+/// Error for a malformed file of the given format.
+fn invalid(format: &'static str, reason: impl std::fmt::Display) -> Error {
+    Error::InterchangeFormat {
+        format,
+        reason: reason.to_string(),
+    }
+}
+
+/// Unescaped value of the attribute `name` of `element`, if present.
+fn attribute(
+    format: &'static str,
+    element: &BytesStart<'_>,
+    name: &[u8],
+) -> Result<Option<String>> {
+    for attribute in element.attributes() {
+        let attribute = attribute.map_err(|error| invalid(format, error))?;
+        if attribute.key.as_ref() == name {
+            let value = attribute
+                .unescape_value()
+                .map_err(|error| invalid(format, error))?;
+            return Ok(Some(value.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// Render each `(page_num, overlays)` page of `doc` and combine them into one PDF.
+fn render_pages(
+    format: &'static str,
+    doc: &PdfDocument,
+    options: &OverlayOptions,
+    pages: impl IntoIterator<Item = (usize, Vec<TranslationOverlay>)>,
+) -> Result<Vec<u8>> {
+    let overlay = PdfOverlay::new(options.clone());
+    let pages = pages
+        .into_iter()
+        .map(|(page_num, overlays)| {
+            overlay.create_translated_page(doc.bytes(), page_num, &overlays)
+        })
+        .collect::<Result<Vec<_>>>()?;
+    if pages.is_empty() {
+        return Err(invalid(format, "no translated pages"));
+    }
+    combine_pdfs(&pages)
+}
@@ -0,0 +1,237 @@
+//! Translation projects: a JSON file with every block of the translated pages,
+//! its layout, and its translation, meant to be corrected by hand and rendered
+//! again without the translator.
+
+use serde::{Deserialize, Serialize};
+
+use super::{invalid, render_pages};
+use crate::TranslatedPage;
+use crate::config::Lang;
+use crate::error::Result;
+use crate::pdf::{BoundingBox, OverlayOptions, PdfDocument, TextBlock, TranslationOverlay};
+
+const FORMAT: &str = "translation project";
+
+/// Version written to new projects; older files are rejected once it changes.
+pub const PROJECT_VERSION: u32 = 1;
+
+/// How the translation of a block was obtained.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BlockStatus {
+    /// Translated by the backend
+    Translated,
+    /// Reused from the translation memory
+    Remembered,
+    /// Already in the target language; the original text stays on the page
+    Kept,
+    /// Corrected or approved by hand
+    Reviewed,
+    /// Failed validation; the original text stays on the page until corrected
+    Flagged,
+}
+
+/// One text block of a page and its translation.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectBlock {
+    /// Original text
+    pub text: String,
+    /// Position in PDF coordinates: `[x0, y0, x1, y1]`
+    pub bbox: [f32; 4],
+    /// Font size of the original text
+    pub font_size: f32,
+    /// Text rendered over the original (unused for kept blocks)
+    pub translation: String,
+    pub status: BlockStatus,
+}
+
+/// Blocks of one translated page.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectPage {
+    /// Page number (1-indexed, as shown by PDF viewers)
+    pub page: usize,
+    pub blocks: Vec<ProjectBlock>,
+}
+
+/// Translated pages of a document, as saved with `--emit-project`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TranslationProject {
+    pub version: u32,
+    /// File name of the source PDF
+    pub source_pdf: String,
+    pub source_lang: Lang,
+    pub target_lang: Lang,
+    pub pages: Vec<ProjectPage>,
+}
+
+impl ProjectBlock {
+    pub(crate) fn translated(overlay: TranslationOverlay, status: BlockStatus) -> Self {
+        Self {
+            text: overlay.original,
+            bbox: overlay.bbox.as_array(),
+            font_size: overlay.font_size,
+            translation: overlay.translated,
+            status,
+        }
+    }
+
+    pub(crate) fn kept(block: TextBlock) -> Self {
+        Self {
+            translation: block.text.clone(),
+            text: block.text,
+            bbox: block.bbox.as_array(),
+            font_size: block.font_size,
+            status: BlockStatus::Kept,
+        }
+    }
+
+    /// Overlay rendering the translation, or `None` for a kept or flagged block.
+    pub fn overlay(&self) -> Option<TranslationOverlay> {
+        let [x0, y0, x1, y1] = self.bbox;
+        (!matches!(self.status, BlockStatus::Kept | BlockStatus::Flagged)).then(|| {
+            TranslationOverlay {
+                bbox: BoundingBox::new(x0, y0, x1, y1),
+                original: self.text.clone(),
+                translated: self.translation.clone(),
+                font_size: self.font_size,
+            }
+        })
+    }
+}
+
+impl TranslationProject {
+    /// Collect the blocks of freshly translated pages.
+    ///
+    /// Pages served from the page cache carry no blocks and are left out.
+    pub fn from_pages(
+        source_pdf: impl Into<String>,
+        source_lang: Lang,
+        target_lang: Lang,
+        pages: &[TranslatedPage],
+    ) -> Self {
+        Self {
+            version: PROJECT_VERSION,
+            source_pdf: source_pdf.into(),
+            source_lang,
+            target_lang,
+            pages: pages
+                .iter()
+                .filter(|page| !page.blocks.is_empty())
+                .map(|page| ProjectPage {
+                    page: page.page_num + 1,
+                    blocks: page.blocks.clone(),
+                })
+                .collect(),
+        }
+    }
+
+    /// Parse a project, possibly edited by hand.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let project: Self = serde_json::from_str(json).map_err(|error| invalid(FORMAT, error))?;
+        if project.version != PROJECT_VERSION {
+            return Err(invalid(
+                FORMAT,
+                format!(
+                    "version {} is not supported (expected {PROJECT_VERSION})",
+                    project.version
+                ),
+            ));
+        }
+        if project.pages.iter().any(|page| page.page == 0) {
+            return Err(invalid(FORMAT, "page numbers start at 1"));
+        }
+        Ok(project)
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(self).map_err(|error| invalid(FORMAT, error))
+    }
+
+    /// Render the project's pages of `doc` with their translations overlaid
+    /// and combine them into one PDF.
+    pub fn render(&self, doc: &PdfDocument, options: &OverlayOptions) -> Result<Vec<u8>> {
+        render_pages(
+            FORMAT,
+            doc,
+            options,
+            self.pages.iter().map(|page| {
+                let overlays = page
+                    .blocks
+                    .iter()
+                    .filter_map(ProjectBlock::overlay)
+                    .collect();
+                (page.page - 1, overlays)
+            }),
+        )
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn project() -> TranslationProject {
+        let overlay = TranslationOverlay {
+            bbox: BoundingBox::new(72.0, 100.5, 540.0, 130.0),
+            original: "Résultats annuels".to_string(),
+            translated: "Annual results".to_string(),
+            font_size: 11.0,
+        };
+        let kept = TextBlock {
+            text: "Executive summary".to_string(),
+            bbox: BoundingBox::new(72.0, 60.0, 300.0, 80.0),
+            font_size: 14.0,
+            line_count: 1,
+            language: Some(Lang::new("en")),
+        };
+        TranslationProject {
+            version: PROJECT_VERSION,
+            source_pdf: "rapport.pdf".to_string(),
+            source_lang: Lang::new("fr"),
+            target_lang: Lang::new("en"),
+            pages: vec![ProjectPage {
+                page: 1,
+                blocks: vec![
+                    ProjectBlock::translated(overlay, BlockStatus::Translated),
+                    ProjectBlock::kept(kept),
+                ],
+            }],
+        }
+    }
+
+    #[test]
+    fn projects_round_trip_through_json() {
+        let project = project();
+        let json = project.to_json().unwrap();
+        assert!(json.contains("\"status\": \"kept\""));
+        assert_eq!(TranslationProject::from_json(&json).unwrap(), project);
+    }
+
+    #[test]
+    fn only_translated_blocks_are_overlaid() {
+        let project = project();
+        let overlays: Vec<_> = project.pages[0]
+            .blocks
+            .iter()
+            .filter_map(ProjectBlock::overlay)
+            .collect();
+        assert_eq!(overlays.len(), 1);
+        assert_eq!(overlays[0].translated, "Annual results");
+        assert_eq!(
+            overlays[0].bbox.as_array().map(f32::to_bits),
+            [72.0_f32, 100.5, 540.0, 130.0].map(f32::to_bits)
+        );
+    }
+
+    #[test]
+    fn unsupported_versions_and_page_zero_are_rejected() {
+        let mut project = project();
+        project.version = PROJECT_VERSION + 1;
+        assert!(TranslationProject::from_json(&project.to_json().unwrap()).is_err());
+
+        project.version = PROJECT_VERSION;
+        project.pages[0].page = 0;
+        assert!(TranslationProject::from_json(&project.to_json().unwrap()).is_err());
+    }
+}
@@ -0,0 +1,255 @@
+//! TMX 1.4 import and export of translation memory segments.
+//!
+//! Export writes one `<tu>` per segment with a `<tuv>` for each language.
+//! Import pairs the variant in the source language of each `<tu>` (its
+//! `srclang`, else the header's) with every other variant. Inline markup is
+//! flattened: the text of `<hi>` is kept, while `<bpt>`, `<ept>`, `<it>`,
+//! `<ph>`, and `<ut>` hold native codes and are dropped.
+
+use quick_xml::Reader;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use std::fmt::Write;
+
+use super::{attribute, invalid};
+use crate::cache::TranslationUnit;
+use crate::config::Lang;
+use crate::error::Result;
+
+const FORMAT: &str = "TMX";
+
+/// `srclang` of a memory whose units do not share a source language.
+const ALL_LANGUAGES: &str = "*all*";
+
+/// Elements holding native codes rather than translatable text.
+const CODE_ELEMENTS: &[&[u8]] = &[b"bpt", b"ept", b"it", b"ph", b"ut"];
+
+/// Serialize `units` as a TMX 1.4 document.
+pub fn write(units: &[TranslationUnit]) -> String {
+    let srclang = match units.split_first() {
+        Some((first, rest))
+            if rest
+                .iter()
+                .all(|unit| unit.source_lang == first.source_lang) =>
+        {
+            first.source_lang.as_str()
+        }
+        _ => ALL_LANGUAGES,
+    };
+
+    let mut xml =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<tmx version=\"1.4\">\n");
+    let _ = writeln!(
+        xml,
+        "  <header creationtool=\"pdf-translator\" creationtoolversion=\"{}\" \
+         datatype=\"plaintext\" segtype=\"paragraph\" adminlang=\"en\" srclang=\"{}\" \
+         o-tmf=\"pdf-translator\"/>",
+        env!("CARGO_PKG_VERSION"),
+        escape(srclang)
+    );
+    xml.push_str("  <body>\n");
+    for unit in units {
+        let _ = writeln!(
+            xml,
+            "    <tu srclang=\"{}\">",
+            escape(unit.source_lang.as_str())
+        );
+        for (lang, text) in [
+            (&unit.source_lang, &unit.source),
+            (&unit.target_lang, &unit.target),
+        ] {
+            let _ = writeln!(
+                xml,
+                "      <tuv xml:lang=\"{}\"><seg>{}</seg></tuv>",
+                escape(lang.as_str()),
+                escape(text.as_str())
+            );
+        }
+        xml.push_str("    </tu>\n");
+    }
+    xml.push_str("  </body>\n</tmx>\n");
+    xml
+}
+
+/// Read the translation units of a TMX document.
+///
+/// Units without a variant in their source language are skipped.
+pub fn read(xml: &str) -> Result<Vec<TranslationUnit>> {
+    let mut reader = Reader::from_str(xml);
+    let mut units = Vec::new();
+    let mut header_srclang: Option<String> = None;
+    let mut tu_srclang: Option<String> = None;
+    let mut variants: Vec<(String, String)> = Vec::new();
+    let mut variant_lang: Option<String> = None;
+    let mut segment: Option<String> = None;
+    let mut code_depth = 0usize;
+
+    loop {
+        let event = reader.read_event().map_err(|error| {
+            invalid(
+                FORMAT,
+                format!("at byte {}: {error}", reader.buffer_position()),
+            )
+        })?;
+        match event {
+            Event::Start(element) | Event::Empty(element)
+                if element.name().as_ref() == b"header" =>
+            {
+                header_srclang = attribute(FORMAT, &element, b"srclang")?;
+            }
+            Event::Start(element)
+                if segment.is_some()
+                    && (code_depth > 0 || CODE_ELEMENTS.contains(&element.name().as_ref())) =>
+            {
+                code_depth += 1;
+            }
+            Event::Start(_) if segment.is_some() => {}
+            Event::End(element) if segment.is_some() && element.name().as_ref() != b"seg" => {
+                code_depth = code_depth.saturating_sub(1);
+            }
+            Event::Start(element) => match element.name().as_ref() {
+                b"tu" => {
+                    tu_srclang = attribute(FORMAT, &element, b"srclang")?;
+                    variants.clear();
+                }
+                b"tuv" => {
+                    // TMX 1.1 used `lang` before `xml:lang`.
+                    let lang = match attribute(FORMAT, &element, b"xml:lang")? {
+                        Some(lang) => Some(lang),
+                        None => attribute(FORMAT, &element, b"lang")?,
+                    };
+                    variant_lang = Some(lang.ok_or_else(|| {
+                        invalid(
+                            FORMAT,
+                            format!(
+                                "<tuv> without xml:lang at byte {}",
+                                reader.buffer_position()
+                            ),
+                        )
+                    })?);
+                }
+                b"seg" => {
+                    segment = Some(String::new());
+                    code_depth = 0;
+                }
+                _ => {}
+            },
+            Event::Text(text) if code_depth == 0 => {
+                if let Some(segment) = &mut segment {
+                    segment.push_str(&text.unescape().map_err(|error| invalid(FORMAT, error))?);
+                }
+            }
+            Event::CData(text) if code_depth == 0 => {
+                if let Some(segment) = &mut segment {
+                    segment.push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            Event::End(element) => match element.name().as_ref() {
+                b"seg" => {
+                    if let (Some(lang), Some(text)) = (variant_lang.clone(), segment.take()) {
+                        variants.push((lang, text));
+                    }
+                }
+                b"tuv" => variant_lang = None,
+                b"tu" => {
+                    let srclang = tu_srclang.take().or_else(|| header_srclang.clone());
+                    units.extend(unit_pairs(srclang.as_deref(), &variants));
+                    variants.clear();
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(units)
+}
+
+/// Pair the source variant of a `<tu>` with each of its other variants.
+fn unit_pairs(srclang: Option<&str>, variants: &[(String, String)]) -> Vec<TranslationUnit> {
+    let source = srclang.filter(|lang| *lang != ALL_LANGUAGES).map_or_else(
+        || variants.first(),
+        |srclang| {
+            variants
+                .iter()
+                .find(|(lang, _)| lang.eq_ignore_ascii_case(srclang))
+        },
+    );
+    let Some((source_lang, source)) = source else {
+        return Vec::new();
+    };
+    if source.trim().is_empty() {
+        return Vec::new();
+    }
+    variants
+        .iter()
+        .filter(|(lang, text)| lang != source_lang && !text.trim().is_empty())
+        .map(|(target_lang, target)| TranslationUnit {
+            source_lang: Lang::new(source_lang.as_str()),
+            target_lang: Lang::new(target_lang.as_str()),
+            source: source.clone(),
+            target: target.clone(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn unit(source: &str, target: &str) -> TranslationUnit {
+        TranslationUnit {
+            source_lang: Lang::new("fr"),
+            target_lang: Lang::new("en"),
+            source: source.to_string(),
+            target: target.to_string(),
+        }
+    }
+
+    #[test]
+    fn written_units_read_back_unchanged() {
+        let units = vec![
+            unit("Résultats & perspectives", "Results & outlook"),
+            unit("Le seuil est < 5 %.", "The threshold is < 5%."),
+        ];
+        let xml = write(&units);
+        assert!(xml.contains("srclang=\"fr\""));
+        assert!(xml.contains("Results &amp; outlook"));
+        assert_eq!(read(&xml).unwrap(), units);
+    }
+
+    #[test]
+    fn cat_tool_memories_are_flattened() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tmx version="1.4">
+  <header creationtool="CAT" creationtoolversion="1" datatype="xml" segtype="sentence"
+          adminlang="en-US" srclang="FR-FR" o-tmf="cat"/>
+  <body>
+    <tu>
+      <tuv xml:lang="fr-FR"><seg>Voir <bpt i="1">&lt;b&gt;</bpt>l'annexe<ept i="1">&lt;/b&gt;</ept>.</seg></tuv>
+      <tuv xml:lang="en-GB"><seg>See <hi>the annex</hi>.</seg></tuv>
+      <tuv xml:lang="de-DE"><seg>Siehe Anhang.</seg></tuv>
+    </tu>
+    <tu>
+      <tuv xml:lang="en-GB"><seg>Orphan</seg></tuv>
+    </tu>
+  </body>
+</tmx>"#;
+        let units = read(xml).unwrap();
+        assert_eq!(units.len(), 2);
+        assert_eq!(units[0].source_lang.as_str(), "fr-FR");
+        assert_eq!(units[0].source, "Voir l'annexe.");
+        assert_eq!(units[0].target, "See the annex.");
+        assert_eq!(units[1].target_lang.as_str(), "de-DE");
+    }
+
+    #[test]
+    fn variants_without_a_language_are_an_error() {
+        let xml = "<tmx><body><tu><tuv><seg>x</seg></tuv></tu></body></tmx>";
+        assert!(matches!(
+            read(xml),
+            Err(crate::error::Error::InterchangeFormat { format: "TMX", .. })
+        ));
+    }
+}
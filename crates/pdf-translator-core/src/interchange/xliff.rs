@@ -0,0 +1,405 @@
+//! XLIFF 2.0 export of translated blocks for human post-editing, and import of
+//! the edited file to render the PDF again without the translator.
+//!
+//! Each block becomes a `<unit>` whose page, bounding box, and font size are
+//! kept in XLIFF metadata (`mda:meta`), so the reviewed text lands where the
+//! original was. Inline markup added by CAT tools is flattened to its text.
+
+use quick_xml::Reader;
+use quick_xml::escape::escape;
+use quick_xml::events::Event;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use super::project::ProjectBlock;
+use super::{attribute, invalid, render_pages};
+use crate::TranslatedPage;
+use crate::config::Lang;
+use crate::error::{Error, Result};
+use crate::pdf::{BoundingBox, OverlayOptions, PdfDocument, TranslationOverlay};
+
+const FORMAT: &str = "XLIFF";
+
+/// `metaGroup` category holding the layout of a block.
+const META_CATEGORY: &str = "pdf-translator";
+
+/// BCP 47 tag written when the source language was left to detection.
+const UNDETERMINED: &str = "und";
+
+/// Translated blocks of a document, as exchanged with reviewers.
+#[derive(Debug, Clone)]
+pub struct XliffDocument {
+    /// File name of the source PDF
+    pub original: String,
+    pub source_lang: Lang,
+    pub target_lang: Lang,
+    /// Translated pages in document order
+    pub pages: Vec<XliffPage>,
+}
+
+/// Translated blocks of one page.
+#[derive(Debug, Clone)]
+pub struct XliffPage {
+    /// Page number (0-indexed)
+    pub page_num: usize,
+    pub overlays: Vec<TranslationOverlay>,
+}
+
+/// Blocks of one `<unit>` being read.
+#[derive(Default)]
+struct UnitState {
+    id: String,
+    meta: Vec<(String, String)>,
+    source: String,
+    target: String,
+    /// Whether a segment was left without a target
+    untranslated: bool,
+}
+
+impl XliffDocument {
+    /// Collect the translated blocks of freshly translated pages.
+    ///
+    /// Pages served from the page cache carry no blocks and are left out, as
+    /// are blocks kept in the original language.
+    pub fn from_pages(
+        original: impl Into<String>,
+        source_lang: Lang,
+        target_lang: Lang,
+        pages: &[TranslatedPage],
+    ) -> Self {
+        Self {
+            original: original.into(),
+            source_lang,
+            target_lang,
+            pages: pages
+                .iter()
+                .map(|page| XliffPage {
+                    page_num: page.page_num,
+                    overlays: page
+                        .blocks
+                        .iter()
+                        .filter_map(ProjectBlock::overlay)
+                        .collect(),
+                })
+                .filter(|page| !page.overlays.is_empty())
+                .collect(),
+        }
+    }
+
+    /// Render the pages of `doc` with the (edited) translations overlaid and
+    /// combine them into one PDF.
+    pub fn render(&self, doc: &PdfDocument, options: &OverlayOptions) -> Result<Vec<u8>> {
+        render_pages(
+            FORMAT,
+            doc,
+            options,
+            self.pages
+                .iter()
+                .map(|page| (page.page_num, page.overlays.clone())),
+        )
+    }
+}
+
+/// Serialize `document` as an XLIFF 2.0 file.
+pub fn write(document: &XliffDocument) -> String {
+    let source_lang = match document.source_lang.as_str() {
+        "auto" => UNDETERMINED,
+        code => code,
+    };
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    let _ = writeln!(
+        xml,
+        "<xliff xmlns=\"urn:oasis:names:tc:xliff:document:2.0\" \
+         xmlns:mda=\"urn:oasis:names:tc:xliff:metadata:2.0\" version=\"2.0\" \
+         srcLang=\"{}\" trgLang=\"{}\">",
+        escape(source_lang),
+        escape(document.target_lang.as_str())
+    );
+    let _ = writeln!(
+        xml,
+        "  <file id=\"f1\" original=\"{}\">",
+        escape(document.original.as_str())
+    );
+    for page in &document.pages {
+        let page_number = page.page_num + 1;
+        for (index, block) in page.overlays.iter().enumerate() {
+            let bbox = block.bbox;
+            let _ = writeln!(xml, "    <unit id=\"p{page_number}-b{}\">", index + 1);
+            xml.push_str("      <mda:metadata>\n");
+            let _ = writeln!(xml, "        <mda:metaGroup category=\"{META_CATEGORY}\">");
+            let _ = writeln!(
+                xml,
+                "          <mda:meta type=\"page\">{page_number}</mda:meta>"
+            );
+            let _ = writeln!(
+                xml,
+                "          <mda:meta type=\"bbox\">{} {} {} {}</mda:meta>",
+                bbox.x0, bbox.y0, bbox.x1, bbox.y1
+            );
+            let _ = writeln!(
+                xml,
+                "          <mda:meta type=\"font-size\">{}</mda:meta>",
+                block.font_size
+            );
+            xml.push_str("        </mda:metaGroup>\n      </mda:metadata>\n");
+            xml.push_str("      <segment state=\"translated\">\n");
+            let _ = writeln!(
+                xml,
+                "        <source>{}</source>",
+                escape(block.original.as_str())
+            );
+            let _ = writeln!(
+                xml,
+                "        <target>{}</target>",
+                escape(block.translated.as_str())
+            );
+            xml.push_str("      </segment>\n    </unit>\n");
+        }
+    }
+    xml.push_str("  </file>\n</xliff>\n");
+    xml
+}
+
+/// Read the translated units of an XLIFF 2.0 file written by [`write`].
+///
+/// Units whose segments were not all translated keep the original text and
+/// are left out.
+pub fn read(xml: &str) -> Result<XliffDocument> {
+    let mut reader = Reader::from_str(xml);
+    let mut source_lang = None;
+    let mut target_lang = None;
+    let mut original = None;
+    let mut pages: BTreeMap<usize, Vec<TranslationOverlay>> = BTreeMap::new();
+    let mut unit: Option<UnitState> = None;
+    let mut meta: Option<(String, String)> = None;
+    // Text of the `<source>` or `<target>` being read, and whether it is the target.
+    let mut content: Option<(bool, String)> = None;
+    // Source of the current `<segment>` or `<ignorable>`, and whether it has a target.
+    let mut part_source = String::new();
+    let mut part_has_target = false;
+
+    loop {
+        let event = reader.read_event().map_err(|error| {
+            invalid(
+                FORMAT,
+                format!("at byte {}: {error}", reader.buffer_position()),
+            )
+        })?;
+        match event {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"xliff" => {
+                    source_lang = attribute(FORMAT, &element, b"srcLang")?;
+                    target_lang = attribute(FORMAT, &element, b"trgLang")?;
+                }
+                b"file" if original.is_none() => {
+                    original = attribute(FORMAT, &element, b"original")?;
+                }
+                b"unit" => {
+                    unit = Some(UnitState {
+                        id: attribute(FORMAT, &element, b"id")?.unwrap_or_default(),
+                        ..UnitState::default()
+                    });
+                }
+                b"meta" if unit.is_some() => {
+                    let kind = attribute(FORMAT, &element, b"type")?.unwrap_or_default();
+                    meta = Some((kind, String::new()));
+                }
+                b"segment" | b"ignorable" => part_has_target = false,
+                b"source" if unit.is_some() => content = Some((false, String::new())),
+                b"target" if unit.is_some() => {
+                    part_has_target = true;
+                    content = Some((true, String::new()));
+                }
+                _ => {}
+            },
+            Event::Text(text) => {
+                let text = text.unescape().map_err(|error| invalid(FORMAT, error))?;
+                if let Some((_, buffer)) = &mut content {
+                    buffer.push_str(&text);
+                } else if let Some((_, value)) = &mut meta {
+                    value.push_str(&text);
+                }
+            }
+            Event::CData(text) => {
+                if let Some((_, buffer)) = &mut content {
+                    buffer.push_str(&String::from_utf8_lossy(&text));
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"meta" => {
+                    if let (Some(state), Some(entry)) = (&mut unit, meta.take()) {
+                        state.meta.push(entry);
+                    }
+                }
+                b"source" | b"target" => {
+                    if let (Some(state), Some((is_target, text))) = (&mut unit, content.take()) {
+                        if is_target {
+                            state.target.push_str(&text);
+                        } else {
+                            state.source.push_str(&text);
+                            part_source = text;
+                        }
+                    }
+                }
+                b"segment" => {
+                    if let Some(state) = &mut unit {
+                        state.untranslated |= !part_has_target;
+                    }
+                }
+                // Whitespace between segments usually has no target of its own.
+                b"ignorable" => {
+                    if let Some(state) = &mut unit
+                        && !part_has_target
+                    {
+                        state.target.push_str(&part_source);
+                    }
+                }
+                b"unit" => {
+                    if let Some(state) = unit.take()
+                        && !state.untranslated
+                        && !state.target.trim().is_empty()
+                    {
+                        let (page_num, overlay) = state.into_overlay()?;
+                        pages.entry(page_num).or_default().push(overlay);
+                    }
+                }
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(XliffDocument {
+        original: original.unwrap_or_default(),
+        source_lang: Lang::new(source_lang.ok_or_else(|| invalid(FORMAT, "missing srcLang"))?),
+        target_lang: Lang::new(target_lang.ok_or_else(|| invalid(FORMAT, "missing trgLang"))?),
+        pages: pages
+            .into_iter()
+            .map(|(page_num, overlays)| XliffPage { page_num, overlays })
+            .collect(),
+    })
+}
+
+impl UnitState {
+    fn meta(&self, kind: &str) -> Result<&str> {
+        self.meta
+            .iter()
+            .find(|(known, _)| known == kind)
+            .map(|(_, value)| value.trim())
+            .ok_or_else(|| invalid(FORMAT, format!("unit {} has no {kind} metadata", self.id)))
+    }
+
+    /// Page number (0-indexed) and overlay described by the unit.
+    fn into_overlay(self) -> Result<(usize, TranslationOverlay)> {
+        let malformed = |kind: &str| -> Error {
+            invalid(
+                FORMAT,
+                format!("unit {} has malformed {kind} metadata", self.id),
+            )
+        };
+        let page = self
+            .meta("page")?
+            .parse::<usize>()
+            .ok()
+            .and_then(|page| page.checked_sub(1))
+            .ok_or_else(|| malformed("page"))?;
+        let coordinates: Vec<f32> = self
+            .meta("bbox")?
+            .split_whitespace()
+            .map(str::parse)
+            .collect::<std::result::Result<_, _>>()
+            .map_err(|_| malformed("bbox"))?;
+        let [x0, y0, x1, y1] = coordinates[..] else {
+            return Err(malformed("bbox"));
+        };
+        let font_size = self
+            .meta("font-size")?
+            .parse::<f32>()
+            .map_err(|_| malformed("font-size"))?;
+        Ok((
+            page,
+            TranslationOverlay {
+                bbox: BoundingBox::new(x0, y0, x1, y1),
+                original: self.source,
+                translated: self.target,
+                font_size,
+            },
+        ))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn document() -> XliffDocument {
+        XliffDocument {
+            original: "rapport.pdf".to_string(),
+            source_lang: Lang::new("auto"),
+            target_lang: Lang::new("en"),
+            pages: vec![XliffPage {
+                page_num: 2,
+                overlays: vec![TranslationOverlay {
+                    bbox: BoundingBox::new(72.0, 100.5, 540.25, 130.0),
+                    original: "Résultats <annuels> & perspectives".to_string(),
+                    translated: "Annual results & outlook".to_string(),
+                    font_size: 10.5,
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn written_documents_read_back_with_their_layout() {
+        let xml = write(&document());
+        assert!(xml.contains("srcLang=\"und\""));
+        assert!(xml.contains("<unit id=\"p3-b1\">"));
+
+        let read = read(&xml).unwrap();
+        assert_eq!(read.original, "rapport.pdf");
+        assert_eq!(read.target_lang.as_str(), "en");
+        assert_eq!(read.pages.len(), 1);
+        assert_eq!(read.pages[0].page_num, 2);
+        let block = &read.pages[0].overlays[0];
+        assert_eq!(
+            block.bbox.as_array().map(f32::to_bits),
+            [72.0_f32, 100.5, 540.25, 130.0].map(f32::to_bits)
+        );
+        assert!((block.font_size - 10.5).abs() < f32::EPSILON);
+        assert_eq!(block.original, "Résultats <annuels> & perspectives");
+        assert_eq!(block.translated, "Annual results & outlook");
+    }
+
+    #[test]
+    fn reviewed_segments_are_joined_and_untranslated_units_skipped() {
+        let xml = write(&document()).replace(
+            "<target>Annual results &amp; outlook</target>",
+            "<target>Annual <pc id=\"1\">results</pc> &amp; outlook.</target>\
+                 </segment><ignorable><source> </source></ignorable>\
+                 <segment state=\"final\"><source>Fin.</source><target>End.</target>",
+        );
+        let reviewed = read(&xml).unwrap();
+        assert_eq!(
+            reviewed.pages[0].overlays[0].translated,
+            "Annual results & outlook. End."
+        );
+
+        let untranslated =
+            write(&document()).replace("<target>Annual results &amp; outlook</target>", "");
+        assert!(read(&untranslated).unwrap().pages.is_empty());
+    }
+
+    #[test]
+    fn units_without_layout_are_an_error() {
+        let xml = write(&document()).replace("<mda:meta type=\"bbox\">", "<mda:meta type=\"box\">");
+        assert!(matches!(
+            read(&xml),
+            Err(Error::InterchangeFormat {
+                format: "XLIFF",
+                ..
+            })
+        ));
+    }
+}
@@ -0,0 +1,357 @@
+//! Offline identification of the language a block of text is written in.
+//!
+//! Most scripts identify their language on their own; Latin-script text is
+//! scored against the most frequent words of each supported language.
+//! Detection is conservative: short, mixed, or ambiguous text yields `None`
+//! rather than a guess.
+
+use std::cmp::Reverse;
+use std::collections::HashMap;
+
+use crate::config::Lang;
+
+/// Letters a text needs before its language is guessed.
+const MIN_LETTERS: usize = 12;
+
+/// Words a Latin-script text needs before its language is guessed.
+const MIN_WORDS: usize = 4;
+
+/// Most frequent words of the Latin-script languages told apart.
+const PROFILES: &[(&str, &[&str])] = &[
+    (
+        "en",
+        &[
+            "the", "and", "of", "to", "in", "is", "that", "for", "it", "with", "as", "was", "on",
+            "are", "be", "this", "by", "not", "or", "from", "at", "which", "have", "an", "they",
+            "you", "we", "their", "has", "will",
+        ],
+    ),
+    (
+        "fr",
+        &[
+            "le", "la", "les", "l", "de", "des", "d", "et", "est", "un", "une", "du", "dans",
+            "que", "qu", "qui", "pour", "pas", "sur", "au", "aux", "avec", "ce", "cette", "sont",
+            "par", "il", "elle", "nous", "vous", "ne", "plus", "à", "été", "être",
+        ],
+    ),
+    (
+        "de",
+        &[
+            "der", "die", "das", "und", "ist", "nicht", "ein", "eine", "zu", "den", "dem", "mit",
+            "von", "sich", "des", "auf", "für", "im", "sie", "es", "wird", "werden", "auch", "als",
+            "oder", "bei", "nach", "wir", "ich", "sind",
+        ],
+    ),
+    (
+        "es",
+        &[
+            "el", "la", "los", "las", "de", "y", "que", "en", "un", "una", "es", "por", "con",
+            "para", "del", "se", "no", "al", "lo", "como", "más", "su", "sus", "pero", "está",
+            "son", "este", "esta", "ha", "fue",
+        ],
+    ),
+    (
+        "it",
+        &[
+            "il", "lo", "la", "gli", "le", "l", "di", "e", "che", "è", "un", "una", "per", "non",
+            "con", "del", "della", "dei", "delle", "sono", "si", "nel", "nella", "al", "alla",
+            "come", "anche", "questo", "questa", "ha", "più",
+        ],
+    ),
+    (
+        "pt",
+        &[
+            "o", "a", "os", "as", "de", "e", "que", "em", "um", "uma", "é", "do", "da", "dos",
+            "das", "não", "para", "com", "por", "no", "na", "se", "mais", "ao", "como", "são",
+            "foi", "está", "seu", "sua",
+        ],
+    ),
+    (
+        "nl",
+        &[
+            "de", "het", "een", "en", "van", "is", "dat", "op", "te", "in", "niet", "zijn", "voor",
+            "met", "die", "er", "aan", "ook", "als", "bij", "door", "om", "naar", "wordt",
+            "worden", "dan", "maar", "of", "heeft", "deze",
+        ],
+    ),
+];
+
+/// Letters used by only one of the profiled languages, each worth one hit.
+const MARKERS: &[(char, &str)] = &[
+    ('ß', "de"),
+    ('ñ', "es"),
+    ('ã', "pt"),
+    ('õ', "pt"),
+    ('œ', "fr"),
+];
+
+/// Writing systems told apart by language detection and quality checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Script {
+    Latin,
+    Greek,
+    Cyrillic,
+    Arabic,
+    Devanagari,
+    Thai,
+    Hangul,
+    Kana,
+    Han,
+}
+
+impl Script {
+    /// Script of a letter, or `None` for digits, punctuation, and other scripts.
+    pub(crate) fn of(c: char) -> Option<Self> {
+        let script = match u32::from(c) {
+            _ if c.is_ascii_alphabetic() => Self::Latin,
+            0x00C0..=0x024F | 0x1E00..=0x1EFF if c.is_alphabetic() => Self::Latin,
+            0x0370..=0x03FF => Self::Greek,
+            0x0400..=0x04FF => Self::Cyrillic,
+            0x0600..=0x06FF | 0x0750..=0x077F => Self::Arabic,
+            0x0900..=0x097F => Self::Devanagari,
+            0x0E00..=0x0E7F => Self::Thai,
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Self::Hangul,
+            0x3040..=0x30FF => Self::Kana,
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF => Self::Han,
+            _ => return None,
+        };
+        c.is_alphabetic().then_some(script)
+    }
+
+    /// Whether text in this script is usually set in full-width characters.
+    pub(crate) const fn is_wide(self) -> bool {
+        matches!(self, Self::Hangul | Self::Kana | Self::Han)
+    }
+}
+
+/// Language of `text`, if it can be told with confidence.
+///
+/// Chinese is reported as `zh`, without a script variant.
+pub fn detect(text: &str) -> Option<Lang> {
+    let mut counts: Vec<(Script, usize)> = Vec::new();
+    for script in text.chars().filter_map(Script::of) {
+        match counts.iter_mut().find(|(known, _)| *known == script) {
+            Some((_, count)) => *count += 1,
+            None => counts.push((script, 1)),
+        }
+    }
+    let total: usize = counts.iter().map(|(_, count)| count).sum();
+    if total < MIN_LETTERS {
+        return None;
+    }
+    let count = |script: Script| {
+        counts
+            .iter()
+            .find(|(known, _)| *known == script)
+            .map_or(0, |(_, count)| *count)
+    };
+
+    // Japanese mixes kana with kanji, and Korean occasionally uses hanja.
+    let (kana, han, hangul) = (
+        count(Script::Kana),
+        count(Script::Han),
+        count(Script::Hangul),
+    );
+    let cjk = kana + han + hangul;
+    if cjk * 10 >= total * 6 {
+        let code = if hangul * 2 >= cjk {
+            "ko"
+        } else if kana * 20 >= cjk {
+            "ja"
+        } else {
+            "zh"
+        };
+        return Some(Lang::new(code));
+    }
+
+    let (script, letters) = counts.iter().copied().max_by_key(|(_, count)| *count)?;
+    if letters * 10 < total * 6 {
+        return None;
+    }
+    let code = match script {
+        Script::Latin => return detect_latin(text),
+        Script::Cyrillic if text.contains(['і', 'ї', 'є', 'ґ']) => "uk",
+        Script::Cyrillic => "ru",
+        Script::Greek => "el",
+        Script::Arabic if text.contains(['پ', 'چ', 'ژ', 'گ']) => "fa",
+        Script::Arabic => "ar",
+        Script::Devanagari => "hi",
+        Script::Thai => "th",
+        Script::Hangul | Script::Kana | Script::Han => return None,
+    };
+    Some(Lang::new(code))
+}
+
+/// Pick the profiled language whose frequent words make up the most of `text`.
+fn detect_latin(text: &str) -> Option<Lang> {
+    let lowered = text.to_lowercase();
+    let words: Vec<&str> = lowered
+        .split(|c: char| !c.is_alphabetic())
+        .filter(|word| !word.is_empty())
+        .collect();
+    if words.len() < MIN_WORDS {
+        return None;
+    }
+
+    let mut scores: Vec<(&str, usize)> = PROFILES
+        .iter()
+        .map(|(code, common)| {
+            let hits = words.iter().filter(|word| common.contains(word)).count();
+            let marked = MARKERS
+                .iter()
+                .filter(|(marker, language)| language == code && lowered.contains(*marker))
+                .count();
+            (*code, hits + marked)
+        })
+        .collect();
+    scores.sort_by_key(|&(_, score)| Reverse(score));
+
+    let (code, best) = *scores.first()?;
+    let second = scores.get(1).map_or(0, |(_, score)| *score);
+    // Ordinary prose is at least a fifth function words; require a clear winner.
+    (best >= 2 && best * 5 >= words.len() && best > second && best * 2 >= second * 3)
+        .then(|| Lang::new(code))
+}
+
+/// Language covering the most text among `(language, weight)` detections.
+pub fn dominant<'a>(detections: impl IntoIterator<Item = (&'a Lang, usize)>) -> Option<Lang> {
+    let mut weights: HashMap<&Lang, usize> = HashMap::new();
+    for (lang, weight) in detections {
+        *weights.entry(lang).or_default() += weight;
+    }
+    weights
+        .into_iter()
+        .max_by(|a, b| a.1.cmp(&b.1).then_with(|| b.0.as_str().cmp(a.0.as_str())))
+        .map(|(lang, _)| lang.clone())
+}
+
+/// Whether [`detect`] can report `lang`, so that text detected as another
+/// language is not merely in a language it has no profile for.
+pub fn is_detectable(lang: &Lang) -> bool {
+    const OTHER_SCRIPTS: &[&str] = &["zh", "ja", "ko", "uk", "ru", "el", "fa", "ar", "hi", "th"];
+    PROFILES
+        .iter()
+        .map(|(code, _)| *code)
+        .chain(OTHER_SCRIPTS.iter().copied())
+        .any(|code| same_language(&Lang::new(code), lang))
+}
+
+/// Whether two codes name the same language. `auto` matches nothing.
+///
+/// Script and region subtags only tell codes apart when both have one: `zh`
+/// matches `zh-CN`, but `zh-CN` and `zh-TW` (or `pt-BR` and `pt-PT`) differ.
+pub fn same_language(a: &Lang, b: &Lang) -> bool {
+    fn subtags(lang: &Lang) -> Option<[Option<String>; 3]> {
+        let mut parts = lang.as_str().split(['-', '_']);
+        let primary = parts.next()?.to_ascii_lowercase();
+        if primary.is_empty() || primary == "auto" {
+            return None;
+        }
+        let (mut script, mut region) = (None, None);
+        for part in parts {
+            let part = part.to_ascii_lowercase();
+            if part.len() == 4 && part.chars().all(|c| c.is_ascii_alphabetic()) {
+                script.get_or_insert(part);
+            } else if (part.len() == 2 && part.chars().all(|c| c.is_ascii_alphabetic()))
+                || (part.len() == 3 && part.chars().all(|c| c.is_ascii_digit()))
+            {
+                region.get_or_insert(part);
+            }
+        }
+        Some([Some(primary), script, region])
+    }
+    let (Some(a), Some(b)) = (subtags(a), subtags(b)) else {
+        return false;
+    };
+    a.iter().zip(&b).all(|pair| match pair {
+        (Some(a), Some(b)) => a == b,
+        _ => true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn code(text: &str) -> Option<String> {
+        detect(text).map(|lang| lang.0)
+    }
+
+    #[test]
+    fn latin_languages_are_told_apart_by_frequent_words() {
+        assert_eq!(
+            code(
+                "Le contrat est signé par les deux parties et prend effet à la date de sa signature."
+            ),
+            Some("fr".to_string())
+        );
+        assert_eq!(
+            code(
+                "The agreement is signed by both parties and takes effect on the date of signature."
+            ),
+            Some("en".to_string())
+        );
+        assert_eq!(
+            code(
+                "Der Vertrag wird von beiden Parteien unterzeichnet und tritt mit der Unterzeichnung in Kraft."
+            ),
+            Some("de".to_string())
+        );
+        assert_eq!(
+            code(
+                "El contrato es firmado por ambas partes y entra en vigor en la fecha de su firma."
+            ),
+            Some("es".to_string())
+        );
+    }
+
+    #[test]
+    fn other_scripts_identify_their_language() {
+        assert_eq!(
+            code("この契約は両当事者が署名した日から効力を生じます。"),
+            Some("ja".to_string())
+        );
+        assert_eq!(
+            code("本合同自双方签字之日起生效，有效期为三年。"),
+            Some("zh".to_string())
+        );
+        assert_eq!(
+            code("Договор вступает в силу с момента его подписания обеими сторонами."),
+            Some("ru".to_string())
+        );
+        assert_eq!(
+            code("Договір набирає чинності з моменту його підписання."),
+            Some("uk".to_string())
+        );
+    }
+
+    #[test]
+    fn short_or_ambiguous_text_is_not_guessed() {
+        assert_eq!(code("Annexe 2"), None);
+        assert_eq!(code("Quarterly Revenue Summary 2024"), None);
+        assert_eq!(code("12.3 / 45.6 / 78.9"), None);
+    }
+
+    #[test]
+    fn dominant_weights_by_length_and_variants_compare_equal() {
+        let (fr, en) = (Lang::new("fr"), Lang::new("en"));
+        assert_eq!(
+            dominant([(&fr, 40), (&en, 30), (&en, 20)]),
+            Some(en.clone())
+        );
+        assert_eq!(dominant(std::iter::empty()), None);
+
+        assert!(same_language(&Lang::new("zh"), &Lang::new("zh-CN")));
+        assert!(same_language(&Lang::new("zh-tw"), &Lang::new("zh_TW")));
+        assert!(same_language(&Lang::new("zh-Hant"), &Lang::new("zh-TW")));
+        assert!(!same_language(&Lang::new("zh-TW"), &Lang::new("zh-CN")));
+        assert!(!same_language(
+            &Lang::new("zh-Hant"),
+            &Lang::new("zh-Hans-CN")
+        ));
+        assert!(!same_language(&Lang::new("pt-BR"), &Lang::new("pt-PT")));
+        assert!(same_language(&Lang::new("es-419"), &Lang::new("es")));
+        assert!(!same_language(&fr, &en));
+        assert!(!same_language(&Lang::new("auto"), &Lang::new("auto")));
+    }
+}
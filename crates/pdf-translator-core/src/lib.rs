@@ -2,36 +2,91 @@
 //!
 //! This library provides the core functionality for translating PDF documents:
 //! - PDF text extraction and rendering
-//! - Translation via OpenAI-compatible APIs
+//! - Translation via OpenAI-compatible APIs, DeepL, or LibreTranslate
+//! - Glossary-driven terminology
 //! - Caching (memory and disk)
 //! - PDF overlay creation for translations
 
 pub mod cache;
 pub mod config;
 pub mod error;
+pub mod glossary;
+pub mod interchange;
+pub mod language;
 pub mod pdf;
+pub mod protect;
+pub mod quality;
 pub mod translator;
 pub mod util;
 
-pub use cache::{CacheKey, TranslationCache};
+pub use cache::{
+    CacheKey, MemoryHits, MemoryMatch, Reservation, TranslationCache, TranslationMemory,
+    TranslationUnit, UsageLedger,
+};
 pub use config::{
-    AppConfig, DEFAULT_SOURCE_LANG, DEFAULT_TARGET_LANG, DEFAULT_TEXT_COLOR, Lang, LanguageOption,
-    TextColor, TranslatorCacheIdentity, TranslatorConfig, flag_for_lang, source_languages,
-    target_languages,
+    AnthropicConfig, AppConfig, AzureOpenAiConfig, BackendConfig, BudgetConfig, CommandConfig,
+    DEFAULT_ANTHROPIC_VERSION, DEFAULT_AZURE_API_VERSION, DEFAULT_SOURCE_LANG, DEFAULT_TARGET_LANG,
+    DEFAULT_TEXT_COLOR, FallbackConfig, HttpClientConfig, Lang, LanguageOption, ModelPrice,
+    OllamaConfig, PricingConfig, ProtectConfig, QualityConfig, RateLimitConfig, TextColor,
+    TranslationMemoryConfig, TranslatorBackend, TranslatorCacheIdentity, TranslatorConfig,
+    flag_for_lang, source_languages, target_languages,
 };
 pub use error::{Error, Result};
-pub use pdf::{BoundingBox, OverlayOptions, PageRenderer, PdfDocument, PdfOverlay, TextBlock};
-pub use translator::{OpenAiTranslator, Translator, create_translator};
+pub use glossary::Glossary;
+pub use interchange::project::{BlockStatus, ProjectBlock, TranslationProject};
+pub use pdf::{
+    BoundingBox, OverlayOptions, PageRenderer, PdfDocument, PdfOverlay, TextBlock,
+    TranslationOverlay,
+};
+pub use protect::{Masked, Protector};
+pub use quality::{QualityIssue, QualityRule, QualityValidator};
+pub use translator::{
+    AnthropicTranslator, BatchTranslation, BreakerState, CommandTranslator, DeepLTranslator,
+    FallbackTranslator, LibreTranslateTranslator, MockTranslator, OllamaTranslator,
+    OpenAiTranslator, PartialTranslation, ProtectedTranslator, RateLimiter, TokenUsage,
+    TranslationContext, Translator, create_translator, create_translator_chain,
+};
 pub use util::clear_translation_cache;
 
+use futures::stream::{self, StreamExt};
 use std::sync::Arc;
-use tracing::{debug, info};
+use tokio::sync::{OnceCell, Semaphore};
+use tracing::{debug, info, warn};
 
 /// High-level PDF translator that combines all components
 pub struct PdfTranslator {
     translator: Arc<dyn Translator>,
     cache: TranslationCache,
     config: AppConfig,
+    context: TranslationContext,
+    /// Bounds translator calls issued by concurrently translated pages.
+    request_slots: Arc<Semaphore>,
+    /// Set once the translator accepted the configured language pair.
+    languages_checked: OnceCell<()>,
+}
+
+/// Partial translation of one block, reported while a page is streamed
+#[derive(Debug, Clone)]
+pub struct BlockPreview {
+    /// Index of the block among the page's non-empty blocks
+    pub block: usize,
+    /// Number of non-empty blocks on the page
+    pub block_count: usize,
+    /// Block position in PDF coordinates
+    pub bbox: BoundingBox,
+    /// Translation received so far
+    pub text: String,
+}
+
+/// Translations of a page's blocks, each with the backend that produced it.
+struct PageTranslation {
+    translations: Vec<String>,
+    /// Cache identity of the backend behind each translation
+    identities: Vec<TranslatorCacheIdentity>,
+    usage: TokenUsage,
+    cost: Option<f64>,
+    /// Indexes of blocks that failed validation
+    flagged: Vec<usize>,
 }
 
 /// Result of translating a single page
@@ -42,40 +97,147 @@ pub struct TranslatedPage {
     pub pdf_bytes: Vec<u8>,
     /// Whether this was a cache hit
     pub from_cache: bool,
+    /// Tokens billed to translate the page (zero for cache hits)
+    pub usage: TokenUsage,
+    /// Estimated cost, when the model that translated the page has a price
+    pub cost: Option<f64>,
+    /// Blocks reused from the translation memory (none for cache hits)
+    pub memory: MemoryHits,
+    /// Blocks of the page and their translations, with blocks kept in the
+    /// original language last (none for cache hits)
+    pub blocks: Vec<ProjectBlock>,
+}
+
+/// Token usage and estimated cost summed over several pages
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct UsageSummary {
+    /// Tokens billed across all pages
+    pub usage: TokenUsage,
+    /// Estimated cost, or `None` when some billed page had no price
+    pub cost: Option<f64>,
+}
+
+impl UsageSummary {
+    /// Add one page's usage and cost.
+    pub fn add_page(&mut self, page: &TranslatedPage) {
+        self.add(page.usage, page.cost);
+    }
+
+    /// Add `usage` and its cost, which only an unpriced model leaves unknown
+    /// once tokens were billed.
+    pub fn add(&mut self, usage: TokenUsage, cost: Option<f64>) {
+        self.usage += usage;
+        self.cost = match (self.cost, cost) {
+            (Some(total), Some(cost)) => Some(total + cost),
+            (total, None) if usage.total_tokens() == 0 => total,
+            _ => None,
+        };
+    }
+
+    /// Token counts, plus the estimated cost when `pricing` lists any model.
+    pub fn describe(&self, pricing: &PricingConfig) -> String {
+        let mut description = format!(
+            "{} tokens ({} prompt, {} completion) in {} requests",
+            self.usage.total_tokens(),
+            self.usage.prompt_tokens,
+            self.usage.completion_tokens,
+            self.usage.requests
+        );
+        if !pricing.models.is_empty() {
+            match self.cost {
+                Some(cost) => {
+                    description.push_str(", estimated cost ");
+                    description.push_str(&pricing.format_cost(cost));
+                }
+                None => description.push_str(", cost unknown for an unpriced model"),
+            }
+        }
+        description
+    }
+}
+
+impl Default for UsageSummary {
+    fn default() -> Self {
+        Self {
+            usage: TokenUsage::default(),
+            cost: Some(0.0),
+        }
+    }
+}
+
+impl<'a> FromIterator<&'a TranslatedPage> for UsageSummary {
+    fn from_iter<I: IntoIterator<Item = &'a TranslatedPage>>(pages: I) -> Self {
+        let mut summary = Self::default();
+        for page in pages {
+            summary.add_page(page);
+        }
+        summary
+    }
 }
 
 impl PdfTranslator {
     /// Create a new PDF translator with the given configuration
     pub fn new(config: AppConfig) -> Result<Self> {
-        let translator = create_translator(&config.translator)?;
-        let cache = TranslationCache::new(&config.cache)?;
+        config.rate_limit.validate()?;
+        let translator = create_translator_chain(&config, &RateLimiter::new(&config.rate_limit))?;
+        let cache = TranslationCache::from_app_config(&config)?;
 
-        Ok(Self {
-            translator,
-            cache,
-            config,
-        })
+        Self::from_parts(translator, cache, config)
     }
 
     /// Create with a shared cache (for cache sharing across instances)
     pub fn with_cache(config: AppConfig, cache: TranslationCache) -> Result<Self> {
-        let translator = create_translator(&config.translator)?;
+        let rate_limiter = RateLimiter::new(&config.rate_limit);
+        Self::with_shared(config, cache, &rate_limiter)
+    }
 
-        Ok(Self {
-            translator,
-            cache,
-            config,
-        })
+    /// Create with a cache and rate limiter shared across instances, so
+    /// concurrent translators stay under one request and token quota
+    pub fn with_shared(
+        config: AppConfig,
+        cache: TranslationCache,
+        rate_limiter: &RateLimiter,
+    ) -> Result<Self> {
+        config.rate_limit.validate()?;
+        let translator = create_translator_chain(&config, rate_limiter)?;
+
+        Self::from_parts(translator, cache, config)
     }
 
     /// Create with a custom translator
     pub fn with_translator(translator: Arc<dyn Translator>, config: AppConfig) -> Result<Self> {
-        let cache = TranslationCache::new(&config.cache)?;
+        let cache = TranslationCache::from_app_config(&config)?;
 
+        Self::from_parts(translator, cache, config)
+    }
+
+    /// Create with a custom translator and a cache shared across instances
+    pub fn with_translator_and_cache(
+        translator: Arc<dyn Translator>,
+        config: AppConfig,
+        cache: TranslationCache,
+    ) -> Result<Self> {
+        Self::from_parts(translator, cache, config)
+    }
+
+    fn from_parts(
+        translator: Arc<dyn Translator>,
+        cache: TranslationCache,
+        config: AppConfig,
+    ) -> Result<Self> {
+        config.budget.validate(&config.pricing)?;
+        config.rate_limit.validate()?;
+        config.quality.validate()?;
+        let context = TranslationContext::new(config.load_glossary()?)
+            .with_quality(QualityValidator::new(&config.quality));
+        let request_slots = Arc::new(Semaphore::new(config.max_in_flight_requests.max(1)));
         Ok(Self {
             translator,
             cache,
             config,
+            context,
+            request_slots,
+            languages_checked: OnceCell::new(),
         })
     }
 
@@ -85,7 +247,8 @@ impl PdfTranslator {
         doc: &PdfDocument,
         page_num: usize,
     ) -> Result<TranslatedPage> {
-        self.translate_page_impl(doc, page_num, false, None).await
+        self.translate_page_impl(doc, page_num, false, None, None)
+            .await
     }
 
     /// Translate a single page, optionally bypassing cache
@@ -95,7 +258,8 @@ impl PdfTranslator {
         page_num: usize,
         force: bool,
     ) -> Result<TranslatedPage> {
-        self.translate_page_impl(doc, page_num, force, None).await
+        self.translate_page_impl(doc, page_num, force, None, None)
+            .await
     }
 
     /// Translate a page as a prefetch (logged differently)
@@ -104,7 +268,25 @@ impl PdfTranslator {
         doc: &PdfDocument,
         page_num: usize,
     ) -> Result<TranslatedPage> {
-        self.translate_page_impl(doc, page_num, false, Some("(prefetch)"))
+        self.translate_page_impl(doc, page_num, false, Some("(prefetch)"), None)
+            .await
+    }
+
+    /// Translate a page block by block, reporting partial translations as they arrive.
+    ///
+    /// The finished page is cached like [`Self::translate_page_force`]; a cache
+    /// hit returns immediately without any previews.
+    pub async fn translate_page_streaming<F>(
+        &self,
+        doc: &PdfDocument,
+        page_num: usize,
+        force: bool,
+        mut on_preview: F,
+    ) -> Result<TranslatedPage>
+    where
+        F: FnMut(BlockPreview) + Send,
+    {
+        self.translate_page_impl(doc, page_num, force, None, Some(&mut on_preview))
             .await
     }
 
@@ -115,6 +297,7 @@ impl PdfTranslator {
         page_num: usize,
         force: bool,
         label: Option<&str>,
+        preview: Option<&mut (dyn FnMut(BlockPreview) + Send)>,
     ) -> Result<TranslatedPage> {
         // MuPDF parsing is synchronous and must not block an async runtime worker.
         let extraction_doc = doc.clone();
@@ -146,23 +329,25 @@ impl PdfTranslator {
         }
 
         let translator_identity = self.translator.cache_identity();
-        let cache_key = CacheKey::from_page(
-            doc.cache_id(),
-            page_num,
-            &page_text,
-            &translator_identity,
-            &self.config.source_lang,
-            &self.config.target_lang,
-            self.config.text_color,
-        );
+        let cache_key = self.page_cache_key(doc, page_num, &page_text, &translator_identity);
 
         // Check cache (unless force is set)
-        if !force && let Some(cached) = self.cache.get(&cache_key).await {
+        if !force
+            && self.config.cache.pages_enabled
+            && let Some(cached) = self.cache.get(&cache_key).await
+        {
             debug!("Cache hit for page {}", page_num);
             return Ok(TranslatedPage {
                 page_num,
                 pdf_bytes: cached,
                 from_cache: true,
+                usage: TokenUsage::default(),
+                cost: self
+                    .config
+                    .pricing
+                    .estimate(translator_identity.model(), TokenUsage::default()),
+                memory: MemoryHits::default(),
+                blocks: Vec::new(),
             });
         }
 
@@ -174,29 +359,192 @@ impl PdfTranslator {
             label.unwrap_or("")
         );
 
-        // Translate each block
-        let mut overlays = Vec::with_capacity(blocks.len());
-        for block in blocks {
-            if block.text.trim().is_empty() {
-                continue;
+        // Translate all non-empty blocks together so batching backends can share
+        // one request (and the surrounding paragraphs as context) per page.
+        // Blocks already written in the target language keep the original text.
+        let target_lang = &self.config.target_lang;
+        let (blocks, skipped): (Vec<TextBlock>, Vec<TextBlock>) = blocks
+            .into_iter()
+            .filter(|block| !block.text.trim().is_empty())
+            .partition(|block| {
+                !block
+                    .language
+                    .as_ref()
+                    .is_some_and(|lang| language::same_language(lang, target_lang))
+            });
+        if !skipped.is_empty() {
+            debug!(
+                "Skipping {} blocks on page {} already in {}",
+                skipped.len(),
+                page_num,
+                target_lang
+            );
+        }
+        let texts: Vec<&str> = blocks.iter().map(|block| block.text.as_str()).collect();
+        let source_lang = self.source_lang_for(&blocks);
+        self.check_languages().await?;
+
+        // A forced retranslation must not reuse cached or remembered blocks either.
+        let cached = if force {
+            vec![None; blocks.len()]
+        } else {
+            self.cached_blocks(&texts, &source_lang, &translator_identity)
+                .await
+        };
+        let (remembered, memory_hits) = if force {
+            (vec![None; blocks.len()], MemoryHits::default())
+        } else {
+            self.recall(&blocks, &cached, &source_lang, &translator_identity)
+                .await
+        };
+        let reused = cached.iter().flatten().count();
+        if reused > 0 {
+            debug!(
+                "Reused {} of {} blocks on page {} from the block cache",
+                reused,
+                blocks.len(),
+                page_num
+            );
+        }
+        if memory_hits.hits() > 0 {
+            info!(
+                "Reused {} of {} blocks on page {} from the translation memory ({} fuzzy)",
+                memory_hits.hits(),
+                memory_hits.lookups,
+                page_num,
+                memory_hits.fuzzy
+            );
+        }
+
+        let known: Vec<Option<String>> = cached
+            .into_iter()
+            .zip(&remembered)
+            .map(|(cached, recalled)| cached.or_else(|| recalled.clone()))
+            .collect();
+        let pending = known.iter().any(Option::is_none);
+        let reservation = if pending {
+            Some(self.reserve_budget(doc, &texts, &known, &translator_identity)?)
+        } else {
+            None
+        };
+
+        let page = {
+            let _slot = if pending {
+                Some(self.request_slots.acquire().await.map_err(|_| {
+                    Error::TranslationRequest("translator was shut down".to_string())
+                })?)
+            } else {
+                None
+            };
+            match preview {
+                Some(on_preview) => {
+                    self.stream_blocks(
+                        &blocks,
+                        &known,
+                        &source_lang,
+                        &translator_identity,
+                        on_preview,
+                    )
+                    .await
+                }
+                None => {
+                    self.translate_pending(&texts, &known, &source_lang, &translator_identity)
+                        .await
+                }
             }
+        };
+        let page = match page {
+            Ok(page) => page,
+            Err(error) => {
+                // Requests that succeeded before the failure were still billed.
+                let (error, usage) = error.into_unbilled();
+                if let Some(reservation) = reservation {
+                    let cost = self
+                        .config
+                        .pricing
+                        .estimate(translator_identity.model(), usage);
+                    reservation.settle(usage, cost);
+                }
+                return Err(error);
+            }
+        };
+        let PageTranslation {
+            translations,
+            identities,
+            usage,
+            cost,
+            flagged,
+        } = page;
+        if let Some(reservation) = reservation {
+            reservation.settle(usage, cost);
+        }
+        if translations.len() != blocks.len() {
+            return Err(Error::TranslationInvalidResponse(format!(
+                "translator returned {} translations for {} blocks",
+                translations.len(),
+                blocks.len()
+            )));
+        }
 
-            let translated = self
-                .translator
-                .translate(
-                    &block.text,
-                    &self.config.source_lang,
-                    &self.config.target_lang,
-                )
-                .await?;
+        // Fresh translations are checked and cached, under the backend that
+        // produced each; only those passing every check are remembered. Cached
+        // and remembered blocks were checked when first translated. Flagged
+        // blocks keep their original text and are neither, so they are retried.
+        let mut fresh: Vec<SegmentGroup> = Vec::new();
+        let mut learned: Vec<SegmentGroup> = Vec::new();
+        for (index, (((text, translated), prior), identity)) in texts
+            .iter()
+            .zip(&translations)
+            .zip(&known)
+            .zip(&identities)
+            .enumerate()
+        {
+            if flagged.contains(&index) {
+                warn!(
+                    "Block on page {} failed validation and keeps its original text",
+                    page_num
+                );
+                continue;
+            }
+            if prior.is_some() {
+                continue;
+            }
+            let mut accepted = true;
+            for violation in self.context.glossary.violations(text, translated) {
+                warn!(
+                    "Glossary term \"{}\" was not rendered as \"{}\" on page {}",
+                    violation.source, violation.expected, page_num
+                );
+                accepted = false;
+            }
+            for issue in self
+                .context
+                .quality
+                .check(text, translated, &self.config.target_lang)
+            {
+                warn!(
+                    "Block on page {} failed the {} check: {}",
+                    page_num, issue.rule, issue.reason
+                );
+                accepted = false;
+            }
+            let segment = ((*text).to_string(), translated.clone());
+            if accepted {
+                push_grouped(&mut learned, identity, segment.clone());
+            }
+            push_grouped(&mut fresh, identity, segment);
+        }
 
-            overlays.push(pdf::overlay::TranslationOverlay {
+        let overlays: Vec<_> = blocks
+            .into_iter()
+            .zip(translations)
+            .map(|(block, translated)| pdf::overlay::TranslationOverlay {
                 bbox: block.bbox,
                 original: block.text,
                 translated,
                 font_size: block.font_size,
-            });
-        }
+            })
+            .collect();
 
         // lopdf overlay generation is synchronous and uses owned inputs off-runtime.
         let overlay_options = OverlayOptions {
@@ -204,23 +552,537 @@ impl PdfTranslator {
             ..Default::default()
         };
         let pdf_data = doc.bytes_arc();
-        let pdf_bytes = tokio::task::spawn_blocking(move || {
+        let held = flagged.clone();
+        let (pdf_bytes, overlays) = tokio::task::spawn_blocking(move || {
             let overlay = PdfOverlay::new(overlay_options);
-            overlay.create_translated_page(pdf_data.as_slice(), page_num, &overlays)
+            let bytes = if held.is_empty() {
+                overlay.create_translated_page(pdf_data.as_slice(), page_num, &overlays)
+            } else {
+                let drawn: Vec<_> = overlays
+                    .iter()
+                    .enumerate()
+                    .filter(|(index, _)| !held.contains(index))
+                    .map(|(_, overlay)| overlay.clone())
+                    .collect();
+                overlay.create_translated_page(pdf_data.as_slice(), page_num, &drawn)
+            };
+            bytes.map(|bytes| (bytes, overlays))
         })
         .await
         .map_err(|_| Error::PdfOverlay("overlay worker failed".to_string()))??;
 
-        // Store in cache
-        self.cache.insert(&cache_key, pdf_bytes.clone()).await;
+        let blocks: Vec<ProjectBlock> = overlays
+            .into_iter()
+            .zip(&remembered)
+            .enumerate()
+            .map(|(index, (overlay, recalled))| {
+                let status = if flagged.contains(&index) {
+                    BlockStatus::Flagged
+                } else if recalled.is_some() {
+                    BlockStatus::Remembered
+                } else {
+                    BlockStatus::Translated
+                };
+                ProjectBlock::translated(overlay, status)
+            })
+            .chain(skipped.into_iter().map(ProjectBlock::kept))
+            .collect();
+
+        // Store under the backend that actually translated the page; a fallback
+        // chain may have skipped the backend the lookup was keyed on, and a page
+        // streamed from several backends is only stored block by block.
+        let page_identity = match fresh.as_slice() {
+            [] => Some(&translator_identity),
+            [(identity, _)] => Some(identity),
+            _ => None,
+        };
+        if let Some(identity) = page_identity
+            && self.config.cache.pages_enabled
+            && flagged.is_empty()
+        {
+            let cache_key = if *identity == translator_identity {
+                cache_key
+            } else {
+                self.page_cache_key(doc, page_num, &page_text, identity)
+            };
+            self.cache.insert(&cache_key, pdf_bytes.clone()).await;
+        }
+        for (identity, segments) in &fresh {
+            self.cache_blocks(segments, &source_lang, identity).await;
+        }
+        for (identity, segments) in learned {
+            self.remember(segments, &source_lang, &identity).await;
+        }
 
         Ok(TranslatedPage {
             page_num,
             pdf_bytes,
             from_cache: false,
+            usage,
+            cost,
+            memory: memory_hits,
+            blocks,
         })
     }
 
+    /// Tokens and estimated cost spent on a document so far by every
+    /// translator sharing this one's cache.
+    pub fn document_usage(&self, doc: &PdfDocument) -> UsageSummary {
+        self.cache.ledger().usage(doc.cache_id())
+    }
+
+    /// Reserve the estimated usage of translating the blocks not yet `known`
+    /// against the document's budget, refusing once a limit is reached.
+    fn reserve_budget(
+        &self,
+        doc: &PdfDocument,
+        texts: &[&str],
+        known: &[Option<String>],
+        identity: &TranslatorCacheIdentity,
+    ) -> Result<Reservation<'_>> {
+        let pending: Vec<&str> = texts
+            .iter()
+            .zip(known)
+            .filter(|(_, known)| known.is_none())
+            .map(|(text, _)| *text)
+            .collect();
+        let usage = TokenUsage::estimate(&pending);
+        let cost = self
+            .config
+            .pricing
+            .estimate(identity.model(), usage)
+            .unwrap_or_default();
+        self.cache
+            .ledger()
+            .reserve(doc.cache_id(), usage, cost, |spent| {
+                self.check_budget(spent)
+            })
+    }
+
+    /// Refuse to translate another page once the usage spent on a document,
+    /// or reserved by its pages in flight, reached a budget limit.
+    fn check_budget(&self, spent: &UsageSummary) -> Result<()> {
+        let budget = self.config.budget;
+        if let Some(max) = budget.max_tokens_per_document
+            && spent.usage.total_tokens() >= max
+        {
+            return Err(Error::TranslationBudgetExceeded(format!(
+                "used {} of {max} tokens for this document",
+                spent.usage.total_tokens()
+            )));
+        }
+        if let Some(max) = budget.max_requests_per_document
+            && spent.usage.requests >= max
+        {
+            return Err(Error::TranslationBudgetExceeded(format!(
+                "sent {} of {max} requests for this document",
+                spent.usage.requests
+            )));
+        }
+        if let Some(max) = budget.max_cost {
+            let pricing = &self.config.pricing;
+            match spent.cost {
+                Some(cost) if cost < max => {}
+                Some(cost) => {
+                    return Err(Error::TranslationBudgetExceeded(format!(
+                        "spent an estimated {} of {} for this document",
+                        pricing.format_cost(cost),
+                        pricing.format_cost(max)
+                    )));
+                }
+                None => {
+                    return Err(Error::TranslationBudgetExceeded(
+                        "tokens were billed to a model without a price, so max_cost cannot be \
+                         enforced"
+                            .to_string(),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Translate the blocks without a cached or remembered translation in one batch.
+    async fn translate_pending(
+        &self,
+        texts: &[&str],
+        known: &[Option<String>],
+        source_lang: &Lang,
+        identity: &TranslatorCacheIdentity,
+    ) -> Result<PageTranslation> {
+        let pending: Vec<&str> = texts
+            .iter()
+            .zip(known)
+            .filter(|(_, known)| known.is_none())
+            .map(|(text, _)| *text)
+            .collect();
+        if pending.is_empty() {
+            return Ok(PageTranslation {
+                translations: known.iter().flatten().cloned().collect(),
+                identities: vec![identity.clone(); known.len()],
+                usage: TokenUsage::default(),
+                cost: self
+                    .config
+                    .pricing
+                    .estimate(identity.model(), TokenUsage::default()),
+                flagged: Vec::new(),
+            });
+        }
+
+        let batch = self
+            .translator
+            .translate_batch_traced(
+                &pending,
+                source_lang,
+                &self.config.target_lang,
+                &self.context,
+            )
+            .await?;
+        if batch.translations.len() != pending.len() {
+            return Err(Error::TranslationInvalidResponse(format!(
+                "translator returned {} translations for {} blocks",
+                batch.translations.len(),
+                pending.len()
+            ))
+            .with_usage(batch.usage));
+        }
+        let positions: Vec<usize> = known
+            .iter()
+            .enumerate()
+            .filter(|(_, known)| known.is_none())
+            .map(|(index, _)| index)
+            .collect();
+        let flagged = batch
+            .flagged
+            .iter()
+            .filter_map(|&index| positions.get(index).copied())
+            .collect();
+        let mut fresh = batch.translations.into_iter();
+        let translations = known
+            .iter()
+            .map(|known| known.clone().or_else(|| fresh.next()).unwrap_or_default())
+            .collect();
+        Ok(PageTranslation {
+            translations,
+            identities: vec![batch.identity.clone(); known.len()],
+            usage: batch.usage,
+            cost: self
+                .config
+                .pricing
+                .estimate(batch.identity.model(), batch.usage),
+            flagged,
+        })
+    }
+
+    /// Stream each block in turn, returning the final translations.
+    ///
+    /// Cached and remembered blocks are reported whole without calling the translator.
+    /// Each streamed block is attributed to the backend its stream reports, and
+    /// revised by that same backend. A failure carries the usage billed by the
+    /// blocks streamed before it.
+    async fn stream_blocks(
+        &self,
+        blocks: &[TextBlock],
+        known: &[Option<String>],
+        source_lang: &Lang,
+        identity: &TranslatorCacheIdentity,
+        on_preview: &mut (dyn FnMut(BlockPreview) + Send),
+    ) -> Result<PageTranslation> {
+        let mut translations = Vec::with_capacity(blocks.len());
+        let mut identities = Vec::with_capacity(blocks.len());
+        let mut usage = TokenUsage::default();
+        let mut billed: Vec<(TranslatorCacheIdentity, TokenUsage)> = Vec::new();
+        let mut flagged = Vec::new();
+        for (index, block) in blocks.iter().enumerate() {
+            if let Some(Some(known)) = known.get(index) {
+                on_preview(BlockPreview {
+                    block: index,
+                    block_count: blocks.len(),
+                    bbox: block.bbox,
+                    text: known.clone(),
+                });
+                translations.push(known.clone());
+                identities.push(identity.clone());
+                continue;
+            }
+            let mut partials = self.translator.translate_stream(
+                &block.text,
+                source_lang,
+                &self.config.target_lang,
+                &self.context,
+            );
+            let mut latest = None;
+            while let Some(partial) = partials.next().await {
+                let partial = partial.map_err(|error| error.with_usage(usage))?;
+                on_preview(BlockPreview {
+                    block: index,
+                    block_count: blocks.len(),
+                    bbox: block.bbox,
+                    text: partial.text.clone(),
+                });
+                latest = Some(partial);
+            }
+            let last = latest.ok_or_else(|| {
+                Error::TranslationInvalidResponse(
+                    "translation stream ended without output".to_string(),
+                )
+                .with_usage(usage)
+            })?;
+            usage += last.usage;
+            let mut block_usage = last.usage;
+            let block_identity = last.identity.clone().unwrap_or_else(|| identity.clone());
+            let target = &self.config.target_lang;
+            let translated = if last.flagged {
+                flagged.push(index);
+                last.text
+            } else if self
+                .context
+                .quality
+                .check(&block.text, &last.text, target)
+                .is_empty()
+            {
+                last.text
+            } else {
+                let mut revise_usage = TokenUsage::default();
+                let revised = self
+                    .translator
+                    .revise(
+                        &block.text,
+                        source_lang,
+                        target,
+                        &self.context,
+                        last,
+                        &mut revise_usage,
+                    )
+                    .await
+                    .map_err(|error| error.with_usage(usage).with_usage(revise_usage))?;
+                usage += revise_usage;
+                block_usage += revise_usage;
+                on_preview(BlockPreview {
+                    block: index,
+                    block_count: blocks.len(),
+                    bbox: block.bbox,
+                    text: revised.clone(),
+                });
+                revised
+            };
+            translations.push(translated);
+            billed.push((block_identity.clone(), block_usage));
+            identities.push(block_identity);
+        }
+        let cost = if billed.is_empty() {
+            self.config.pricing.estimate(identity.model(), usage)
+        } else {
+            billed
+                .iter()
+                .map(|(identity, usage)| self.config.pricing.estimate(identity.model(), *usage))
+                .sum()
+        };
+        Ok(PageTranslation {
+            translations,
+            identities,
+            usage,
+            cost,
+            flagged,
+        })
+    }
+
+    /// Source language sent with `blocks`: the configured one, or for `auto`
+    /// the dominant detected language of the blocks when there is one.
+    fn source_lang_for(&self, blocks: &[TextBlock]) -> Lang {
+        if self.config.source_lang.as_str() != "auto" {
+            return self.config.source_lang.clone();
+        }
+        TextBlock::dominant_language(blocks).unwrap_or_else(|| self.config.source_lang.clone())
+    }
+
+    /// Translation memory matches for the `blocks` missing from the block
+    /// cache, looked up off the async runtime.
+    async fn recall(
+        &self,
+        blocks: &[TextBlock],
+        cached: &[Option<String>],
+        source_lang: &Lang,
+        identity: &TranslatorCacheIdentity,
+    ) -> (Vec<Option<String>>, MemoryHits) {
+        let Some(memory) = self.cache.memory().cloned() else {
+            return (vec![None; blocks.len()], MemoryHits::default());
+        };
+        let texts: Vec<Option<String>> = blocks
+            .iter()
+            .zip(cached)
+            .map(|(block, cached)| cached.is_none().then(|| block.text.clone()))
+            .collect();
+        let looked_up: Vec<bool> = texts.iter().map(Option::is_some).collect();
+        let source_lang = source_lang.clone();
+        let target_lang = self.config.target_lang.clone();
+        let identity = self.output_identity(identity);
+        let found = tokio::task::spawn_blocking(move || {
+            texts
+                .iter()
+                .map(|text| {
+                    let text = text.as_deref()?;
+                    memory.lookup(text, &source_lang, &target_lang, &identity)
+                })
+                .collect::<Vec<_>>()
+        })
+        .await
+        .unwrap_or_else(|_| {
+            warn!("Translation memory lookup worker failed");
+            vec![None; blocks.len()]
+        });
+
+        let mut hits = MemoryHits::default();
+        for (recalled, _) in found
+            .iter()
+            .zip(&looked_up)
+            .filter(|(_, looked_up)| **looked_up)
+        {
+            hits.record(recalled.as_ref());
+        }
+        let translations = found
+            .into_iter()
+            .map(|recalled| recalled.map(MemoryMatch::into_translation))
+            .collect();
+        (translations, hits)
+    }
+
+    /// Translations of `texts` in the block cache under `identity`.
+    async fn cached_blocks(
+        &self,
+        texts: &[&str],
+        source_lang: &Lang,
+        identity: &TranslatorCacheIdentity,
+    ) -> Vec<Option<String>> {
+        let identity = self.output_identity(identity);
+        let mut cached = Vec::with_capacity(texts.len());
+        for text in texts {
+            let key = CacheKey::for_block(text, &identity, source_lang, &self.config.target_lang);
+            cached.push(self.cache.get_text(&key).await);
+        }
+        cached
+    }
+
+    /// Store translated blocks in the block cache under `identity`.
+    async fn cache_blocks(
+        &self,
+        segments: &[(String, String)],
+        source_lang: &Lang,
+        identity: &TranslatorCacheIdentity,
+    ) {
+        let identity = self.output_identity(identity);
+        for (text, translated) in segments {
+            let key = CacheKey::for_block(text, &identity, source_lang, &self.config.target_lang);
+            self.cache.insert_text(&key, translated).await;
+        }
+    }
+
+    /// Store translated blocks in the translation memory under `identity`.
+    async fn remember(
+        &self,
+        segments: Vec<(String, String)>,
+        source_lang: &Lang,
+        identity: &TranslatorCacheIdentity,
+    ) {
+        let Some(memory) = self.cache.memory().cloned() else {
+            return;
+        };
+        if segments.is_empty() {
+            return;
+        }
+        let source_lang = source_lang.clone();
+        let target_lang = self.config.target_lang.clone();
+        let identity = self.output_identity(identity);
+        let _ = tokio::task::spawn_blocking(move || {
+            let pairs: Vec<(&str, &str)> = segments
+                .iter()
+                .map(|(source, target)| (source.as_str(), target.as_str()))
+                .collect();
+            if let Err(e) = memory.insert(&source_lang, &target_lang, &identity, &pairs) {
+                warn!("Failed to update translation memory: {e}");
+            }
+        })
+        .await;
+    }
+
+    /// `identity` extended with the document settings that change the output.
+    fn output_identity(&self, identity: &TranslatorCacheIdentity) -> TranslatorCacheIdentity {
+        if self.context.glossary.is_empty() {
+            identity.clone()
+        } else {
+            identity
+                .clone()
+                .with_option("glossary", self.context.glossary.fingerprint())
+        }
+    }
+
+    /// Cache key for a page translated by the backend with `identity`.
+    fn page_cache_key(
+        &self,
+        doc: &PdfDocument,
+        page_num: usize,
+        page_text: &str,
+        identity: &TranslatorCacheIdentity,
+    ) -> CacheKey {
+        CacheKey::from_page(
+            doc.cache_id(),
+            page_num,
+            page_text,
+            &self.output_identity(identity),
+            &self.config.source_lang,
+            &self.config.target_lang,
+            self.config.text_color,
+        )
+    }
+
+    /// Verify the translator supports the configured language pair.
+    ///
+    /// The backend is asked until it accepts the pair once; later calls return
+    /// right away.
+    pub async fn check_languages(&self) -> Result<()> {
+        self.languages_checked
+            .get_or_try_init(|| {
+                self.translator
+                    .check_languages(&self.config.source_lang, &self.config.target_lang)
+            })
+            .await?;
+        Ok(())
+    }
+
+    /// Translate several pages, keeping up to `max_concurrent_pages` in flight.
+    ///
+    /// `on_page` is called as each page finishes, which may be out of order;
+    /// the returned pages always follow the order of `pages`.
+    pub async fn translate_pages<F>(
+        &self,
+        doc: &PdfDocument,
+        pages: &[usize],
+        mut on_page: F,
+    ) -> Result<Vec<TranslatedPage>>
+    where
+        F: FnMut(&TranslatedPage),
+    {
+        self.check_languages().await?;
+
+        let mut in_flight = stream::iter(pages.iter().copied().enumerate())
+            .map(|(position, page_num)| async move {
+                self.translate_page(doc, page_num)
+                    .await
+                    .map(|page| (position, page))
+            })
+            .buffer_unordered(self.config.max_concurrent_pages.max(1));
+
+        let mut translated = Vec::with_capacity(pages.len());
+        while let Some(result) = in_flight.next().await {
+            let (position, page) = result?;
+            on_page(&page);
+            translated.push((position, page));
+        }
+
+        translated.sort_unstable_by_key(|(position, _)| *position);
+        Ok(translated.into_iter().map(|(_, page)| page).collect())
+    }
+
     /// Translate all pages and combine into a single PDF
     pub async fn translate_document(
         &self,
@@ -228,16 +1090,21 @@ impl PdfTranslator {
         progress_callback: Option<Box<dyn Fn(usize, usize) + Send>>,
     ) -> Result<Vec<u8>> {
         let total_pages = doc.page_count();
-        let mut translated_pages = Vec::with_capacity(total_pages);
-
-        for page_num in 0..total_pages {
-            let result = self.translate_page(doc, page_num).await?;
-            translated_pages.push(result.pdf_bytes);
+        let pages: Vec<usize> = (0..total_pages).collect();
+        let mut completed = 0;
 
-            if let Some(callback) = &progress_callback {
-                callback(page_num + 1, total_pages);
-            }
-        }
+        let translated = self
+            .translate_pages(doc, &pages, move |_| {
+                completed += 1;
+                if let Some(callback) = &progress_callback {
+                    callback(completed, total_pages);
+                }
+            })
+            .await?;
+        let summary: UsageSummary = translated.iter().collect();
+        info!("Document usage: {}", summary.describe(&self.config.pricing));
+        let translated_pages: Vec<Vec<u8>> =
+            translated.into_iter().map(|page| page.pdf_bytes).collect();
 
         tokio::task::spawn_blocking(move || pdf::overlay::combine_pdfs(&translated_pages))
             .await
@@ -269,7 +1136,23 @@ pub fn render_page_webp(doc: &PdfDocument, page_num: usize, scale: f32) -> Resul
     renderer.render_page_webp(page_num)
 }
 
+/// Translated segments stored under the backend that produced them.
+type SegmentGroup = (TranslatorCacheIdentity, Vec<(String, String)>);
+
+/// Add `segment` to the group of `identity`, keeping the order groups were first seen in.
+fn push_grouped(
+    groups: &mut Vec<SegmentGroup>,
+    identity: &TranslatorCacheIdentity,
+    segment: (String, String),
+) {
+    match groups.iter_mut().find(|(known, _)| known == identity) {
+        Some((_, segments)) => segments.push(segment),
+        None => groups.push((identity.clone(), vec![segment])),
+    }
+}
+
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
 
@@ -279,4 +1162,68 @@ mod tests {
         assert_eq!(config.source_lang.as_str(), "fr");
         assert_eq!(config.target_lang.as_str(), "en");
     }
+
+    #[test]
+    fn usage_summary_loses_cost_only_for_unpriced_billed_pages() {
+        let page = |prompt_tokens, cost| TranslatedPage {
+            page_num: 0,
+            pdf_bytes: Vec::new(),
+            from_cache: false,
+            usage: TokenUsage {
+                prompt_tokens,
+                completion_tokens: 0,
+                requests: 0,
+            },
+            cost,
+            memory: MemoryHits::default(),
+            blocks: Vec::new(),
+        };
+
+        let priced = [page(10, Some(0.5)), page(0, None)];
+        let summary: UsageSummary = priced.iter().collect();
+        assert_eq!(summary.usage.prompt_tokens, 10);
+        assert_eq!(summary.cost, Some(0.5));
+
+        let unpriced = [page(10, Some(0.5)), page(5, None)];
+        let summary: UsageSummary = unpriced.iter().collect();
+        assert_eq!(summary.usage.total_tokens(), 15);
+        assert_eq!(summary.cost, None);
+    }
+
+    /// Mock translator that counts language pair checks.
+    #[derive(Default)]
+    struct PairChecks(std::sync::atomic::AtomicUsize);
+
+    #[async_trait::async_trait]
+    impl Translator for PairChecks {
+        fn info(&self) -> translator::TranslatorInfo {
+            MockTranslator.info()
+        }
+
+        fn cache_identity(&self) -> TranslatorCacheIdentity {
+            MockTranslator.cache_identity()
+        }
+
+        async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
+            MockTranslator.translate(text, source, target).await
+        }
+
+        async fn check_languages(&self, _source: &Lang, _target: &Lang) -> Result<()> {
+            self.0.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn language_pair_is_checked_once_per_translator() {
+        let mut config = AppConfig::default();
+        config.cache.disk_enabled = false;
+        let checks = Arc::new(PairChecks::default());
+        let pdf_translator = PdfTranslator::with_translator(checks.clone(), config).unwrap();
+
+        for _ in 0..3 {
+            pdf_translator.check_languages().await.unwrap();
+        }
+        assert_eq!(checks.0.load(std::sync::atomic::Ordering::SeqCst), 1);
+    }
 }
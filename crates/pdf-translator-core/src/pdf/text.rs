@@ -2,7 +2,9 @@ use mupdf::TextPageOptions;
 
 use super::document::PdfDocument;
 use super::page_index::PageIndex;
+use crate::config::Lang;
 use crate::error::{Error, Result};
+use crate::language;
 
 /// A text block extracted from a PDF page with bounding box
 #[derive(Debug, Clone)]
@@ -15,6 +17,19 @@ pub struct TextBlock {
     pub font_size: f32,
     /// Number of lines in the original text
     pub line_count: usize,
+    /// Language the text is written in, when it could be detected
+    pub language: Option<Lang>,
+}
+
+impl TextBlock {
+    /// Language covering the most text among `blocks`, if any was detected.
+    pub fn dominant_language(blocks: &[Self]) -> Option<Lang> {
+        language::dominant(
+            blocks
+                .iter()
+                .filter_map(|block| Some((block.language.as_ref()?, block.text.len()))),
+        )
+    }
 }
 
 /// Bounding box in PDF coordinates
@@ -203,6 +218,7 @@ impl<'a> TextExtractor<'a> {
                     bbox,
                     font_size,
                     line_count,
+                    language: None,
                 });
             }
         }
@@ -211,11 +227,25 @@ impl<'a> TextExtractor<'a> {
         let blocks = Self::merge_hyphenated_blocks(blocks);
 
         // Remove duplicate/overlapping blocks (e.g., from OCR layers)
-        let blocks = Self::deduplicate_overlapping_blocks(blocks);
+        let mut blocks = Self::deduplicate_overlapping_blocks(blocks);
+
+        // Detect languages on the final text, after fragments were merged
+        for block in &mut blocks {
+            block.language = language::detect(&block.text);
+        }
 
         Ok(blocks)
     }
 
+    /// Detect the dominant language of the first `max_pages` pages
+    pub fn detect_document_language(&self, max_pages: usize) -> Result<Option<Lang>> {
+        let mut blocks = Vec::new();
+        for page_num in 0..self.doc.page_count().min(max_pages) {
+            blocks.extend(self.extract_page_blocks(page_num)?);
+        }
+        Ok(TextBlock::dominant_language(&blocks))
+    }
+
     /// Remove blocks that significantly overlap with other blocks.
     ///
     /// Some PDFs have duplicate text (e.g., OCR layer + visible text), which
@@ -0,0 +1,296 @@
+//! Placeholder masking for spans that must survive translation verbatim.
+//!
+//! Models tend to reformat references such as "Art. 12.3", rewrite URLs, or
+//! translate code identifiers. A [`Protector`] replaces every span matched by
+//! its patterns with a numbered placeholder like `⟦1⟧` before a block is sent,
+//! and [`Masked::restore`] puts the original spans back into the answer.
+
+use regex::Regex;
+use std::fmt::Write;
+use std::sync::LazyLock;
+
+use crate::config::ProtectConfig;
+use crate::error::{Error, Result};
+
+/// Patterns enabled by `builtin = true`.
+const BUILTIN_PATTERNS: &[&str] = &[
+    // URLs
+    r"(?i)\b(?:https?://|www\.)[^\s<>]*[^\s<>.,;:!?)\]}'\x22]",
+    // Email addresses
+    r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+",
+    // ISBNs, with or without the prefix
+    r"(?i)\bISBN(?:-1[03])?:?\s?((?:97[89][- ]?)?\d{1,5}[- ]?\d+[- ]?\d+[- ]?[\dX])\b",
+    r"\b97[89]-\d{1,5}-\d+-\d+-\d\b",
+    // Inline code
+    r"`[^`\n]+`",
+    // Code identifiers: snake_case, paths, and calls
+    r"\b[A-Za-z_]\w*(?:(?:_|::)[A-Za-z0-9]\w*)+(?:\(\))?",
+    r"\b[A-Za-z_]\w*\(\)",
+    // Numbers of articles, sections, and paragraphs
+    r"(?i)(?:\b(?:art|arts|sec|secs|section|chapter|ch|no|nr|para)\.?|§§?)\s*(\d+(?:\.\d+)*[a-z]?)\b",
+];
+
+/// Placeholder as written into masked text.
+#[allow(clippy::expect_used)]
+pub(crate) static PLACEHOLDER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"⟦\s*(\d+)\s*⟧").expect("Invalid placeholder pattern"));
+
+/// Spans matched by a set of patterns, masked with numbered placeholders.
+#[derive(Debug, Clone, Default)]
+pub struct Protector {
+    patterns: Vec<Regex>,
+    fingerprint: String,
+}
+
+/// Text with protected spans replaced by placeholders.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Masked {
+    /// Text to translate, containing placeholders
+    pub text: String,
+    /// Original span of each placeholder; `⟦1⟧` is the first entry
+    spans: Vec<String>,
+}
+
+impl Protector {
+    /// Compile the patterns enabled in `config`.
+    ///
+    /// Fails on a pattern that is not a valid regular expression.
+    pub fn new(config: &ProtectConfig) -> Result<Self> {
+        if !config.enabled {
+            return Ok(Self::default());
+        }
+
+        let builtin: &[&str] = if config.builtin {
+            BUILTIN_PATTERNS
+        } else {
+            &[]
+        };
+        let sources: Vec<&str> = builtin
+            .iter()
+            .copied()
+            .chain(config.patterns.iter().map(String::as_str))
+            .collect();
+        let patterns = sources
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| Error::ConfigInvalid {
+                    field: "protect.patterns".to_string(),
+                    reason: format!("invalid pattern {pattern:?}: {e}"),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            patterns,
+            fingerprint: Self::compute_fingerprint(&sources),
+        })
+    }
+
+    /// Whether no pattern is configured.
+    pub const fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Stable hash of the patterns, for cache keys.
+    pub fn fingerprint(&self) -> &str {
+        &self.fingerprint
+    }
+
+    /// Replace every protected span of `text` with a placeholder.
+    ///
+    /// Overlapping matches keep the one starting first (the longest on a tie),
+    /// and repeated spans share a placeholder.
+    pub fn mask(&self, text: &str) -> Masked {
+        let mut ranges: Vec<(usize, usize)> = self
+            .patterns
+            .iter()
+            .flat_map(|pattern| {
+                pattern.captures_iter(text).filter_map(|captures| {
+                    captures
+                        .get(1)
+                        .or_else(|| captures.get(0))
+                        .map(|found| (found.start(), found.end()))
+                })
+            })
+            .filter(|(start, end)| start < end)
+            .collect();
+        ranges.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1)));
+
+        let mut masked = String::with_capacity(text.len());
+        let mut spans: Vec<String> = Vec::new();
+        let mut cursor = 0;
+        for (start, end) in ranges {
+            if start < cursor {
+                continue;
+            }
+            let span = &text[start..end];
+            let index = spans
+                .iter()
+                .position(|known| known == span)
+                .unwrap_or_else(|| {
+                    spans.push(span.to_string());
+                    spans.len() - 1
+                });
+            masked.push_str(&text[cursor..start]);
+            let _ = write!(masked, "⟦{}⟧", index + 1);
+            cursor = end;
+        }
+        masked.push_str(&text[cursor..]);
+
+        Masked {
+            text: masked,
+            spans,
+        }
+    }
+
+    fn compute_fingerprint(patterns: &[&str]) -> String {
+        let mut context = md5::Context::new();
+        context.consume(b"pdf-translator-protect-v1");
+        for pattern in patterns {
+            context.consume((pattern.len() as u64).to_be_bytes());
+            context.consume(pattern.as_bytes());
+        }
+        format!("{:x}", context.compute())
+    }
+}
+
+impl Masked {
+    /// Whether nothing was masked.
+    pub const fn is_empty(&self) -> bool {
+        self.spans.is_empty()
+    }
+
+    /// Put the original spans back into a translation of [`Masked::text`].
+    ///
+    /// Fails when a placeholder is missing from `translated` or it contains
+    /// one that was never issued.
+    pub fn restore(&self, translated: &str) -> Result<String> {
+        if self.is_empty() {
+            return Ok(translated.to_string());
+        }
+
+        let missing = missing_placeholders(&self.text, translated);
+        if !missing.is_empty() {
+            return Err(Error::TranslationInvalidResponse(format!(
+                "translation lost placeholder {}",
+                missing.join(", ")
+            )));
+        }
+
+        let mut unknown = None;
+        let restored = PLACEHOLDER.replace_all(translated, |captures: &regex::Captures<'_>| {
+            let span = captures[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| index.checked_sub(1))
+                .and_then(|index| self.spans.get(index));
+            span.map_or_else(
+                || {
+                    unknown = Some(captures[0].to_string());
+                    captures[0].to_string()
+                },
+                Clone::clone,
+            )
+        });
+        if let Some(placeholder) = unknown {
+            return Err(Error::TranslationInvalidResponse(format!(
+                "translation contains unknown placeholder {placeholder}"
+            )));
+        }
+        Ok(restored.into_owned())
+    }
+}
+
+/// Placeholders of `source` that do not appear in `translated`.
+pub(crate) fn missing_placeholders(source: &str, translated: &str) -> Vec<String> {
+    let present: Vec<&str> = PLACEHOLDER
+        .captures_iter(translated)
+        .filter_map(|captures| captures.get(1))
+        .map(|index| index.as_str())
+        .collect();
+    let mut missing = Vec::new();
+    for captures in PLACEHOLDER.captures_iter(source) {
+        let placeholder = format!("⟦{}⟧", &captures[1]);
+        if !present.contains(&&captures[1]) && !missing.contains(&placeholder) {
+            missing.push(placeholder);
+        }
+    }
+    missing
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn protector(patterns: &[&str]) -> Protector {
+        Protector::new(&ProtectConfig {
+            patterns: patterns.iter().map(ToString::to_string).collect(),
+            ..ProtectConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn builtin_patterns_mask_references_links_and_code() {
+        let masked = protector(&[]).mask(
+            "Voir l'Art. 12.3 sur https://example.com/a?b=1, ISBN 978-3-16-148410-0 \
+             et `cargo build` via parse_args().",
+        );
+        assert_eq!(
+            masked.text,
+            "Voir l'Art. ⟦1⟧ sur ⟦2⟧, ISBN ⟦3⟧ et ⟦4⟧ via ⟦5⟧."
+        );
+        assert_eq!(
+            masked.spans,
+            [
+                "12.3",
+                "https://example.com/a?b=1",
+                "978-3-16-148410-0",
+                "`cargo build`",
+                "parse_args()"
+            ]
+        );
+    }
+
+    #[test]
+    fn restore_puts_spans_back_and_rejects_lost_placeholders() {
+        let masked = protector(&[r"ACME-\d+"]).mask("Le ticket ACME-42 remplace ACME-42.");
+        assert_eq!(masked.text, "Le ticket ⟦1⟧ remplace ⟦1⟧.");
+        assert_eq!(
+            masked.restore("Ticket ⟦ 1 ⟧ replaces ⟦1⟧.").unwrap(),
+            "Ticket ACME-42 replaces ACME-42."
+        );
+        assert!(matches!(
+            masked.restore("The ticket replaces it."),
+            Err(Error::TranslationInvalidResponse(_))
+        ));
+        assert!(matches!(
+            masked.restore("Ticket ⟦1⟧ replaces ⟦2⟧."),
+            Err(Error::TranslationInvalidResponse(_))
+        ));
+    }
+
+    #[test]
+    fn invalid_and_disabled_patterns() {
+        let result = Protector::new(&ProtectConfig {
+            patterns: vec!["(".to_string()],
+            ..ProtectConfig::default()
+        });
+        assert!(matches!(
+            result,
+            Err(Error::ConfigInvalid { field, .. }) if field == "protect.patterns"
+        ));
+
+        let disabled = Protector::new(&ProtectConfig {
+            enabled: false,
+            ..ProtectConfig::default()
+        })
+        .unwrap();
+        assert!(disabled.mask("https://example.com").is_empty());
+        assert_ne!(
+            protector(&[]).fingerprint(),
+            protector(&["x"]).fingerprint()
+        );
+    }
+}
@@ -0,0 +1,459 @@
+//! Checks catching common failures of model translations.
+//!
+//! Language models sometimes answer in the source language, wrap the
+//! translation in chatter, drop numbers or links, or ramble far past the input.
+//! A [`QualityValidator`] runs a list of [`QualityRule`]s over each translated
+//! block; chat backends re-ask blocks that fail, and the page pipeline logs
+//! whatever still fails.
+
+use regex::Regex;
+use std::fmt::Debug;
+use std::sync::{Arc, LazyLock};
+
+use crate::config::{Lang, QualityConfig};
+use crate::language::{self, Script};
+use crate::protect;
+
+/// Blocks shorter than this many characters skip the length and copy checks;
+/// headings and labels legitimately keep their length or stay unchanged.
+const MIN_CHECKED_CHARS: usize = 20;
+
+/// Letters an answer needs before its script is judged.
+const MIN_SCRIPT_LETTERS: usize = 10;
+
+/// Openings of answers that talk about the translation instead of giving it.
+const PREAMBLES: &[&str] = &[
+    "here is",
+    "here's",
+    "here are",
+    "sure,",
+    "sure!",
+    "certainly",
+    "of course",
+    "translation:",
+    "translated text:",
+    "the translation",
+    "below is",
+    "voici",
+    "aquí está",
+    "hier ist",
+];
+
+/// `http(s)://` and `www.` links.
+#[allow(clippy::expect_used)]
+static URL: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?i)\b(?:https?://|www\.)[^\s<>]+").expect("Invalid URL pattern")
+});
+/// Email addresses.
+#[allow(clippy::expect_used)]
+static EMAIL: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[\w.+-]+@[\w-]+(?:\.[\w-]+)+").expect("Invalid email pattern"));
+/// Numbers, including `.`/`,` grouping and decimal separators.
+#[allow(clippy::expect_used)]
+static NUMBER: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\d+(?:[.,]\d+)*").expect("Invalid number pattern"));
+
+/// A failed check on one translated block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QualityIssue {
+    /// Name of the rule that failed
+    pub rule: &'static str,
+    /// What was wrong, phrased so it can be shown to the model
+    pub reason: String,
+}
+
+/// One check run on every translated block.
+pub trait QualityRule: Debug + Send + Sync {
+    /// Short name used in logs
+    fn name(&self) -> &'static str;
+
+    /// Describe what is wrong with `translated`, or `None` if it passes.
+    fn check(&self, source: &str, translated: &str, target: &Lang) -> Option<String>;
+}
+
+/// Ordered list of quality rules and the re-ask budget for failing blocks.
+///
+/// The default validator has no rules and accepts every translation.
+#[derive(Debug, Clone, Default)]
+pub struct QualityValidator {
+    rules: Vec<Arc<dyn QualityRule>>,
+    max_reasks: u32,
+}
+
+impl QualityValidator {
+    /// Build the built-in rules enabled in `config`.
+    ///
+    /// Placeholders of protected spans are always checked.
+    pub fn new(config: &QualityConfig) -> Self {
+        let mut validator = Self {
+            rules: Vec::new(),
+            max_reasks: config.max_reasks,
+        }
+        .with_rule(Placeholders);
+        if config.untranslated {
+            validator = validator.with_rule(Untranslated);
+        }
+        if config.preamble {
+            validator = validator.with_rule(Preamble);
+        }
+        if config.preserved_tokens {
+            validator = validator.with_rule(PreservedTokens);
+        }
+        if config.length_ratio {
+            validator = validator.with_rule(LengthRatio {
+                min: config.min_length_ratio,
+                max: config.max_length_ratio,
+            });
+        }
+        validator
+    }
+
+    /// Add a rule run after the existing ones.
+    #[must_use]
+    pub fn with_rule(mut self, rule: impl QualityRule + 'static) -> Self {
+        self.rules.push(Arc::new(rule));
+        self
+    }
+
+    /// Whether no rule is configured.
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Extra requests a backend may spend per block to fix failed checks.
+    pub const fn max_reasks(&self) -> u32 {
+        self.max_reasks
+    }
+
+    /// Every rule `translated` fails, in rule order.
+    pub fn check(&self, source: &str, translated: &str, target: &Lang) -> Vec<QualityIssue> {
+        self.rules
+            .iter()
+            .filter_map(|rule| {
+                rule.check(source, translated, target)
+                    .map(|reason| QualityIssue {
+                        rule: rule.name(),
+                        reason,
+                    })
+            })
+            .collect()
+    }
+}
+
+/// The answer dropped placeholders standing in for protected spans.
+#[derive(Debug, Clone, Copy)]
+pub struct Placeholders;
+
+impl QualityRule for Placeholders {
+    fn name(&self) -> &'static str {
+        "placeholders"
+    }
+
+    fn check(&self, source: &str, translated: &str, _target: &Lang) -> Option<String> {
+        let missing = protect::missing_placeholders(source, translated);
+        (!missing.is_empty()).then(|| {
+            format!(
+                "the answer is missing the placeholders {}; copy them unchanged",
+                missing.join(", ")
+            )
+        })
+    }
+}
+
+/// The answer repeats the source, is not written in the target's script, or
+/// is detected as another language than the target.
+#[derive(Debug, Clone, Copy)]
+pub struct Untranslated;
+
+impl QualityRule for Untranslated {
+    fn name(&self) -> &'static str {
+        "untranslated"
+    }
+
+    fn check(&self, source: &str, translated: &str, target: &Lang) -> Option<String> {
+        if source.chars().count() >= MIN_CHECKED_CHARS
+            && normalized(source) == normalized(translated)
+        {
+            return Some("the text was returned untranslated".to_string());
+        }
+
+        let wrong_script = target_scripts(target).is_some_and(|scripts| {
+            let letters: Vec<Script> = translated.chars().filter_map(Script::of).collect();
+            let in_target = letters
+                .iter()
+                .filter(|script| scripts.contains(script))
+                .count();
+            letters.len() >= MIN_SCRIPT_LETTERS && in_target * 2 < letters.len()
+        });
+        // Same-script answers left in the source language (fr to en) need the detector.
+        let wrong_language = language::is_detectable(target)
+            && language::detect(translated)
+                .is_some_and(|detected| !language::same_language(&detected, target));
+        (wrong_script || wrong_language)
+            .then(|| "the answer is not written in the target language".to_string())
+    }
+}
+
+/// The answer opens with chatter about the translation.
+#[derive(Debug, Clone, Copy)]
+pub struct Preamble;
+
+impl QualityRule for Preamble {
+    fn name(&self) -> &'static str {
+        "preamble"
+    }
+
+    fn check(&self, source: &str, translated: &str, _target: &Lang) -> Option<String> {
+        let answer = translated.trim_start().to_lowercase();
+        let source = source.trim_start().to_lowercase();
+        PREAMBLES
+            .iter()
+            .find(|preamble| answer.starts_with(**preamble) && !source.starts_with(**preamble))
+            .map(|_| "the answer contains an introduction; give only the translation".to_string())
+    }
+}
+
+/// Numbers, URLs, and email addresses of the source are missing from the answer.
+#[derive(Debug, Clone, Copy)]
+pub struct PreservedTokens;
+
+impl QualityRule for PreservedTokens {
+    fn name(&self) -> &'static str {
+        "preserved_tokens"
+    }
+
+    fn check(&self, source: &str, translated: &str, _target: &Lang) -> Option<String> {
+        // Placeholder numbers are not part of the text; they have their own rule.
+        let source = protect::PLACEHOLDER.replace_all(source, "");
+        let translated = protect::PLACEHOLDER.replace_all(translated, "");
+        let mut missing: Vec<&str> = URL
+            .find_iter(&source)
+            .chain(EMAIL.find_iter(&source))
+            .map(|found| trim_trailing_punctuation(found.as_str()))
+            .filter(|token| !translated.contains(token))
+            .collect();
+
+        // Separators are localized ("1,000.5" becomes "1.000,5"), so compare digits.
+        let answer_numbers: Vec<String> = NUMBER
+            .find_iter(&translated)
+            .map(|found| digits(found.as_str()))
+            .collect();
+        missing.extend(
+            NUMBER
+                .find_iter(&source)
+                .map(|found| found.as_str())
+                .filter(|number| !answer_numbers.contains(&digits(number))),
+        );
+
+        if missing.is_empty() {
+            return None;
+        }
+        missing.dedup();
+        let shown: Vec<&str> = missing.iter().take(5).copied().collect();
+        Some(format!(
+            "the answer is missing {} from the source",
+            shown.join(", ")
+        ))
+    }
+}
+
+/// The answer is much shorter or longer than the source.
+#[derive(Debug, Clone, Copy)]
+pub struct LengthRatio {
+    /// Smallest accepted answer length relative to the source
+    pub min: f32,
+    /// Largest accepted answer length relative to the source
+    pub max: f32,
+}
+
+impl QualityRule for LengthRatio {
+    fn name(&self) -> &'static str {
+        "length_ratio"
+    }
+
+    fn check(&self, source: &str, translated: &str, _target: &Lang) -> Option<String> {
+        let source_width = display_width(source);
+        if source.chars().count() < MIN_CHECKED_CHARS || source_width == 0 {
+            return None;
+        }
+        let ratio = as_f32(display_width(translated)) / as_f32(source_width);
+        if ratio < self.min {
+            Some("the answer is much shorter than the source; translate all of it".to_string())
+        } else if ratio > self.max {
+            Some("the answer is much longer than the source; translate only the text".to_string())
+        } else {
+            None
+        }
+    }
+}
+
+/// Scripts a target language is written in, when known.
+fn target_scripts(target: &Lang) -> Option<&'static [Script]> {
+    let primary = target
+        .as_str()
+        .split(['-', '_'])
+        .next()?
+        .to_ascii_lowercase();
+    let scripts: &[Script] = match primary.as_str() {
+        "zh" => &[Script::Han],
+        "ja" => &[Script::Kana, Script::Han],
+        "ko" => &[Script::Hangul, Script::Han],
+        "ru" | "uk" | "bg" | "be" | "mk" => &[Script::Cyrillic],
+        "el" => &[Script::Greek],
+        "ar" | "fa" | "ur" => &[Script::Arabic],
+        "hi" | "mr" | "ne" => &[Script::Devanagari],
+        "th" => &[Script::Thai],
+        "en" | "fr" | "de" | "es" | "it" | "pt" | "nl" | "pl" | "cs" | "sk" | "sl" | "hr"
+        | "ro" | "hu" | "sv" | "da" | "no" | "nb" | "fi" | "et" | "lv" | "lt" | "tr" | "vi"
+        | "id" | "ms" | "ca" => &[Script::Latin],
+        _ => return None,
+    };
+    Some(scripts)
+}
+
+/// Lowercased words, ignoring surrounding quotes and spacing.
+fn normalized(text: &str) -> String {
+    text.trim()
+        .trim_matches('"')
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Length in narrow-character units, counting CJK characters twice so ratios
+/// between CJK and alphabetic languages stay comparable.
+fn display_width(text: &str) -> usize {
+    text.chars()
+        .filter(|c| !c.is_whitespace())
+        .map(|c| {
+            if Script::of(c).is_some_and(Script::is_wide) {
+                2
+            } else {
+                1
+            }
+        })
+        .sum()
+}
+
+fn digits(number: &str) -> String {
+    number.chars().filter(char::is_ascii_digit).collect()
+}
+
+fn trim_trailing_punctuation(token: &str) -> &str {
+    token.trim_end_matches(['.', ',', ';', ':', '!', '?', ')', ']', '}', '"', '\''])
+}
+
+#[allow(clippy::cast_precision_loss)] // Block lengths stay far below 2^24.
+const fn as_f32(length: usize) -> f32 {
+    length as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn issues(source: &str, translated: &str, target: &str) -> Vec<&'static str> {
+        QualityValidator::new(&QualityConfig::default())
+            .check(source, translated, &Lang::new(target))
+            .into_iter()
+            .map(|issue| issue.rule)
+            .collect()
+    }
+
+    #[test]
+    fn faithful_translations_pass() {
+        assert!(
+            issues(
+                "Le contrat de 1250,50 € est disponible sur https://example.com/c.",
+                "The 1250.50 € contract is available at https://example.com/c.",
+                "en",
+            )
+            .is_empty()
+        );
+        assert!(issues("Bonjour", "Bonjour", "en").is_empty());
+    }
+
+    #[test]
+    fn copies_and_wrong_scripts_are_untranslated() {
+        let source = "Le contrat est signé par les deux parties.";
+        assert_eq!(issues(source, source, "en"), ["untranslated"]);
+        assert_eq!(
+            issues(
+                source,
+                "Le contrat est signé par les deux parties!",
+                "zh-CN"
+            ),
+            ["untranslated"]
+        );
+        assert!(issues(source, "合同由双方签署，双方各执一份。", "zh-CN").is_empty());
+    }
+
+    #[test]
+    fn answers_left_in_a_same_script_source_language_are_untranslated() {
+        assert_eq!(
+            issues(
+                "Le contrat est signé par les deux parties.",
+                "Le contrat a été signé par les deux parties.",
+                "en"
+            ),
+            ["untranslated"]
+        );
+        assert_eq!(
+            issues(
+                "Der Vertrag wird von beiden Parteien unterzeichnet.",
+                "Der Vertrag ist von den beiden Parteien unterzeichnet.",
+                "nl"
+            ),
+            ["untranslated"]
+        );
+        assert!(
+            issues(
+                "Le contrat est signé par les deux parties.",
+                "The contract is signed by both parties.",
+                "en"
+            )
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn preambles_and_dropped_tokens_are_flagged() {
+        assert_eq!(
+            issues(
+                "Voir l'article 12.3.",
+                "Here is the translation: See article 12.3.",
+                "en"
+            ),
+            ["preamble"]
+        );
+        let rule = PreservedTokens;
+        let reason = rule
+            .check(
+                "Écrivez à info@example.org avant le 12.3.",
+                "Write to us before the deadline.",
+                &Lang::new("en"),
+            )
+            .unwrap_or_default();
+        assert!(reason.contains("info@example.org"), "{reason}");
+        assert!(reason.contains("12.3"), "{reason}");
+
+        assert_eq!(
+            issues("Voir l'article ⟦1⟧ et ⟦2⟧.", "See article ⟦2⟧.", "en"),
+            ["placeholders"]
+        );
+    }
+
+    #[test]
+    fn length_ratio_counts_wide_characters_twice() {
+        let rule = LengthRatio {
+            min: 0.25,
+            max: 4.0,
+        };
+        let en = Lang::new("en");
+        let source = "The contract is signed by both parties.";
+        assert!(rule.check(source, "Signed.", &en).is_some());
+        assert!(rule.check(source, &source.repeat(5), &en).is_some());
+        assert!(rule.check(source, "合同由双方签署。", &en).is_none());
+    }
+}
@@ -0,0 +1,374 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::chat::{self, ChatBackend, ChatPrompt};
+use super::http::{self, Pacing};
+use super::rate_limit::RateLimiter;
+use super::traits::{
+    BatchTranslation, PartialTranslation, TokenUsage, TranslationContext, Translator,
+    TranslatorInfo,
+};
+use crate::config::{
+    AnthropicConfig, HttpClientConfig, Lang, PromptConfig, TranslatorCacheIdentity,
+};
+use crate::error::{Error, Result};
+
+/// Completion budget sent when `max_tokens` is not configured; the Messages
+/// API requires one.
+const DEFAULT_MAX_TOKENS: u32 = 4096;
+
+/// Anthropic Messages API translator
+pub struct AnthropicTranslator {
+    client: Client,
+    /// Base URL for the API (e.g., "https://api.anthropic.com")
+    pub api_base: String,
+    /// Anthropic API key
+    pub api_key: String,
+    /// Model identifier
+    pub model: String,
+    /// Value of the `anthropic-version` header
+    pub anthropic_version: String,
+    /// Number of retry attempts
+    pub retry_count: u32,
+    /// Initial delay between retries in milliseconds, doubled per attempt
+    pub retry_delay_ms: u64,
+    /// Prompt templates and sampling parameters
+    pub prompt: PromptConfig,
+    rate_limiter: RateLimiter,
+}
+
+#[derive(Debug, Serialize)]
+struct MessagesRequest {
+    model: String,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    system: Option<String>,
+    messages: [Message; 1],
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct MessagesResponse {
+    #[serde(default)]
+    content: Vec<ContentBlock>,
+    stop_reason: Option<String>,
+    #[serde(default)]
+    usage: Option<Usage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ContentBlock {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct Usage {
+    #[serde(default)]
+    input_tokens: u64,
+    #[serde(default)]
+    output_tokens: u64,
+}
+
+impl AnthropicTranslator {
+    /// Create a new Anthropic translator.
+    ///
+    /// Fails if the HTTP client options are invalid.
+    pub fn new(
+        options: AnthropicConfig,
+        retry_count: u32,
+        retry_delay_ms: u64,
+        http_config: &HttpClientConfig,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: http::client(http_config)?,
+            api_base: options.api_base,
+            api_key: options.api_key.unwrap_or_default(),
+            model: options.model,
+            anthropic_version: options.anthropic_version,
+            retry_count,
+            retry_delay_ms,
+            prompt: options.prompt,
+            rate_limiter: RateLimiter::unlimited(),
+        })
+    }
+
+    /// Pace requests through a (possibly shared) rate limiter.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    fn max_tokens(&self) -> u32 {
+        self.prompt.max_tokens.unwrap_or(DEFAULT_MAX_TOKENS)
+    }
+
+    fn messages_request(&self, prompt: ChatPrompt) -> MessagesRequest {
+        MessagesRequest {
+            model: self.model.clone(),
+            max_tokens: self.max_tokens(),
+            system: prompt.system,
+            messages: [Message {
+                role: "user",
+                content: prompt.user,
+            }],
+            temperature: self.prompt.temperature,
+            top_p: self.prompt.top_p,
+            extra: self.prompt.extra_body.clone(),
+        }
+    }
+
+    /// Extract the text content, adding the reported usage to `usage` even
+    /// when the content itself is rejected.
+    fn parse_message(body: &[u8], usage: &mut TokenUsage) -> Result<String> {
+        let response: MessagesResponse = serde_json::from_slice(body).map_err(|_| {
+            Error::TranslationInvalidResponse("Anthropic API returned malformed JSON".to_string())
+        })?;
+        if let Some(reported) = response.usage {
+            *usage += TokenUsage {
+                prompt_tokens: reported.input_tokens,
+                completion_tokens: reported.output_tokens,
+                requests: 0,
+            };
+        }
+        match response.stop_reason.as_deref() {
+            Some("end_turn" | "stop_sequence") => {}
+            Some("max_tokens") => {
+                return Err(Error::TranslationInvalidResponse(
+                    "Anthropic API response was truncated".to_string(),
+                ));
+            }
+            Some("refusal") => {
+                return Err(Error::TranslationInvalidResponse(
+                    "Anthropic API declined to translate the text".to_string(),
+                ));
+            }
+            _ => {
+                return Err(Error::TranslationInvalidResponse(
+                    "Anthropic API response was incomplete".to_string(),
+                ));
+            }
+        }
+
+        let content: String = response
+            .content
+            .iter()
+            .filter(|block| block.kind == "text")
+            .map(|block| block.text.as_str())
+            .collect();
+        let content = content.trim();
+        if content.is_empty() {
+            return Err(Error::TranslationInvalidResponse(
+                "Anthropic API response was blank".to_string(),
+            ));
+        }
+        Ok(content.to_string())
+    }
+}
+
+#[async_trait]
+impl ChatBackend for AnthropicTranslator {
+    fn prompt_config(&self) -> &PromptConfig {
+        &self.prompt
+    }
+
+    async fn complete(&self, prompt: ChatPrompt, usage: &mut TokenUsage) -> Result<String> {
+        let url = format!("{}/v1/messages", self.api_base.trim_end_matches('/'));
+        let pacing = Pacing {
+            retry_count: self.retry_count,
+            retry_delay_ms: self.retry_delay_ms,
+            rate_limiter: &self.rate_limiter,
+            estimated_tokens: prompt.estimated_tokens(Some(self.max_tokens())),
+        };
+        let request = self.messages_request(prompt);
+        chat::complete_with_retry(
+            "Anthropic API",
+            pacing,
+            || {
+                self.client
+                    .post(&url)
+                    .header("x-api-key", &self.api_key)
+                    .header("anthropic-version", &self.anthropic_version)
+                    .json(&request)
+            },
+            Self::parse_message,
+            usage,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl Translator for AnthropicTranslator {
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "Anthropic",
+            requires_api_key: true,
+            supports_auto_detect: true,
+        }
+    }
+
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        let identity = TranslatorCacheIdentity::new(
+            "anthropic",
+            http::normalized_endpoint(&self.api_base),
+            &self.model,
+        );
+        self.prompt.apply_to_identity(identity)
+    }
+
+    async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
+        chat::translate_text(self, text, source, target).await
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        Ok(self
+            .translate_batch_traced(texts, source, target, context)
+            .await?
+            .translations)
+    }
+
+    async fn translate_batch_traced(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<BatchTranslation> {
+        let mut usage = TokenUsage::default();
+        let translations = chat::translate_blocks(self, texts, source, target, context, &mut usage)
+            .await
+            .map_err(|error| error.with_usage(usage))?;
+        Ok(BatchTranslation {
+            translations,
+            identity: self.cache_identity(),
+            usage,
+            flagged: Vec::new(),
+        })
+    }
+
+    async fn revise(
+        &self,
+        text: &str,
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+        translated: PartialTranslation,
+        usage: &mut TokenUsage,
+    ) -> Result<String> {
+        chat::revise(self, text, source, target, context, translated.text, usage).await
+    }
+
+    fn is_available(&self) -> bool {
+        !self.api_key.trim().is_empty()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn translator(api_base: String) -> AnthropicTranslator {
+        AnthropicTranslator::new(
+            AnthropicConfig {
+                api_base,
+                api_key: Some("secret".to_string()),
+                model: "test-model".to_string(),
+                prompt: PromptConfig {
+                    system: Some("You translate into {target}.".to_string()),
+                    ..PromptConfig::default()
+                },
+                ..AnthropicConfig::default()
+            },
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn sends_a_messages_request_and_counts_usage() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("x-api-key", "secret"))
+            .and(header("anthropic-version", "2023-06-01"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "test-model",
+                "max_tokens": 4096,
+                "system": "You translate into English.",
+                "messages": [{"role": "user"}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "content": [{"type": "text", "text": "\"Hello\""}],
+                "stop_reason": "end_turn",
+                "usage": {"input_tokens": 20, "output_tokens": 2}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let batch = translator(server.uri())
+            .translate_batch_traced(
+                &["Bonjour"],
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(batch.translations, vec!["Hello"]);
+        assert_eq!(batch.identity.backend(), "anthropic");
+        assert_eq!(
+            batch.usage,
+            TokenUsage {
+                prompt_tokens: 20,
+                completion_tokens: 2,
+                requests: 1,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn truncated_messages_are_an_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "content": [{"type": "text", "text": "Hel"}],
+                "stop_reason": "max_tokens"
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = translator(server.uri())
+            .translate("Bonjour", &Lang::new("fr"), &Lang::new("en"))
+            .await;
+        assert!(matches!(result, Err(Error::TranslationInvalidResponse(_))));
+    }
+}
@@ -0,0 +1,678 @@
+//! Prompting, batching, and retries shared by the chat-model backends.
+
+use async_trait::async_trait;
+use reqwest::{RequestBuilder, StatusCode};
+use tracing::{debug, error, warn};
+
+use super::http::{self, MAX_ERROR_BODY_BYTES, MAX_SUCCESS_BODY_BYTES, Pacing};
+use super::traits::{TokenUsage, TranslationContext};
+use crate::config::{Lang, PromptConfig};
+use crate::error::{Error, Result};
+use crate::protect::PLACEHOLDER;
+use crate::quality::QualityIssue;
+
+/// Upper bound on blocks sent in one batched request.
+const MAX_BATCH_BLOCKS: usize = 24;
+/// Upper bound on source bytes sent in one batched request.
+const MAX_BATCH_BYTES: usize = 6_000;
+/// Rough prompt size of one token, used to reserve rate limiter and budget capacity.
+pub(super) const BYTES_PER_TOKEN_ESTIMATE: u64 = 4;
+
+/// Response format appended to user templates when several blocks share a request.
+const BATCH_FORMAT_INSTRUCTIONS: &str = "The text above is a JSON object of numbered \
+     segments, consecutive paragraphs of one document page. Translate every segment separately; \
+     do not merge, split, or omit segments. Respond with only a JSON object mapping every \
+     segment number to its translation, using exactly the same keys.";
+
+/// Rendered system and user prompts for one completion.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatPrompt {
+    /// Rendered system prompt, if one is configured
+    pub system: Option<String>,
+    /// User message carrying the text to translate
+    pub user: String,
+}
+
+impl ChatPrompt {
+    /// Prompt translating one block.
+    pub fn single(
+        config: &PromptConfig,
+        text: &str,
+        source: &Lang,
+        target: &Lang,
+        glossary: &str,
+    ) -> Self {
+        let mut user = config.user.as_deref().map_or_else(
+            || create_prompt(text, source, target, glossary),
+            |template| render_template(template, &placeholders(text, source, target, glossary)),
+        );
+        user.push_str(placeholder_instructions(&[text]));
+        Self::with_system(config, user, source, target, glossary)
+    }
+
+    /// Prompt translating several numbered blocks in one request.
+    pub fn batch(
+        config: &PromptConfig,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        glossary: &str,
+    ) -> Self {
+        let mut user = config.user.as_deref().map_or_else(
+            || create_batch_prompt(texts, source, target, glossary),
+            |template| {
+                let segments = numbered_segments(texts).to_string();
+                let values = placeholders(&segments, source, target, glossary);
+                format!(
+                    "{}\n\n{BATCH_FORMAT_INSTRUCTIONS}",
+                    render_template(template, &values)
+                )
+            },
+        );
+        user.push_str(placeholder_instructions(texts));
+        Self::with_system(config, user, source, target, glossary)
+    }
+
+    /// Pair the user message with the configured system prompt, if any.
+    fn with_system(
+        config: &PromptConfig,
+        user: String,
+        source: &Lang,
+        target: &Lang,
+        glossary: &str,
+    ) -> Self {
+        let system = config
+            .system
+            .as_deref()
+            .map(|system| render_template(system, &placeholders("", source, target, glossary)));
+        Self { system, user }
+    }
+
+    /// Ask again after `previous` failed the quality checks, telling the
+    /// model what was wrong with it.
+    #[must_use]
+    pub fn with_feedback(mut self, previous: &str, issues: &[QualityIssue]) -> Self {
+        let reasons: Vec<&str> = issues.iter().map(|issue| issue.reason.as_str()).collect();
+        self.user = format!(
+            "{}\n\nYour previous answer was:\n{previous}\n\nIt was rejected because {}. \
+             Answer again with only the corrected translation.",
+            self.user,
+            reasons.join("; ")
+        );
+        self
+    }
+
+    /// Tokens to reserve before sending: the prompt size, plus the same again
+    /// (or `max_tokens`) for the completion.
+    pub fn estimated_tokens(&self, max_tokens: Option<u32>) -> u64 {
+        let bytes = self.system.as_ref().map_or(0, String::len) + self.user.len();
+        let prompt = u64::try_from(bytes)
+            .unwrap_or(u64::MAX)
+            .div_ceil(BYTES_PER_TOKEN_ESTIMATE);
+        let completion = max_tokens.map_or(prompt, u64::from);
+        prompt.saturating_add(completion)
+    }
+}
+
+/// A backend that answers a chat prompt with one completion.
+#[async_trait]
+pub trait ChatBackend: Send + Sync {
+    /// Prompt templates and sampling parameters
+    fn prompt_config(&self) -> &PromptConfig;
+
+    /// Send `prompt` and return the completion content.
+    ///
+    /// Usage reported by every attempt is added to `usage`, since rejected
+    /// completions are billed too.
+    async fn complete(&self, prompt: ChatPrompt, usage: &mut TokenUsage) -> Result<String>;
+}
+
+/// Send a completion request with retries, returning the completion content.
+///
+/// Unlike [`http::send_with_retry`], responses that arrive but cannot be used
+/// are asked again. `parse` extracts the content and adds the usage it reports
+/// to `usage`, which is also used to settle the rate limiter reservation.
+pub async fn complete_with_retry<B, P>(
+    label: &str,
+    pacing: Pacing<'_>,
+    build: B,
+    parse: P,
+    usage: &mut TokenUsage,
+) -> Result<String>
+where
+    B: Fn() -> RequestBuilder + Send + Sync,
+    P: Fn(&[u8], &mut TokenUsage) -> Result<String> + Send + Sync,
+{
+    let attempts = pacing.retry_count.max(1);
+    let mut last_error = None;
+
+    for attempt in 0..attempts {
+        debug!("{} request attempt {}/{}", label, attempt + 1, attempts);
+
+        pacing.rate_limiter.acquire(pacing.estimated_tokens).await;
+        usage.requests += 1;
+        let retry_after = match build().send().await {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_success() {
+                    match http::read_body_limited(response, MAX_SUCCESS_BODY_BYTES).await {
+                        Ok(body) => {
+                            let billed = usage.total_tokens();
+                            let parsed = parse(&body, usage);
+                            let reported = usage.total_tokens() - billed;
+                            if reported > 0 {
+                                pacing
+                                    .rate_limiter
+                                    .settle(pacing.estimated_tokens, reported);
+                            }
+                            match parsed {
+                                Ok(content) => return Ok(content),
+                                Err(error) => last_error = Some(error),
+                            }
+                        }
+                        Err(error) => last_error = Some(error),
+                    }
+                    None
+                } else {
+                    let retry_after = http::retry_after_seconds(&response);
+                    let _ = http::read_body_limited(response, MAX_ERROR_BODY_BYTES).await;
+                    if status == StatusCode::TOO_MANY_REQUESTS {
+                        warn!("{} rate limited the request", label);
+                        last_error = Some(Error::TranslationRateLimited { retry_after });
+                    } else {
+                        let error =
+                            Error::TranslationRequest(format!("upstream returned HTTP {status}"));
+                        if !http::is_retryable(status) {
+                            return Err(error);
+                        }
+                        warn!("{} returned HTTP {}", label, status);
+                        last_error = Some(error);
+                    }
+                    retry_after
+                }
+            }
+            Err(error) => {
+                warn!("{} request failed: {}", label, error);
+                last_error = Some(http::request_error(&error));
+                None
+            }
+        };
+
+        if attempt + 1 < attempts {
+            tokio::time::sleep(http::retry_delay(
+                attempt,
+                pacing.retry_delay_ms,
+                retry_after,
+            ))
+            .await;
+        }
+    }
+
+    error!("Translation failed after {} attempts", attempts);
+    Err(last_error.unwrap_or(Error::TranslationMaxRetriesExceeded))
+}
+
+/// Whether text would be translated into the language it is already in.
+pub fn is_same_language(source: &Lang, target: &Lang) -> bool {
+    source.as_str() == target.as_str() && source.as_str() != "auto"
+}
+
+/// Translate one block, passing blank text and same-language pairs through.
+pub async fn translate_text<B: ChatBackend + ?Sized>(
+    backend: &B,
+    text: &str,
+    source: &Lang,
+    target: &Lang,
+) -> Result<String> {
+    if text.trim().is_empty() || is_same_language(source, target) {
+        return Ok(text.to_string());
+    }
+    translate_single(
+        backend,
+        text,
+        source,
+        target,
+        &TranslationContext::default(),
+        &mut TokenUsage::default(),
+    )
+    .await
+}
+
+/// Translate blocks in bounded batches, adding billed usage to `usage`.
+///
+/// On failure `usage` still holds what the chunks before it billed.
+///
+/// Blank blocks pass through untouched and never reach the model.
+pub async fn translate_blocks<B: ChatBackend + ?Sized>(
+    backend: &B,
+    texts: &[&str],
+    source: &Lang,
+    target: &Lang,
+    context: &TranslationContext,
+    usage: &mut TokenUsage,
+) -> Result<Vec<String>> {
+    if is_same_language(source, target) {
+        return Ok(texts.iter().map(ToString::to_string).collect());
+    }
+
+    let mut translations = Vec::with_capacity(texts.len());
+    let mut start = 0;
+    while start < texts.len() {
+        if texts[start].trim().is_empty() {
+            translations.push(texts[start].to_string());
+            start += 1;
+            continue;
+        }
+
+        let mut end = start;
+        let mut bytes = 0;
+        while end < texts.len()
+            && end - start < MAX_BATCH_BLOCKS
+            && !texts[end].trim().is_empty()
+            && (end == start || bytes + texts[end].len() <= MAX_BATCH_BYTES)
+        {
+            bytes += texts[end].len();
+            end += 1;
+        }
+
+        translations.extend(
+            translate_chunk(backend, &texts[start..end], source, target, context, usage).await?,
+        );
+        start = end;
+    }
+    Ok(translations)
+}
+
+/// Translate a single block with its own request.
+async fn translate_single<B: ChatBackend + ?Sized>(
+    backend: &B,
+    text: &str,
+    source: &Lang,
+    target: &Lang,
+    context: &TranslationContext,
+    usage: &mut TokenUsage,
+) -> Result<String> {
+    let glossary = context.glossary.prompt_instructions(&[text]);
+    let prompt = ChatPrompt::single(backend.prompt_config(), text, source, target, &glossary);
+    let completion = backend.complete(prompt, usage).await?;
+    let translated = clean_translation(&completion)?;
+    revise(backend, text, source, target, context, translated, usage).await
+}
+
+/// Re-ask for a block whose translation fails the quality checks, up to the
+/// validator's budget. The last usable answer is kept when the budget runs out
+/// or a re-ask fails.
+pub async fn revise<B: ChatBackend + ?Sized>(
+    backend: &B,
+    text: &str,
+    source: &Lang,
+    target: &Lang,
+    context: &TranslationContext,
+    mut translated: String,
+    usage: &mut TokenUsage,
+) -> Result<String> {
+    for _ in 0..context.quality.max_reasks() {
+        let issues = context.quality.check(text, &translated, target);
+        let Some(first) = issues.first() else {
+            break;
+        };
+        debug!(
+            "Re-asking a translation that failed the {} check",
+            first.rule
+        );
+
+        let glossary = context.glossary.prompt_instructions(&[text]);
+        let prompt = ChatPrompt::single(backend.prompt_config(), text, source, target, &glossary)
+            .with_feedback(&translated, &issues);
+        match backend
+            .complete(prompt, usage)
+            .await
+            .and_then(|completion| clean_translation(&completion))
+        {
+            Ok(revised) => translated = revised,
+            Err(error) => {
+                warn!("Re-asking a failed translation did not succeed: {}", error);
+                break;
+            }
+        }
+    }
+    Ok(translated)
+}
+
+/// Translate one bounded chunk of blocks, falling back to per-block requests
+/// for anything the batch response did not cover.
+async fn translate_chunk<B: ChatBackend + ?Sized>(
+    backend: &B,
+    texts: &[&str],
+    source: &Lang,
+    target: &Lang,
+    context: &TranslationContext,
+    usage: &mut TokenUsage,
+) -> Result<Vec<String>> {
+    if let [text] = texts {
+        return Ok(vec![
+            translate_single(backend, text, source, target, context, usage).await?,
+        ]);
+    }
+
+    let glossary = context.glossary.prompt_instructions(texts);
+    let prompt = ChatPrompt::batch(backend.prompt_config(), texts, source, target, &glossary);
+    let aligned = match backend.complete(prompt, usage).await {
+        Ok(completion) => parse_batch_response(&completion, texts.len()),
+        Err(Error::TranslationInvalidResponse(reason)) => {
+            warn!("Batch translation response was unusable: {}", reason);
+            vec![None; texts.len()]
+        }
+        Err(error) => return Err(error),
+    };
+
+    let missing = aligned.iter().filter(|entry| entry.is_none()).count();
+    if missing > 0 {
+        warn!(
+            "Batch response covered {} of {} blocks; translating the rest individually",
+            texts.len() - missing,
+            texts.len()
+        );
+    }
+
+    let mut translations = Vec::with_capacity(texts.len());
+    for (text, translated) in texts.iter().zip(aligned) {
+        let translated = match translated {
+            Some(translated) => {
+                revise(backend, text, source, target, context, translated, usage).await?
+            }
+            None => translate_single(backend, text, source, target, context, usage).await?,
+        };
+        translations.push(translated);
+    }
+    Ok(translations)
+}
+
+/// Strip the quotes models like to echo around a single translated block.
+pub fn clean_translation(content: &str) -> Result<String> {
+    let translated = content
+        .trim()
+        .trim_start_matches('"')
+        .trim_end_matches('"')
+        .trim();
+    if translated.is_empty() {
+        return Err(Error::TranslationInvalidResponse(
+            "translation API response was blank".to_string(),
+        ));
+    }
+    Ok(translated.to_string())
+}
+
+/// Create translation prompt
+fn create_prompt(text: &str, source: &Lang, target: &Lang, glossary: &str) -> String {
+    format!(
+        "Translate the following text{} into {}. Output only the translation, no explanations.\n\n{}Text: \"{}\"",
+        source_hint(source),
+        language_name(target),
+        glossary_section(glossary),
+        text
+    )
+}
+
+/// Create a prompt translating several numbered blocks in one request.
+///
+/// Blocks are sent as a JSON object keyed by 1-based position so the
+/// response can be re-aligned even when the model drops or reorders entries.
+fn create_batch_prompt(texts: &[&str], source: &Lang, target: &Lang, glossary: &str) -> String {
+    format!(
+        "Translate each numbered segment below{} into {}. The segments are consecutive \
+         paragraphs of one document page; use them as context for each other, but translate \
+         every segment separately. Do not merge, split, or omit segments.\n\n\
+         Respond with only a JSON object mapping every segment number to its translation, \
+         using exactly the same keys, for example {{\"1\": \"...\", \"2\": \"...\"}}.\n\n\
+         {}Segments:\n{}",
+        source_hint(source),
+        language_name(target),
+        glossary_section(glossary),
+        numbered_segments(texts)
+    )
+}
+
+/// Re-align a batch response with its inputs.
+///
+/// Returns one entry per expected segment; entries the model omitted or
+/// left blank are `None` so callers can retry them individually.
+fn parse_batch_response(content: &str, expected: usize) -> Vec<Option<String>> {
+    let mut aligned = vec![None; expected];
+    let json = content
+        .find(['{', '['])
+        .zip(content.rfind(['}', ']']))
+        .and_then(|(start, end)| content.get(start..=end));
+    let Some(value) = json.and_then(|json| serde_json::from_str(json).ok()) else {
+        return aligned;
+    };
+
+    match value {
+        serde_json::Value::Object(entries) => {
+            for (key, value) in entries {
+                let Some(index) = key
+                    .trim()
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|n| n.checked_sub(1))
+                else {
+                    continue;
+                };
+                if let (Some(slot), Some(text)) = (aligned.get_mut(index), value.as_str()) {
+                    *slot = clean_translation(text).ok();
+                }
+            }
+        }
+        // A bare array carries no keys, so only trust it when nothing was dropped.
+        serde_json::Value::Array(values) if values.len() == expected => {
+            for (slot, value) in aligned.iter_mut().zip(values) {
+                *slot = value.as_str().and_then(|text| clean_translation(text).ok());
+            }
+        }
+        _ => {}
+    }
+    aligned
+}
+
+/// Prompt fragment naming the source language, empty for auto-detection.
+fn source_hint(source: &Lang) -> String {
+    if source.as_str() == "auto" {
+        String::new()
+    } else {
+        format!(" from {}", language_name(source))
+    }
+}
+
+/// Blocks as a JSON object keyed by 1-based position.
+fn numbered_segments(texts: &[&str]) -> serde_json::Value {
+    texts
+        .iter()
+        .enumerate()
+        .map(|(index, text)| ((index + 1).to_string(), (*text).into()))
+        .collect::<serde_json::Map<_, _>>()
+        .into()
+}
+
+/// Values for the `{source}`, `{target}`, `{text}`, and `{context}` template placeholders.
+fn placeholders<'a>(
+    text: &'a str,
+    source: &'a Lang,
+    target: &'a Lang,
+    glossary: &'a str,
+) -> [(&'static str, &'a str); 4] {
+    let source = if source.as_str() == "auto" {
+        "the source language"
+    } else {
+        language_name(source)
+    };
+    [
+        ("source", source),
+        ("target", language_name(target)),
+        ("text", text),
+        ("context", glossary.trim_end()),
+    ]
+}
+
+/// Substitute `{name}` placeholders in a single pass.
+///
+/// Substituted values are never re-scanned, and unknown placeholders are kept
+/// as written.
+fn render_template(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let replacement = after.find('}').and_then(|end| {
+            let name = &after[..end];
+            values
+                .iter()
+                .find(|(key, _)| *key == name)
+                .map(|(_, value)| (end, *value))
+        });
+        if let Some((end, value)) = replacement {
+            rendered.push_str(value);
+            rest = &after[end + 1..];
+        } else {
+            rendered.push('{');
+            rest = after;
+        }
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+/// Instruction to keep the placeholders of protected spans, empty when the
+/// texts have none.
+fn placeholder_instructions(texts: &[&str]) -> &'static str {
+    if texts.iter().any(|text| PLACEHOLDER.is_match(text)) {
+        "\n\nPlaceholders such as ⟦1⟧ stand for text that must stay as written; copy every \
+         placeholder into the translation unchanged."
+    } else {
+        ""
+    }
+}
+
+/// Prompt paragraph carrying glossary instructions, empty when there are none.
+fn glossary_section(instructions: &str) -> String {
+    if instructions.is_empty() {
+        String::new()
+    } else {
+        format!("{instructions}\n")
+    }
+}
+
+/// Convert a language code to a human-readable prompt value when known.
+fn language_name(lang: &Lang) -> &str {
+    match lang.as_str() {
+        "en" => "English",
+        "zh-CN" => "Simplified Chinese",
+        "zh-TW" => "Traditional Chinese",
+        "zh" => "Chinese",
+        "ja" => "Japanese",
+        "ko" => "Korean",
+        "es" => "Spanish",
+        "fr" => "French",
+        "de" => "German",
+        "it" => "Italian",
+        "pt" => "Portuguese",
+        "ru" => "Russian",
+        "ar" => "Arabic",
+        "hi" => "Hindi",
+        "th" => "Thai",
+        "vi" => "Vietnamese",
+        _ => lang.as_str(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_name() {
+        assert_eq!(language_name(&Lang::new("en")), "English");
+        assert_eq!(language_name(&Lang::new("zh-CN")), "Simplified Chinese");
+        assert_eq!(language_name(&Lang::new("unknown")), "unknown");
+    }
+
+    #[test]
+    fn batch_prompt_numbers_segments_as_json() {
+        let prompt = create_batch_prompt(
+            &["Bonjour", "Il a dit \"oui\""],
+            &Lang::new("fr"),
+            &Lang::new("en"),
+            "",
+        );
+        assert!(prompt.contains("from French into English"));
+        assert!(prompt.contains(r#"{"1":"Bonjour","2":"Il a dit \"oui\""}"#));
+    }
+
+    #[test]
+    fn prompts_include_glossary_instructions() {
+        let glossary =
+            crate::glossary::Glossary::new([("contrat", Some("agreement"))]).unwrap_or_default();
+        let texts = ["Le contrat", "Signé"];
+        let instructions = glossary.prompt_instructions(&texts);
+
+        let batch = create_batch_prompt(&texts, &Lang::new("fr"), &Lang::new("en"), &instructions);
+        let single = create_prompt(texts[0], &Lang::new("fr"), &Lang::new("en"), &instructions);
+        for prompt in [batch, single] {
+            assert!(prompt.contains("\"contrat\" => \"agreement\""));
+        }
+    }
+
+    #[test]
+    fn prompts_mention_placeholders_only_when_present() {
+        let config = PromptConfig::default();
+        let (fr, en) = (Lang::new("fr"), Lang::new("en"));
+        let masked = ChatPrompt::batch(&config, &["Voir ⟦1⟧", "Fin"], &fr, &en, "");
+        let plain = ChatPrompt::single(&config, "Voir l'article", &fr, &en, "");
+        assert!(masked.user.contains("copy every placeholder"));
+        assert!(!plain.user.contains("placeholder"));
+    }
+
+    #[test]
+    fn batch_response_realigns_keyed_and_fenced_objects() {
+        let content = "```json\n{\"2\": \"Second\", \"1\": \"\\\"First\\\"\"}\n```";
+        assert_eq!(
+            parse_batch_response(content, 2),
+            vec![Some("First".to_string()), Some("Second".to_string())]
+        );
+    }
+
+    #[test]
+    fn batch_response_marks_missing_blank_and_unknown_segments() {
+        let content = r#"{"1": "One", "3": "  ", "7": "Extra", "x": "Junk"}"#;
+        assert_eq!(
+            parse_batch_response(content, 3),
+            vec![Some("One".to_string()), None, None]
+        );
+    }
+
+    #[test]
+    fn batch_response_only_trusts_arrays_of_the_expected_length() {
+        assert_eq!(
+            parse_batch_response(r#"["One", "Two"]"#, 2),
+            vec![Some("One".to_string()), Some("Two".to_string())]
+        );
+        assert_eq!(
+            parse_batch_response(r#"["One and two"]"#, 2),
+            vec![None, None]
+        );
+        assert_eq!(parse_batch_response("not json", 1), vec![None]);
+    }
+
+    #[test]
+    fn templates_substitute_placeholders_once() {
+        let rendered = render_template(
+            "{source} -> {target}: {text} {unknown} {",
+            &[
+                ("source", "French"),
+                ("target", "English"),
+                ("text", "{target}"),
+            ],
+        );
+        assert_eq!(rendered, "French -> English: {target} {unknown} {");
+    }
+}
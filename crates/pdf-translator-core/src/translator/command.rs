@@ -0,0 +1,232 @@
+use async_trait::async_trait;
+use futures::stream::{self, StreamExt, TryStreamExt};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tokio::sync::Semaphore;
+use tracing::debug;
+
+use super::http::MAX_SUCCESS_BODY_BYTES;
+use super::traits::{TranslationContext, Translator, TranslatorInfo};
+use crate::config::{CommandConfig, Lang, TranslatorCacheIdentity};
+use crate::error::{Error, Result};
+
+/// Characters of stderr kept in the error of a failed invocation.
+const MAX_STDERR_CHARS: usize = 2_000;
+
+/// Translator that runs an external program once per block.
+///
+/// The program receives `{"text", "source", "target"}` as JSON on stdin and
+/// prints the translation on stdout; a non-zero exit fails the block with the
+/// program's stderr.
+pub struct CommandTranslator {
+    /// Program to run
+    pub program: PathBuf,
+    /// Arguments passed to the program
+    pub args: Vec<String>,
+    /// Time a single invocation may run before it is killed
+    pub timeout: Duration,
+    slots: Semaphore,
+    max_concurrent: usize,
+}
+
+#[derive(Debug, Serialize)]
+struct CommandRequest<'a> {
+    text: &'a str,
+    source: &'a str,
+    target: &'a str,
+}
+
+impl CommandTranslator {
+    /// Create a translator running `options.program`
+    pub fn new(options: CommandConfig) -> Self {
+        let max_concurrent = options.max_concurrent.max(1);
+        Self {
+            program: options.program,
+            args: options.args,
+            timeout: Duration::from_secs(options.timeout_seconds),
+            slots: Semaphore::new(max_concurrent),
+            max_concurrent,
+        }
+    }
+
+    /// Run the program for one block, waiting for a free slot first.
+    async fn run(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
+        let input = serde_json::to_vec(&CommandRequest {
+            text,
+            source: source.as_str(),
+            target: target.as_str(),
+        })
+        .map_err(|e| Error::TranslationRequest(format!("failed to encode command input: {e}")))?;
+
+        let _slot =
+            self.slots.acquire().await.map_err(|_| {
+                Error::TranslationRequest("translation command was shut down".into())
+            })?;
+        debug!("Running translation command {}", self.program.display());
+
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(|e| {
+                Error::TranslationRequest(format!(
+                    "failed to start translation command {}: {e}",
+                    self.program.display()
+                ))
+            })?;
+
+        // Write stdin while collecting output so large texts cannot deadlock
+        // on full pipes; dropping it afterwards signals end of input. Programs
+        // may exit without reading all of it, so a broken pipe is left for the
+        // exit status to explain.
+        let stdin = child.stdin.take();
+        let write = async move {
+            if let Some(mut stdin) = stdin {
+                let _ = stdin.write_all(&input).await;
+            }
+        };
+        let ((), output) = tokio::time::timeout(self.timeout, async {
+            tokio::join!(write, child.wait_with_output())
+        })
+        .await
+        .map_err(|_| Error::TranslationTimeout)?;
+        let output = output.map_err(|e| {
+            Error::TranslationRequest(format!("translation command could not be awaited: {e}"))
+        })?;
+
+        if !output.status.success() {
+            let stderr = String::from_utf8_lossy(&output.stderr);
+            let stderr: String = stderr.trim().chars().take(MAX_STDERR_CHARS).collect();
+            return Err(Error::TranslationRequest(if stderr.is_empty() {
+                format!("translation command exited with {}", output.status)
+            } else {
+                format!(
+                    "translation command exited with {}: {stderr}",
+                    output.status
+                )
+            }));
+        }
+        if output.stdout.len() > MAX_SUCCESS_BODY_BYTES {
+            return Err(Error::TranslationInvalidResponse(
+                "translation command output exceeded the size limit".to_string(),
+            ));
+        }
+        let stdout = String::from_utf8(output.stdout).map_err(|_| {
+            Error::TranslationInvalidResponse("translation command output was not UTF-8".into())
+        })?;
+        let translated = stdout.trim_end_matches(['\n', '\r']);
+        if translated.trim().is_empty() {
+            return Err(Error::TranslationInvalidResponse(
+                "translation command output was blank".to_string(),
+            ));
+        }
+        Ok(translated.to_string())
+    }
+}
+
+#[async_trait]
+impl Translator for CommandTranslator {
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "External command",
+            requires_api_key: false,
+            supports_auto_detect: true,
+        }
+    }
+
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        TranslatorCacheIdentity::new("command", self.program.to_string_lossy(), "").with_option(
+            "args",
+            serde_json::Value::from(self.args.clone()).to_string(),
+        )
+    }
+
+    async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
+        if text.trim().is_empty()
+            || (source.as_str() == target.as_str() && source.as_str() != "auto")
+        {
+            return Ok(text.to_string());
+        }
+        self.run(text, source, target).await
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        _context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        // Invocations beyond `max_concurrent` wait for a slot inside `run`.
+        let translations: Vec<_> = texts
+            .iter()
+            .map(|text| self.translate(text, source, target))
+            .collect();
+        stream::iter(translations)
+            .buffered(self.max_concurrent)
+            .try_collect()
+            .await
+    }
+}
+
+#[cfg(all(test, unix))]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+
+    fn shell(script: &str, timeout_seconds: u64) -> CommandTranslator {
+        CommandTranslator::new(CommandConfig {
+            program: PathBuf::from("sh"),
+            args: vec!["-c".to_string(), script.to_string()],
+            timeout_seconds,
+            max_concurrent: 2,
+        })
+    }
+
+    #[tokio::test]
+    async fn program_reads_json_and_prints_the_translation() {
+        // Echo the request back so the test can see exactly what was sent.
+        let translator = shell("cat; echo", 5);
+        let translated = translator
+            .translate("Bonjour", &Lang::new("fr"), &Lang::new("en"))
+            .await
+            .unwrap();
+        assert_eq!(
+            translated,
+            r#"{"text":"Bonjour","source":"fr","target":"en"}"#
+        );
+
+        let identity = translator.cache_identity();
+        assert_eq!(identity.endpoint(), "sh");
+        assert_eq!(
+            identity.options().collect::<Vec<_>>(),
+            [("args", r#"["-c","cat; echo"]"#)]
+        );
+    }
+
+    #[tokio::test]
+    async fn failures_carry_stderr() {
+        let result = shell("cat >/dev/null; echo 'model not loaded' >&2; exit 3", 5)
+            .translate("Bonjour", &Lang::new("fr"), &Lang::new("en"))
+            .await;
+        let Err(Error::TranslationRequest(message)) = result else {
+            unreachable!("expected a request error, got {result:?}");
+        };
+        assert!(message.contains("model not loaded"), "{message}");
+    }
+
+    #[tokio::test]
+    async fn slow_programs_time_out() {
+        let result = shell("sleep 5", 1)
+            .translate("Bonjour", &Lang::new("fr"), &Lang::new("en"))
+            .await;
+        assert!(matches!(result, Err(Error::TranslationTimeout)));
+    }
+}
@@ -0,0 +1,461 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::http::{self, Pacing};
+use super::rate_limit::RateLimiter;
+use super::traits::{TranslationContext, Translator, TranslatorInfo};
+use crate::config::{DeepLConfig, DeepLFormality, HttpClientConfig, Lang, TranslatorCacheIdentity};
+use crate::error::{Error, Result};
+
+/// Endpoint for DeepL API Free keys (suffixed with `:fx`)
+pub const DEEPL_FREE_API_BASE: &str = "https://api-free.deepl.com/v2";
+/// Endpoint for DeepL API Pro keys
+pub const DEEPL_PRO_API_BASE: &str = "https://api.deepl.com/v2";
+
+/// DeepL accepts at most 50 texts per request.
+const MAX_BATCH_TEXTS: usize = 50;
+/// DeepL rejects request bodies over 128 KiB; leave room for JSON overhead.
+const MAX_BATCH_BYTES: usize = 96 * 1024;
+/// Non-standard status DeepL uses when the character quota is exhausted.
+const QUOTA_EXCEEDED_STATUS: u16 = 456;
+
+/// DeepL REST API translator
+pub struct DeepLTranslator {
+    client: Client,
+    /// Base URL for the API (e.g., "https://api-free.deepl.com/v2")
+    pub api_base: String,
+    /// DeepL authentication key
+    pub api_key: String,
+    /// Formality preference sent with every request
+    pub formality: Option<DeepLFormality>,
+    /// Server-side glossary applied to every request
+    pub glossary_id: Option<String>,
+    /// Number of retry attempts
+    pub retry_count: u32,
+    /// Initial delay between retries in milliseconds, doubled per attempt
+    pub retry_delay_ms: u64,
+    rate_limiter: RateLimiter,
+}
+
+#[derive(Debug, Serialize)]
+struct TranslateRequest<'a> {
+    text: &'a [&'a str],
+    target_lang: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    source_lang: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    formality: Option<&'static str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    glossary_id: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranslateResponse {
+    translations: Vec<Translation>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Translation {
+    text: String,
+}
+
+impl DeepLTranslator {
+    /// Create a new DeepL translator.
+    ///
+    /// The free or pro endpoint is chosen from the key unless `options.api_base`
+    /// overrides it. Fails if the HTTP client options are invalid.
+    pub fn new(
+        options: DeepLConfig,
+        retry_count: u32,
+        retry_delay_ms: u64,
+        http_config: &HttpClientConfig,
+    ) -> Result<Self> {
+        let api_key = options.api_key.unwrap_or_default();
+        let api_base = options
+            .api_base
+            .unwrap_or_else(|| default_api_base(&api_key).to_string());
+
+        Ok(Self {
+            client: http::client(http_config)?,
+            api_base,
+            api_key,
+            formality: options.formality,
+            glossary_id: options.glossary_id,
+            retry_count,
+            retry_delay_ms,
+            rate_limiter: RateLimiter::unlimited(),
+        })
+    }
+
+    /// Pace requests through a (possibly shared) rate limiter.
+    ///
+    /// Only the request limit applies, since the backend bills no tokens.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    fn build_request<'a>(
+        &'a self,
+        texts: &'a [&'a str],
+        source: &Lang,
+        target: &Lang,
+    ) -> Result<TranslateRequest<'a>> {
+        let source_lang = source_code(source);
+        if self.glossary_id.is_some() && source_lang.is_none() {
+            return Err(Error::ConfigInvalid {
+                field: "translator.deepl.glossary_id".to_string(),
+                reason: "DeepL glossaries require an explicit source language".to_string(),
+            });
+        }
+
+        Ok(TranslateRequest {
+            text: texts,
+            target_lang: target_code(target),
+            source_lang,
+            formality: self.formality.map(DeepLFormality::as_str),
+            glossary_id: self.glossary_id.as_deref(),
+        })
+    }
+
+    /// Translate one bounded chunk of non-blank texts.
+    async fn translate_chunk(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+    ) -> Result<Vec<String>> {
+        let request = self.build_request(texts, source, target)?;
+        let response = self.request_with_retry(&request).await?;
+        if response.translations.len() != texts.len() {
+            return Err(Error::TranslationInvalidResponse(format!(
+                "DeepL returned {} translations for {} texts",
+                response.translations.len(),
+                texts.len()
+            )));
+        }
+        Ok(response
+            .translations
+            .into_iter()
+            .map(|translation| translation.text)
+            .collect())
+    }
+
+    /// Make API request with retry logic.
+    ///
+    /// Quota exhaustion and client errors are returned immediately; rate limits,
+    /// server errors, and transport failures are retried.
+    async fn request_with_retry(
+        &self,
+        request: &TranslateRequest<'_>,
+    ) -> Result<TranslateResponse> {
+        let url = format!("{}/translate", self.api_base.trim_end_matches('/'));
+        let pacing = Pacing {
+            retry_count: self.retry_count,
+            retry_delay_ms: self.retry_delay_ms,
+            rate_limiter: &self.rate_limiter,
+            estimated_tokens: 0,
+        };
+        let body = http::send_with_retry(
+            "DeepL",
+            pacing,
+            || {
+                self.client
+                    .post(&url)
+                    .header("Authorization", format!("DeepL-Auth-Key {}", self.api_key))
+                    .json(request)
+            },
+            |status| match status.as_u16() {
+                QUOTA_EXCEEDED_STATUS => Some(Error::TranslationQuotaExceeded),
+                403 => Some(Error::TranslationRequest(
+                    "DeepL rejected the API key".to_string(),
+                )),
+                _ => None,
+            },
+        )
+        .await?;
+
+        serde_json::from_slice(&body).map_err(|_| {
+            Error::TranslationInvalidResponse("DeepL returned malformed JSON".to_string())
+        })
+    }
+}
+
+#[async_trait]
+impl Translator for DeepLTranslator {
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "DeepL",
+            requires_api_key: true,
+            supports_auto_detect: true,
+        }
+    }
+
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        let mut identity =
+            TranslatorCacheIdentity::new("deepl", http::normalized_endpoint(&self.api_base), "");
+        if let Some(formality) = self.formality {
+            identity = identity.with_option("formality", formality.as_str());
+        }
+        if let Some(glossary_id) = &self.glossary_id {
+            identity = identity.with_option("glossary_id", glossary_id.clone());
+        }
+        identity
+    }
+
+    async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
+        let mut translations = self
+            .translate_batch(&[text], source, target, &TranslationContext::default())
+            .await?;
+        translations.pop().ok_or_else(|| {
+            Error::TranslationInvalidResponse("DeepL returned no translation".to_string())
+        })
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        _context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        // Blank blocks pass through untouched; DeepL bills every character.
+        let pending: Vec<&str> = texts
+            .iter()
+            .copied()
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+
+        let mut translated = Vec::with_capacity(pending.len());
+        let mut start = 0;
+        while start < pending.len() {
+            let mut end = start;
+            let mut bytes = 0;
+            while end < pending.len()
+                && end - start < MAX_BATCH_TEXTS
+                && (end == start || bytes + pending[end].len() <= MAX_BATCH_BYTES)
+            {
+                bytes += pending[end].len();
+                end += 1;
+            }
+
+            translated.extend(
+                self.translate_chunk(&pending[start..end], source, target)
+                    .await?,
+            );
+            start = end;
+        }
+
+        let mut translated = translated.into_iter();
+        Ok(texts
+            .iter()
+            .map(|text| {
+                if text.trim().is_empty() {
+                    (*text).to_string()
+                } else {
+                    translated.next().unwrap_or_default()
+                }
+            })
+            .collect())
+    }
+
+    fn is_available(&self) -> bool {
+        !self.api_key.trim().is_empty()
+    }
+}
+
+/// Free-plan keys carry a `:fx` suffix; everything else is a pro key.
+fn default_api_base(api_key: &str) -> &'static str {
+    if api_key.trim().ends_with(":fx") {
+        DEEPL_FREE_API_BASE
+    } else {
+        DEEPL_PRO_API_BASE
+    }
+}
+
+/// DeepL source code, or `None` to let DeepL detect the language.
+///
+/// Source languages are never regional, so `pt-BR` is sent as `PT`.
+fn source_code(lang: &Lang) -> Option<String> {
+    let code = lang.as_str();
+    if code == "auto" {
+        return None;
+    }
+    let base = code.split(['-', '_']).next().unwrap_or(code);
+    Some(base.to_ascii_uppercase())
+}
+
+/// DeepL target code; bare English and Portuguese need a regional variant.
+fn target_code(lang: &Lang) -> String {
+    match lang.as_str() {
+        "en" => "EN-US".to_string(),
+        "pt" => "PT-PT".to_string(),
+        "zh" | "zh-CN" => "ZH-HANS".to_string(),
+        "zh-TW" => "ZH-HANT".to_string(),
+        code => code.replace('_', "-").to_ascii_uppercase(),
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn translator(server: &MockServer, options: DeepLConfig) -> DeepLTranslator {
+        DeepLTranslator::new(
+            DeepLConfig {
+                api_key: Some("test-key".to_string()),
+                api_base: Some(server.uri()),
+                ..options
+            },
+            2,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn endpoint_follows_key_plan() {
+        assert_eq!(default_api_base("abc:fx"), DEEPL_FREE_API_BASE);
+        assert_eq!(default_api_base("abc"), DEEPL_PRO_API_BASE);
+    }
+
+    #[test]
+    fn language_codes_map_to_deepl_variants() {
+        assert_eq!(source_code(&Lang::new("auto")), None);
+        assert_eq!(source_code(&Lang::new("pt-BR")), Some("PT".to_string()));
+        assert_eq!(target_code(&Lang::new("en")), "EN-US");
+        assert_eq!(target_code(&Lang::new("zh-CN")), "ZH-HANS");
+        assert_eq!(target_code(&Lang::new("en-GB")), "EN-GB");
+    }
+
+    #[test]
+    fn cache_identity_includes_formality_and_glossary() {
+        let plain =
+            DeepLTranslator::new(DeepLConfig::default(), 1, 0, &HttpClientConfig::default())
+                .unwrap();
+        let formal = DeepLTranslator::new(
+            DeepLConfig {
+                formality: Some(DeepLFormality::More),
+                glossary_id: Some("g-1".to_string()),
+                ..DeepLConfig::default()
+            },
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(plain.cache_identity().backend(), "deepl");
+        assert_ne!(plain.cache_identity(), formal.cache_identity());
+        assert_eq!(
+            formal.cache_identity().options().collect::<Vec<_>>(),
+            vec![("formality", "more"), ("glossary_id", "g-1")]
+        );
+    }
+
+    #[tokio::test]
+    async fn batch_is_sent_as_one_request_with_options() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(header("authorization", "DeepL-Auth-Key test-key"))
+            .and(body_partial_json(serde_json::json!({
+                "text": ["Bonjour", "Au revoir"],
+                "source_lang": "FR",
+                "target_lang": "EN-US",
+                "formality": "less",
+                "glossary_id": "g-1",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "translations": [
+                    {"detected_source_language": "FR", "text": "Hello"},
+                    {"detected_source_language": "FR", "text": "Goodbye"},
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = translator(
+            &server,
+            DeepLConfig {
+                formality: Some(DeepLFormality::Less),
+                glossary_id: Some("g-1".to_string()),
+                ..DeepLConfig::default()
+            },
+        );
+        let translations = translator
+            .translate_batch(
+                &["Bonjour", " ", "Au revoir"],
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(translations, vec!["Hello", " ", "Goodbye"]);
+    }
+
+    #[tokio::test]
+    async fn quota_exhaustion_is_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(456))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let result = translator(&server, DeepLConfig::default())
+            .translate("Bonjour", &Lang::new("fr"), &Lang::new("en"))
+            .await;
+
+        assert!(matches!(result, Err(Error::TranslationQuotaExceeded)));
+    }
+
+    #[tokio::test]
+    async fn rate_limits_are_retried_then_reported() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "0"))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let result = translator(&server, DeepLConfig::default())
+            .translate("Bonjour", &Lang::new("fr"), &Lang::new("en"))
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(Error::TranslationRateLimited {
+                retry_after: Some(0)
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn glossary_requires_explicit_source_language() {
+        let server = MockServer::start().await;
+        let translator = translator(
+            &server,
+            DeepLConfig {
+                glossary_id: Some("g-1".to_string()),
+                ..DeepLConfig::default()
+            },
+        );
+
+        let result = translator
+            .translate("Bonjour", &Lang::new("auto"), &Lang::new("en"))
+            .await;
+        assert!(matches!(result, Err(Error::ConfigInvalid { .. })));
+    }
+}
@@ -0,0 +1,569 @@
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use super::traits::{
+    BatchTranslation, PartialTranslation, TokenUsage, TranslationContext, Translator,
+    TranslatorInfo,
+};
+use crate::config::{Lang, TranslatorCacheIdentity};
+use crate::error::{Error, Result};
+
+/// Health of one backend in a fallback chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Requests flow normally
+    Closed,
+    /// Too many consecutive failures; requests skip this backend until the cooldown ends
+    Open,
+    /// Cooldown ended; a single probe request decides whether to close again
+    HalfOpen,
+}
+
+#[derive(Debug)]
+enum Breaker {
+    Closed { failures: u32 },
+    Open { until: Instant },
+    HalfOpen { since: Instant },
+}
+
+#[derive(Debug)]
+struct CircuitBreaker {
+    state: Mutex<Breaker>,
+    failure_threshold: u32,
+    cooldown: Duration,
+}
+
+impl CircuitBreaker {
+    const fn new(failure_threshold: u32, cooldown: Duration) -> Self {
+        Self {
+            state: Mutex::new(Breaker::Closed { failures: 0 }),
+            failure_threshold,
+            cooldown,
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Breaker> {
+        self.state.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn state(&self) -> BreakerState {
+        match *self.lock() {
+            Breaker::Closed { .. } => BreakerState::Closed,
+            Breaker::Open { until } if Instant::now() >= until => BreakerState::HalfOpen,
+            Breaker::Open { .. } => BreakerState::Open,
+            Breaker::HalfOpen { .. } => BreakerState::HalfOpen,
+        }
+    }
+
+    /// Whether a request may be sent now, claiming the half-open probe if needed.
+    fn try_acquire(&self) -> bool {
+        let mut state = self.lock();
+        let now = Instant::now();
+        match *state {
+            Breaker::Closed { .. } => true,
+            Breaker::Open { until } if now >= until => {
+                *state = Breaker::HalfOpen { since: now };
+                true
+            }
+            // A probe abandoned mid-flight must not wedge the backend forever.
+            Breaker::HalfOpen { since } if now.duration_since(since) >= self.cooldown => {
+                *state = Breaker::HalfOpen { since: now };
+                true
+            }
+            Breaker::Open { .. } | Breaker::HalfOpen { .. } => false,
+        }
+    }
+
+    fn record_success(&self) {
+        *self.lock() = Breaker::Closed { failures: 0 };
+    }
+
+    /// Record a failure, returning true when it opened the breaker.
+    fn record_failure(&self) -> bool {
+        let mut state = self.lock();
+        let failures = match *state {
+            Breaker::Closed { failures } => failures.saturating_add(1),
+            Breaker::Open { .. } | Breaker::HalfOpen { .. } => self.failure_threshold,
+        };
+        if failures >= self.failure_threshold.max(1) {
+            *state = Breaker::Open {
+                until: Instant::now() + self.cooldown,
+            };
+            true
+        } else {
+            *state = Breaker::Closed { failures };
+            false
+        }
+    }
+}
+
+struct Backend {
+    translator: Arc<dyn Translator>,
+    breaker: CircuitBreaker,
+}
+
+impl Backend {
+    fn record_failure(&self, error: &Error) {
+        // A backend that cannot handle this language pair is not unhealthy.
+        if !matches!(error.cause(), Error::TranslationUnsupportedLanguage(_))
+            && self.breaker.record_failure()
+        {
+            warn!(
+                "Circuit opened for {} after repeated failures",
+                self.translator.name()
+            );
+        }
+    }
+}
+
+fn unavailable() -> Error {
+    Error::TranslationRequest("all translation backends are unavailable".to_string())
+}
+
+/// Translator that tries an ordered list of backends, skipping unhealthy ones.
+///
+/// Each backend has a circuit breaker that opens after `failure_threshold`
+/// consecutive failures and lets a single probe through once `cooldown` has
+/// passed. A whole batch is always translated by one backend, and
+/// [`Translator::translate_batch_traced`] reports which one.
+pub struct FallbackTranslator {
+    backends: Vec<Backend>,
+}
+
+impl FallbackTranslator {
+    pub fn new(
+        translators: Vec<Arc<dyn Translator>>,
+        failure_threshold: u32,
+        cooldown: Duration,
+    ) -> Self {
+        let backends = translators
+            .into_iter()
+            .map(|translator| Backend {
+                translator,
+                breaker: CircuitBreaker::new(failure_threshold, cooldown),
+            })
+            .collect();
+        Self { backends }
+    }
+
+    /// Current breaker state of each backend, in fallback order.
+    pub fn health(&self) -> Vec<(&'static str, BreakerState)> {
+        self.backends
+            .iter()
+            .map(|backend| (backend.translator.name(), backend.breaker.state()))
+            .collect()
+    }
+
+    /// First backend whose breaker is not open.
+    fn current_backend(&self) -> Option<&Backend> {
+        self.backends
+            .iter()
+            .find(|backend| backend.breaker.state() != BreakerState::Open)
+    }
+
+    /// Run `call` against each healthy backend in order until one succeeds,
+    /// returning its value with the usage billed by the backends that failed.
+    async fn first_success<'a, T, F, Fut>(&'a self, call: F) -> Result<(T, TokenUsage)>
+    where
+        F: Fn(&'a Arc<dyn Translator>) -> Fut + Send,
+        Fut: Future<Output = Result<T>> + Send,
+        T: Send,
+    {
+        let mut last_error = None;
+        let mut billed = TokenUsage::default();
+        for backend in &self.backends {
+            if !backend.breaker.try_acquire() {
+                continue;
+            }
+
+            match call(&backend.translator).await {
+                Ok(value) => {
+                    backend.breaker.record_success();
+                    return Ok((value, billed));
+                }
+                Err(error) => {
+                    backend.record_failure(&error);
+                    let (error, usage) = error.into_unbilled();
+                    billed += usage;
+                    info!(
+                        "{} failed ({}); trying the next backend",
+                        backend.translator.name(),
+                        error
+                    );
+                    last_error = Some(error);
+                }
+            }
+        }
+
+        Err(last_error.unwrap_or_else(unavailable).with_usage(billed))
+    }
+}
+
+#[async_trait]
+impl Translator for FallbackTranslator {
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "Fallback chain",
+            requires_api_key: false,
+            supports_auto_detect: self
+                .backends
+                .iter()
+                .all(|backend| backend.translator.info().supports_auto_detect),
+        }
+    }
+
+    /// Identity of the first backend that would currently be tried.
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        self.current_backend()
+            .or_else(|| self.backends.first())
+            .map_or_else(
+                || TranslatorCacheIdentity::new("fallback", "", ""),
+                |backend| backend.translator.cache_identity(),
+            )
+    }
+
+    async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
+        let (translation, _) = self
+            .first_success(|translator| translator.translate(text, source, target))
+            .await?;
+        Ok(translation)
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        Ok(self
+            .translate_batch_traced(texts, source, target, context)
+            .await?
+            .translations)
+    }
+
+    async fn translate_batch_traced(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<BatchTranslation> {
+        let (mut batch, billed) = self
+            .first_success(|translator| {
+                translator.translate_batch_traced(texts, source, target, context)
+            })
+            .await?;
+        batch.usage += billed;
+        Ok(batch)
+    }
+
+    /// Stream from the first backend that accepts a request, naming it in
+    /// [`PartialTranslation::identity`].
+    ///
+    /// Partial output cannot be retracted, so a failing stream is not retried
+    /// on the next backend; its failure still counts towards the breaker.
+    fn translate_stream<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a Lang,
+        target: &'a Lang,
+        context: &'a TranslationContext,
+    ) -> BoxStream<'a, Result<PartialTranslation>> {
+        let Some(backend) = self
+            .backends
+            .iter()
+            .find(|backend| backend.breaker.try_acquire())
+        else {
+            return stream::once(async { Err(unavailable()) }).boxed();
+        };
+
+        let identity = backend.translator.cache_identity();
+        backend
+            .translator
+            .translate_stream(text, source, target, context)
+            .inspect(move |item| match item {
+                Ok(_) => backend.breaker.record_success(),
+                Err(error) => backend.record_failure(error),
+            })
+            .map_ok(move |partial| PartialTranslation {
+                identity: partial.identity.or_else(|| Some(identity.clone())),
+                ..partial
+            })
+            .boxed()
+    }
+
+    /// Revise through the backend that streamed the block.
+    async fn revise(
+        &self,
+        text: &str,
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+        translated: PartialTranslation,
+        usage: &mut TokenUsage,
+    ) -> Result<String> {
+        let backend = translated.identity.as_ref().map_or_else(
+            || self.current_backend(),
+            |identity| {
+                self.backends
+                    .iter()
+                    .find(|backend| backend.translator.cache_identity() == *identity)
+            },
+        );
+        match backend {
+            Some(backend) => {
+                backend
+                    .translator
+                    .revise(text, source, target, context, translated, usage)
+                    .await
+            }
+            None => Ok(translated.text),
+        }
+    }
+
+    async fn check_languages(&self, source: &Lang, target: &Lang) -> Result<()> {
+        let mut first_error = None;
+        for backend in &self.backends {
+            match backend.translator.check_languages(source, target).await {
+                Ok(()) => return Ok(()),
+                Err(error) => {
+                    first_error.get_or_insert(error);
+                }
+            }
+        }
+        first_error.map_or(Ok(()), Err)
+    }
+
+    fn is_available(&self) -> bool {
+        self.backends
+            .iter()
+            .any(|backend| backend.translator.is_available())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+    struct Scripted {
+        name: &'static str,
+        failing: AtomicBool,
+        calls: AtomicUsize,
+    }
+
+    impl Scripted {
+        fn new(name: &'static str, failing: bool) -> Arc<Self> {
+            Arc::new(Self {
+                name,
+                failing: AtomicBool::new(failing),
+                calls: AtomicUsize::new(0),
+            })
+        }
+    }
+
+    #[async_trait]
+    impl Translator for Scripted {
+        fn info(&self) -> TranslatorInfo {
+            TranslatorInfo {
+                name: self.name,
+                requires_api_key: false,
+                supports_auto_detect: true,
+            }
+        }
+
+        fn cache_identity(&self) -> TranslatorCacheIdentity {
+            TranslatorCacheIdentity::new(self.name, "local", "")
+        }
+
+        async fn translate(&self, text: &str, _source: &Lang, _target: &Lang) -> Result<String> {
+            self.calls.fetch_add(1, Ordering::SeqCst);
+            if self.failing.load(Ordering::SeqCst) {
+                Err(Error::TranslationTimeout)
+            } else {
+                Ok(format!("{}: {text}", self.name))
+            }
+        }
+
+        async fn revise(
+            &self,
+            _text: &str,
+            _source: &Lang,
+            _target: &Lang,
+            _context: &TranslationContext,
+            translated: PartialTranslation,
+            _usage: &mut TokenUsage,
+        ) -> Result<String> {
+            Ok(format!("{} revised {}", self.name, translated.text))
+        }
+    }
+
+    fn chain(
+        primary: &Arc<Scripted>,
+        secondary: &Arc<Scripted>,
+        cooldown: Duration,
+    ) -> FallbackTranslator {
+        FallbackTranslator::new(vec![primary.clone(), secondary.clone()], 2, cooldown)
+    }
+
+    async fn batch(translator: &FallbackTranslator) -> Result<BatchTranslation> {
+        translator
+            .translate_batch_traced(
+                &["hi"],
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+            )
+            .await
+    }
+
+    #[tokio::test]
+    async fn failed_backend_falls_through_and_reports_actual_identity() {
+        let primary = Scripted::new("primary", true);
+        let secondary = Scripted::new("secondary", false);
+        let translator = chain(&primary, &secondary, Duration::from_mins(1));
+
+        let result = batch(&translator).await.unwrap();
+        assert_eq!(result.translations, vec!["secondary: hi"]);
+        assert_eq!(result.identity.backend(), "secondary");
+    }
+
+    #[tokio::test]
+    async fn usage_billed_by_failed_backends_is_reported() {
+        let primary = Scripted::new("primary", true);
+        let secondary = Scripted::new("secondary", false);
+        let translator = chain(&primary, &secondary, Duration::from_mins(1));
+        assert_eq!(batch(&translator).await.unwrap().usage.requests, 2);
+    }
+
+    #[tokio::test]
+    async fn breaker_opens_after_threshold_and_skips_backend() {
+        let primary = Scripted::new("primary", true);
+        let secondary = Scripted::new("secondary", false);
+        let translator = chain(&primary, &secondary, Duration::from_mins(1));
+
+        for _ in 0..4 {
+            batch(&translator).await.unwrap();
+        }
+
+        assert_eq!(primary.calls.load(Ordering::SeqCst), 2);
+        assert_eq!(
+            translator.health(),
+            vec![
+                ("primary", BreakerState::Open),
+                ("secondary", BreakerState::Closed)
+            ]
+        );
+        assert_eq!(translator.cache_identity().backend(), "secondary");
+    }
+
+    #[tokio::test]
+    async fn half_open_probe_closes_breaker_on_success() {
+        let primary = Scripted::new("primary", true);
+        let secondary = Scripted::new("secondary", false);
+        let translator = chain(&primary, &secondary, Duration::ZERO);
+
+        batch(&translator).await.unwrap();
+        batch(&translator).await.unwrap();
+        assert_eq!(translator.health()[0].1, BreakerState::HalfOpen);
+
+        primary.failing.store(false, Ordering::SeqCst);
+        let result = batch(&translator).await.unwrap();
+        assert_eq!(result.identity.backend(), "primary");
+        assert_eq!(translator.health()[0].1, BreakerState::Closed);
+    }
+
+    #[tokio::test]
+    async fn stream_uses_the_backend_named_by_cache_identity() {
+        let primary = Scripted::new("primary", true);
+        let secondary = Scripted::new("secondary", false);
+        let translator = chain(&primary, &secondary, Duration::from_mins(1));
+        let context = TranslationContext::default();
+        let (fr, en) = (Lang::new("fr"), Lang::new("en"));
+
+        for _ in 0..2 {
+            let streamed: Result<Vec<PartialTranslation>> = translator
+                .translate_stream("hi", &fr, &en, &context)
+                .try_collect()
+                .await;
+            assert!(streamed.is_err());
+        }
+
+        assert_eq!(translator.cache_identity().backend(), "secondary");
+        let streamed: Vec<PartialTranslation> = translator
+            .translate_stream("hi", &fr, &en, &context)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            streamed,
+            vec![PartialTranslation {
+                usage: TokenUsage {
+                    requests: 1,
+                    ..TokenUsage::default()
+                },
+                identity: Some(secondary.cache_identity()),
+                ..PartialTranslation::from("secondary: hi".to_string())
+            }]
+        );
+    }
+
+    #[tokio::test]
+    async fn stream_falls_through_while_another_task_holds_the_probe() {
+        let primary = Scripted::new("primary", true);
+        let secondary = Scripted::new("secondary", false);
+        let translator = chain(&primary, &secondary, Duration::from_millis(50));
+        batch(&translator).await.unwrap();
+        batch(&translator).await.unwrap();
+        tokio::time::sleep(Duration::from_millis(60)).await;
+        assert!(translator.backends[0].breaker.try_acquire());
+
+        let context = TranslationContext::default();
+        let streamed: Vec<PartialTranslation> = translator
+            .translate_stream("hi", &Lang::new("fr"), &Lang::new("en"), &context)
+            .try_collect()
+            .await
+            .unwrap();
+        assert_eq!(streamed[0].text, "secondary: hi");
+    }
+
+    #[tokio::test]
+    async fn revise_goes_to_the_backend_that_streamed_the_block() {
+        let primary = Scripted::new("primary", false);
+        let secondary = Scripted::new("secondary", false);
+        let translator = chain(&primary, &secondary, Duration::from_mins(1));
+        let streamed = PartialTranslation {
+            identity: Some(secondary.cache_identity()),
+            ..PartialTranslation::from("hi".to_string())
+        };
+
+        let revised = translator
+            .revise(
+                "salut",
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+                streamed,
+                &mut TokenUsage::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(revised, "secondary revised hi");
+    }
+
+    #[tokio::test]
+    async fn exhausted_chain_returns_last_error() {
+        let primary = Scripted::new("primary", true);
+        let secondary = Scripted::new("secondary", true);
+        let translator = chain(&primary, &secondary, Duration::from_mins(1));
+
+        let error = batch(&translator).await.unwrap_err();
+        assert!(matches!(error.cause(), Error::TranslationTimeout));
+        assert_eq!(error.billed_usage().requests, 2);
+    }
+}
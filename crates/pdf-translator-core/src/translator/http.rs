@@ -0,0 +1,577 @@
+//! HTTP helpers shared by the network translator backends.
+
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Certificate, Client, Identity, Proxy, RequestBuilder, StatusCode};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::{debug, error, warn};
+
+use super::rate_limit::RateLimiter;
+use crate::config::HttpClientConfig;
+use crate::error::{Error, Result};
+
+pub const MAX_SUCCESS_BODY_BYTES: usize = 1024 * 1024;
+pub const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
+pub const MAX_RETRY_AFTER_SECONDS: u64 = 300;
+/// Upper bound on the exponential backoff between attempts.
+pub const MAX_RETRY_DELAY_MS: u64 = 30_000;
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+/// Build the HTTP client used by translator backends.
+pub fn client(config: &HttpClientConfig) -> Result<Client> {
+    let invalid = |field: &str, reason: String| Error::ConfigInvalid {
+        field: format!("translator.http.{field}"),
+        reason,
+    };
+    for (field, seconds) in [
+        (
+            "connect_timeout_seconds",
+            Some(config.connect_timeout_seconds),
+        ),
+        ("read_timeout_seconds", config.read_timeout_seconds),
+        ("timeout_seconds", Some(config.timeout_seconds)),
+    ] {
+        if seconds == Some(0) {
+            return Err(invalid(field, "must be greater than 0".to_string()));
+        }
+    }
+
+    let mut builder = Client::builder()
+        .connect_timeout(Duration::from_secs(config.connect_timeout_seconds))
+        .timeout(Duration::from_secs(config.timeout_seconds));
+    if let Some(seconds) = config.read_timeout_seconds {
+        builder = builder.read_timeout(Duration::from_secs(seconds));
+    }
+
+    if let Some(proxy) = &config.proxy {
+        let proxy = Proxy::all(proxy.trim())
+            .map_err(|_| invalid("proxy", "must be a valid proxy URL".to_string()))?;
+        builder = builder.proxy(proxy);
+    }
+
+    for path in &config.ca_certificates {
+        let pem = read_pem("ca_certificates", path)?;
+        let certificates = Certificate::from_pem_bundle(&pem).map_err(|_| {
+            invalid(
+                "ca_certificates",
+                format!("{} holds no valid PEM certificate", path.display()),
+            )
+        })?;
+        for certificate in certificates {
+            builder = builder.add_root_certificate(certificate);
+        }
+    }
+
+    match (&config.client_certificate, &config.client_key) {
+        (Some(certificate), Some(key)) => {
+            let identity = Identity::from_pkcs8_pem(
+                &read_pem("client_certificate", certificate)?,
+                &read_pem("client_key", key)?,
+            )
+            .map_err(|_| {
+                invalid(
+                    "client_certificate",
+                    "certificate and PKCS #8 key could not be loaded".to_string(),
+                )
+            })?;
+            builder = builder.identity(identity);
+        }
+        (None, None) => {}
+        _ => {
+            return Err(invalid(
+                "client_certificate",
+                "client_certificate and client_key must be set together".to_string(),
+            ));
+        }
+    }
+
+    let mut headers = HeaderMap::new();
+    for (name, value) in &config.headers {
+        let name = HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| invalid("headers", format!("'{name}' is not a valid header name")))?;
+        let mut value = HeaderValue::from_str(value)
+            .map_err(|_| invalid("headers", format!("value of '{name}' is not valid")))?;
+        value.set_sensitive(true);
+        headers.insert(name, value);
+    }
+
+    builder
+        .default_headers(headers)
+        .build()
+        .map_err(|error| Error::ConfigInvalid {
+            field: "translator.http".to_string(),
+            reason: format!("HTTP client could not be created: {error}"),
+        })
+}
+
+fn read_pem(field: &str, path: &Path) -> Result<Vec<u8>> {
+    std::fs::read(path).map_err(|error| Error::ConfigInvalid {
+        field: format!("translator.http.{field}"),
+        reason: format!("failed to read {}: {error}", path.display()),
+    })
+}
+
+/// Strip credentials, query, fragment, and trailing slashes from an API base URL.
+pub fn normalized_endpoint(api_base: &str) -> String {
+    let trimmed = api_base.trim();
+    if let Ok(mut url) = reqwest::Url::parse(trimmed) {
+        let path = url.path().trim_end_matches('/').to_string();
+        url.set_path(if path.is_empty() { "/" } else { &path });
+        let _ = url.set_username("");
+        let _ = url.set_password(None);
+        url.set_query(None);
+        url.set_fragment(None);
+        return url.as_str().trim_end_matches('/').to_string();
+    }
+
+    trimmed.trim_end_matches('/').to_string()
+}
+
+/// `Retry-After` delay in seconds, capped to a sane maximum.
+pub fn retry_after_seconds(response: &reqwest::Response) -> Option<u64> {
+    response
+        .headers()
+        .get("retry-after")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| parse_retry_after(value, SystemTime::now()))
+        .map(|seconds| seconds.min(MAX_RETRY_AFTER_SECONDS))
+}
+
+/// Parse a `Retry-After` value given either as delay seconds or as an HTTP date.
+fn parse_retry_after(value: &str, now: SystemTime) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(seconds);
+    }
+    let at = parse_http_date(value)?;
+    // A date in the past means the client may retry right away.
+    Some(at.duration_since(now).map_or(0, |delay| {
+        delay.as_secs() + u64::from(delay.subsec_nanos() > 0)
+    }))
+}
+
+/// Parse an IMF-fixdate such as `Sun, 06 Nov 1994 08:49:37 GMT`.
+fn parse_http_date(value: &str) -> Option<SystemTime> {
+    let (_, date) = value.split_once(", ")?;
+    let fields: Vec<&str> = date.split(' ').collect();
+    let [day, month, year, time, "GMT"] = fields.as_slice() else {
+        return None;
+    };
+    let day: u64 = day.parse().ok()?;
+    let month = u64::try_from(MONTHS.iter().position(|name| name == month)? + 1).ok()?;
+    let year: u64 = year.parse().ok()?;
+    let clock: Vec<u64> = time
+        .split(':')
+        .map(|field| field.parse().ok())
+        .collect::<Option<_>>()?;
+    let [hours, minutes, seconds] = clock.as_slice() else {
+        return None;
+    };
+    if !(1970..=9999).contains(&year)
+        || !(1..=31).contains(&day)
+        || *hours > 23
+        || *minutes > 59
+        || *seconds > 60
+    {
+        return None;
+    }
+
+    let days = days_since_epoch(year, month, day);
+    Some(UNIX_EPOCH + Duration::from_secs(days * 86_400 + hours * 3_600 + minutes * 60 + seconds))
+}
+
+/// Days from 1970-01-01 to a proleptic Gregorian date in or after 1970.
+const fn days_since_epoch(year: u64, month: u64, day: u64) -> u64 {
+    // Count from March so the leap day falls at the end of each year.
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year / 400;
+    let year_of_era = year - era * 400;
+    let day_of_year = (153 * ((month + 9) % 12) + 2) / 5 + day - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+/// Whether a request that failed with `status` may succeed when sent again.
+///
+/// Timeouts, rate limits, and transient gateway or server failures
+/// (including Anthropic's 529 "overloaded") are retried; other statuses (bad
+/// requests, rejected keys, unsupported features) would fail the same way
+/// every time.
+pub const fn is_retryable(status: StatusCode) -> bool {
+    matches!(status.as_u16(), 408 | 429 | 500 | 502 | 503 | 504 | 529)
+}
+
+/// Delay before retrying after failed attempt `attempt` (0-based).
+///
+/// A server-provided `Retry-After` wins; otherwise the delay doubles from
+/// `base_ms` per attempt up to [`MAX_RETRY_DELAY_MS`], and a random half of it
+/// is jittered so concurrent clients do not retry in lockstep.
+pub fn retry_delay(attempt: u32, base_ms: u64, retry_after: Option<u64>) -> Duration {
+    if let Some(seconds) = retry_after {
+        return Duration::from_secs(seconds.min(MAX_RETRY_AFTER_SECONDS));
+    }
+    let ceiling = 1_u64
+        .checked_shl(attempt)
+        .map_or(u64::MAX, |factor| base_ms.saturating_mul(factor))
+        .min(MAX_RETRY_DELAY_MS);
+    let half = ceiling / 2;
+    Duration::from_millis(ceiling - half + jitter(half))
+}
+
+/// Pseudo-random value in `0..=bound`, seeded per call from the process's
+/// randomized hasher keys.
+fn jitter(bound: u64) -> u64 {
+    if bound == 0 {
+        return 0;
+    }
+    RandomState::new().build_hasher().finish() % (bound + 1)
+}
+
+/// Read a response body, failing once it grows past `limit` bytes.
+pub async fn read_body_limited(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>> {
+    let limit_u64 = u64::try_from(limit).map_err(|_| {
+        Error::TranslationInvalidResponse("response size limit is invalid".to_string())
+    })?;
+    if response
+        .content_length()
+        .is_some_and(|length| length > limit_u64)
+    {
+        return Err(Error::TranslationInvalidResponse(
+            "translation API response exceeded the size limit".to_string(),
+        ));
+    }
+
+    let mut body = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|_| {
+        Error::TranslationRequest("failed to read translation API response".to_string())
+    })? {
+        let new_len = body.len().checked_add(chunk.len()).ok_or_else(|| {
+            Error::TranslationInvalidResponse(
+                "translation API response exceeded the size limit".to_string(),
+            )
+        })?;
+        if new_len > limit {
+            return Err(Error::TranslationInvalidResponse(
+                "translation API response exceeded the size limit".to_string(),
+            ));
+        }
+        body.extend_from_slice(&chunk);
+    }
+    Ok(body)
+}
+
+/// Map a transport failure onto the translation error variants.
+pub fn request_error(error: &reqwest::Error) -> Error {
+    if error.is_timeout() {
+        Error::TranslationTimeout
+    } else {
+        Error::TranslationRequest("translation API request could not be completed".to_string())
+    }
+}
+
+/// Retry and pacing settings for [`send_with_retry`] and
+/// [`complete_with_retry`](super::chat::complete_with_retry).
+pub struct Pacing<'a> {
+    /// Number of attempts
+    pub retry_count: u32,
+    /// Initial delay between attempts in milliseconds
+    pub retry_delay_ms: u64,
+    /// Limiter consulted before every attempt
+    pub rate_limiter: &'a RateLimiter,
+    /// Tokens reserved per attempt until the upstream reports actual usage
+    pub estimated_tokens: u64,
+}
+
+/// Send a request, retrying rate limits, transient server errors, and
+/// transport failures with exponential backoff.
+///
+/// `build` is called once per attempt, after the rate limiter in `pacing`
+/// admits it. `fatal` maps statuses that must fail
+/// immediately to a specific error; any other status that is not
+/// [retryable](is_retryable) also fails immediately. Returns the body of the
+/// first successful response.
+pub async fn send_with_retry<B, F>(
+    label: &str,
+    pacing: Pacing<'_>,
+    build: B,
+    fatal: F,
+) -> Result<Vec<u8>>
+where
+    B: Fn() -> RequestBuilder + Send + Sync,
+    F: Fn(StatusCode) -> Option<Error> + Send + Sync,
+{
+    let response = send_for_response(label, pacing, build, fatal).await?;
+    read_body_limited(response, MAX_SUCCESS_BODY_BYTES).await
+}
+
+/// Like [`send_with_retry`], but hands back the successful response unread.
+///
+/// Used for streamed bodies, where only establishing the response is retried.
+pub async fn send_for_response<B, F>(
+    label: &str,
+    pacing: Pacing<'_>,
+    build: B,
+    fatal: F,
+) -> Result<reqwest::Response>
+where
+    B: Fn() -> RequestBuilder + Send + Sync,
+    F: Fn(StatusCode) -> Option<Error> + Send + Sync,
+{
+    let attempts = pacing.retry_count.max(1);
+    let mut last_error = None;
+
+    for attempt in 0..attempts {
+        debug!("{} request attempt {}/{}", label, attempt + 1, attempts);
+
+        pacing.rate_limiter.acquire(pacing.estimated_tokens).await;
+        let retry_after = match build().send().await {
+            Ok(response) => {
+                let status = response.status();
+                if status.is_success() {
+                    return Ok(response);
+                }
+
+                let retry_after = retry_after_seconds(&response);
+                let _ = read_body_limited(response, MAX_ERROR_BODY_BYTES).await;
+                if let Some(error) = fatal(status) {
+                    return Err(error);
+                }
+                if status == StatusCode::TOO_MANY_REQUESTS {
+                    warn!("{} rate limited the request", label);
+                    last_error = Some(Error::TranslationRateLimited { retry_after });
+                } else {
+                    let error =
+                        Error::TranslationRequest(format!("{label} returned HTTP {status}"));
+                    if !is_retryable(status) {
+                        return Err(error);
+                    }
+                    warn!("{} returned HTTP {}", label, status);
+                    last_error = Some(error);
+                }
+                retry_after
+            }
+            Err(error) => {
+                warn!("{} request failed: {}", label, error);
+                last_error = Some(request_error(&error));
+                None
+            }
+        };
+
+        if attempt + 1 < attempts {
+            tokio::time::sleep(retry_delay(attempt, pacing.retry_delay_ms, retry_after)).await;
+        }
+    }
+
+    error!("{} request failed after {} attempts", label, attempts);
+    Err(last_error.unwrap_or(Error::TranslationMaxRetriesExceeded))
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    static UNLIMITED: RateLimiter = RateLimiter::unlimited();
+
+    fn pacing(retry_count: u32) -> Pacing<'static> {
+        Pacing {
+            retry_count,
+            retry_delay_ms: 0,
+            rate_limiter: &UNLIMITED,
+            estimated_tokens: 0,
+        }
+    }
+
+    async fn send(server: &MockServer) -> Result<Vec<u8>> {
+        let client = client(&HttpClientConfig::default()).unwrap();
+        send_with_retry("Test API", pacing(3), || client.get(server.uri()), |_| None).await
+    }
+
+    #[tokio::test]
+    async fn client_errors_are_not_retried() {
+        for status in [400, 401, 404, 422] {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(status))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            assert!(
+                matches!(send(&server).await, Err(Error::TranslationRequest(_))),
+                "HTTP {status} should fail without a retry"
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_server_errors_are_retried() {
+        for status in [408, 500, 502, 503, 504, 529] {
+            let server = MockServer::start().await;
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(status))
+                .up_to_n_times(2)
+                .expect(2)
+                .mount(&server)
+                .await;
+            Mock::given(method("GET"))
+                .respond_with(ResponseTemplate::new(200).set_body_string("ok"))
+                .expect(1)
+                .mount(&server)
+                .await;
+
+            assert_eq!(send(&server).await.unwrap(), b"ok", "HTTP {status}");
+        }
+    }
+
+    #[tokio::test]
+    async fn permanent_server_errors_are_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(ResponseTemplate::new(501))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        assert!(send(&server).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn http_date_retry_after_is_honored() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .respond_with(
+                ResponseTemplate::new(429)
+                    .insert_header("retry-after", "Thu, 01 Jan 1970 00:00:00 GMT"),
+            )
+            .expect(3)
+            .mount(&server)
+            .await;
+
+        assert!(matches!(
+            send(&server).await,
+            Err(Error::TranslationRateLimited {
+                retry_after: Some(0)
+            })
+        ));
+    }
+
+    #[tokio::test]
+    async fn configured_headers_are_sent_with_every_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(header("api-version", "2024-06-01"))
+            .and(header("openai-organization", "org-1"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let config = HttpClientConfig {
+            headers: [
+                ("api-version", "2024-06-01"),
+                ("OpenAI-Organization", "org-1"),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect(),
+            ..HttpClientConfig::default()
+        };
+        let client = client(&config).unwrap();
+        send_with_retry("Test API", pacing(1), || client.get(server.uri()), |_| None)
+            .await
+            .unwrap();
+    }
+
+    #[test]
+    fn invalid_client_options_are_config_errors() {
+        let field_of = |config: HttpClientConfig| match client(&config) {
+            Err(Error::ConfigInvalid { field, .. }) => field,
+            other => format!("unexpected: {other:?}"),
+        };
+
+        assert_eq!(
+            field_of(HttpClientConfig {
+                timeout_seconds: 0,
+                ..HttpClientConfig::default()
+            }),
+            "translator.http.timeout_seconds"
+        );
+        assert_eq!(
+            field_of(HttpClientConfig {
+                proxy: Some("http://proxy:99999".to_string()),
+                ..HttpClientConfig::default()
+            }),
+            "translator.http.proxy"
+        );
+        assert_eq!(
+            field_of(HttpClientConfig {
+                client_key: Some("key.pem".into()),
+                ..HttpClientConfig::default()
+            }),
+            "translator.http.client_certificate"
+        );
+        assert_eq!(
+            field_of(HttpClientConfig {
+                ca_certificates: vec!["/nonexistent/ca.pem".into()],
+                ..HttpClientConfig::default()
+            }),
+            "translator.http.ca_certificates"
+        );
+        assert_eq!(
+            field_of(HttpClientConfig {
+                headers: [("bad header".to_string(), "x".to_string())].into(),
+                ..HttpClientConfig::default()
+            }),
+            "translator.http.headers"
+        );
+    }
+
+    #[test]
+    fn retry_after_accepts_seconds_and_http_dates() {
+        let date = UNIX_EPOCH + Duration::from_secs(784_111_777);
+        let now = date - Duration::from_secs(90);
+
+        assert_eq!(parse_retry_after(" 7 ", now), Some(7));
+        assert_eq!(parse_http_date("Sun, 06 Nov 1994 08:49:37 GMT"), Some(date));
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", now),
+            Some(90)
+        );
+        assert_eq!(
+            parse_retry_after(
+                "Sun, 06 Nov 1994 08:49:37 GMT",
+                date + Duration::from_secs(5)
+            ),
+            Some(0)
+        );
+        assert_eq!(
+            parse_http_date("Tue, 29 Feb 2000 12:34:56 GMT"),
+            Some(UNIX_EPOCH + Duration::from_secs(951_827_696))
+        );
+        assert_eq!(
+            parse_retry_after("Sunday, 06-Nov-94 08:49:37 GMT", now),
+            None
+        );
+        assert_eq!(parse_retry_after("soon", now), None);
+    }
+
+    #[test]
+    fn backoff_grows_exponentially_within_jitter_and_cap() {
+        for attempt in 0..4 {
+            let ceiling = Duration::from_millis(1_000 << attempt);
+            let delay = retry_delay(attempt, 1_000, None);
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "{delay:?}");
+        }
+        assert!(retry_delay(40, 1_000, None) <= Duration::from_millis(MAX_RETRY_DELAY_MS));
+        assert_eq!(retry_delay(0, 1_000, Some(3)), Duration::from_secs(3));
+        assert_eq!(retry_delay(5, 0, None), Duration::ZERO);
+    }
+}
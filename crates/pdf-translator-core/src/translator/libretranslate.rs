@@ -0,0 +1,470 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use tokio::sync::OnceCell;
+
+use super::http::{self, Pacing};
+use super::rate_limit::RateLimiter;
+use super::traits::{TranslationContext, Translator, TranslatorInfo};
+use crate::config::{HttpClientConfig, Lang, TranslatorCacheIdentity};
+use crate::error::{Error, Result};
+
+/// Upper bound on texts sent in one request.
+const MAX_BATCH_TEXTS: usize = 32;
+/// Upper bound on source bytes sent in one request.
+const MAX_BATCH_BYTES: usize = 16 * 1024;
+
+/// LibreTranslate (Argos Translate) REST API translator for offline deployments
+pub struct LibreTranslateTranslator {
+    client: Client,
+    /// Base URL of the server (e.g., "http://localhost:5000")
+    pub api_base: String,
+    /// Optional API key for servers that require one
+    pub api_key: Option<String>,
+    /// Number of retry attempts
+    pub retry_count: u32,
+    /// Initial delay between retries in milliseconds, doubled per attempt
+    pub retry_delay_ms: u64,
+    languages: OnceCell<Vec<LibreLanguage>>,
+    rate_limiter: RateLimiter,
+}
+
+/// A language supported by a LibreTranslate server.
+#[derive(Debug, Clone, Deserialize)]
+pub struct LibreLanguage {
+    /// Language code used by the server (e.g., "en", "zh-Hans")
+    pub code: String,
+    /// Display name
+    pub name: String,
+    /// Codes this language can be translated into
+    #[serde(default)]
+    pub targets: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct TranslateRequest<'a> {
+    q: &'a [&'a str],
+    source: &'a str,
+    target: &'a str,
+    format: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TranslateResponse {
+    #[serde(rename = "translatedText")]
+    translated_text: serde_json::Value,
+}
+
+#[derive(Debug, Serialize)]
+struct DetectRequest<'a> {
+    q: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    api_key: Option<&'a str>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Detection {
+    confidence: f64,
+    language: String,
+}
+
+impl LibreTranslateTranslator {
+    /// Create a new LibreTranslate translator.
+    ///
+    /// Fails if the HTTP client options are invalid.
+    pub fn new(
+        api_base: String,
+        api_key: Option<String>,
+        retry_count: u32,
+        retry_delay_ms: u64,
+        http_config: &HttpClientConfig,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: http::client(http_config)?,
+            api_base,
+            api_key: api_key.filter(|key| !key.trim().is_empty()),
+            retry_count,
+            retry_delay_ms,
+            languages: OnceCell::new(),
+            rate_limiter: RateLimiter::unlimited(),
+        })
+    }
+
+    /// Pace requests through a (possibly shared) rate limiter.
+    ///
+    /// Only the request limit applies, since the backend bills no tokens.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}/{path}", self.api_base.trim_end_matches('/'))
+    }
+
+    async fn send<T: Serialize + Sync>(&self, path: &str, body: Option<&T>) -> Result<Vec<u8>> {
+        let url = self.url(path);
+        let pacing = Pacing {
+            retry_count: self.retry_count,
+            retry_delay_ms: self.retry_delay_ms,
+            rate_limiter: &self.rate_limiter,
+            estimated_tokens: 0,
+        };
+        http::send_with_retry(
+            "LibreTranslate",
+            pacing,
+            || {
+                body.map_or_else(
+                    || self.client.get(&url),
+                    |body| self.client.post(&url).json(body),
+                )
+            },
+            |status| {
+                (status.as_u16() == 403).then(|| {
+                    Error::TranslationRequest("LibreTranslate rejected the API key".to_string())
+                })
+            },
+        )
+        .await
+    }
+
+    /// Languages supported by the server, fetched once from `/languages`.
+    pub async fn languages(&self) -> Result<&[LibreLanguage]> {
+        let languages = self
+            .languages
+            .get_or_try_init(|| async {
+                let body = self.send::<()>("languages", None).await?;
+                serde_json::from_slice::<Vec<LibreLanguage>>(&body).map_err(|_| {
+                    Error::TranslationInvalidResponse(
+                        "LibreTranslate returned a malformed language list".to_string(),
+                    )
+                })
+            })
+            .await?;
+        Ok(languages)
+    }
+
+    /// Detect the language of `text` using `/detect`.
+    pub async fn detect(&self, text: &str) -> Result<Lang> {
+        let request = DetectRequest {
+            q: text,
+            api_key: self.api_key.as_deref(),
+        };
+        let body = self.send("detect", Some(&request)).await?;
+        let detections: Vec<Detection> = serde_json::from_slice(&body).map_err(|_| {
+            Error::TranslationInvalidResponse(
+                "LibreTranslate returned a malformed detection".to_string(),
+            )
+        })?;
+
+        detections
+            .into_iter()
+            .max_by(|a, b| a.confidence.total_cmp(&b.confidence))
+            .map(|detection| Lang::new(detection.language))
+            .ok_or_else(|| {
+                Error::TranslationInvalidResponse(
+                    "LibreTranslate could not detect the language".to_string(),
+                )
+            })
+    }
+
+    /// Map `lang` to the code the server uses for it.
+    async fn resolve(&self, lang: &Lang) -> Result<String> {
+        let languages = self.languages().await?;
+        let code = lang.as_str();
+        let alias = match code {
+            "zh-CN" => Some("zh-Hans"),
+            "zh-TW" => Some("zh-Hant"),
+            _ => None,
+        };
+        let base = code.split(['-', '_']).next().unwrap_or(code);
+
+        [Some(code), alias, Some(base)]
+            .into_iter()
+            .flatten()
+            .find_map(|candidate| {
+                languages
+                    .iter()
+                    .find(|language| language.code.eq_ignore_ascii_case(candidate))
+            })
+            .map(|language| language.code.clone())
+            .ok_or_else(|| Error::TranslationUnsupportedLanguage(code.to_string()))
+    }
+
+    /// Resolve the source (keeping `auto`) and target codes for a request.
+    async fn resolve_pair(&self, source: &Lang, target: &Lang) -> Result<(String, String)> {
+        let target_code = self.resolve(target).await?;
+        if source.as_str() == "auto" {
+            return Ok(("auto".to_string(), target_code));
+        }
+
+        let source_code = self.resolve(source).await?;
+        let languages = self.languages().await?;
+        let reachable = languages
+            .iter()
+            .find(|language| language.code == source_code)
+            .is_none_or(|language| {
+                language.targets.is_empty() || language.targets.contains(&target_code)
+            });
+        if !reachable {
+            return Err(Error::TranslationUnsupportedLanguage(format!(
+                "{source} to {target}"
+            )));
+        }
+        Ok((source_code, target_code))
+    }
+
+    /// Translate one bounded chunk of non-blank texts.
+    async fn translate_chunk(
+        &self,
+        texts: &[&str],
+        source: &str,
+        target: &str,
+    ) -> Result<Vec<String>> {
+        let request = TranslateRequest {
+            q: texts,
+            source,
+            target,
+            format: "text",
+            api_key: self.api_key.as_deref(),
+        };
+        let body = self.send("translate", Some(&request)).await?;
+        let response: TranslateResponse = serde_json::from_slice(&body).map_err(|_| {
+            Error::TranslationInvalidResponse("LibreTranslate returned malformed JSON".to_string())
+        })?;
+
+        let translations: Vec<String> = match response.translated_text {
+            serde_json::Value::Array(values) => values
+                .into_iter()
+                .map(|value| value.as_str().map(ToString::to_string))
+                .collect::<Option<_>>()
+                .unwrap_or_default(),
+            serde_json::Value::String(text) => vec![text],
+            _ => Vec::new(),
+        };
+        if translations.len() != texts.len() {
+            return Err(Error::TranslationInvalidResponse(format!(
+                "LibreTranslate returned {} translations for {} texts",
+                translations.len(),
+                texts.len()
+            )));
+        }
+        Ok(translations)
+    }
+}
+
+#[async_trait]
+impl Translator for LibreTranslateTranslator {
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "LibreTranslate",
+            requires_api_key: false,
+            supports_auto_detect: true,
+        }
+    }
+
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        TranslatorCacheIdentity::new(
+            "libretranslate",
+            http::normalized_endpoint(&self.api_base),
+            "",
+        )
+    }
+
+    async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
+        let mut translations = self
+            .translate_batch(&[text], source, target, &TranslationContext::default())
+            .await?;
+        translations.pop().ok_or_else(|| {
+            Error::TranslationInvalidResponse("LibreTranslate returned no translation".to_string())
+        })
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        _context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        // Blank blocks pass through untouched and never reach the server.
+        let pending: Vec<&str> = texts
+            .iter()
+            .copied()
+            .filter(|text| !text.trim().is_empty())
+            .collect();
+        if pending.is_empty() {
+            return Ok(texts.iter().map(ToString::to_string).collect());
+        }
+
+        let (source_code, target_code) = self.resolve_pair(source, target).await?;
+        let mut translated = Vec::with_capacity(pending.len());
+        let mut start = 0;
+        while start < pending.len() {
+            let mut end = start;
+            let mut bytes = 0;
+            while end < pending.len()
+                && end - start < MAX_BATCH_TEXTS
+                && (end == start || bytes + pending[end].len() <= MAX_BATCH_BYTES)
+            {
+                bytes += pending[end].len();
+                end += 1;
+            }
+
+            translated.extend(
+                self.translate_chunk(&pending[start..end], &source_code, &target_code)
+                    .await?,
+            );
+            start = end;
+        }
+
+        let mut translated = translated.into_iter();
+        Ok(texts
+            .iter()
+            .map(|text| {
+                if text.trim().is_empty() {
+                    (*text).to_string()
+                } else {
+                    translated.next().unwrap_or_default()
+                }
+            })
+            .collect())
+    }
+
+    async fn check_languages(&self, source: &Lang, target: &Lang) -> Result<()> {
+        self.resolve_pair(source, target).await.map(|_| ())
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn server_with_languages() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/languages"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {"code": "en", "name": "English", "targets": ["fr", "zh-Hans"]},
+                {"code": "fr", "name": "French", "targets": ["en"]},
+                {"code": "zh-Hans", "name": "Chinese", "targets": ["en"]},
+            ])))
+            .expect(1)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    fn translator(server: &MockServer) -> LibreTranslateTranslator {
+        LibreTranslateTranslator::new(
+            server.uri(),
+            Some("secret".to_string()),
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn language_pairs_are_validated_against_the_server_list() {
+        let server = server_with_languages().await;
+        let translator = translator(&server);
+
+        translator
+            .check_languages(&Lang::new("fr"), &Lang::new("en"))
+            .await
+            .unwrap();
+        translator
+            .check_languages(&Lang::new("en"), &Lang::new("zh-CN"))
+            .await
+            .unwrap();
+        assert!(matches!(
+            translator
+                .check_languages(&Lang::new("fr"), &Lang::new("zh-CN"))
+                .await,
+            Err(Error::TranslationUnsupportedLanguage(_))
+        ));
+        assert!(matches!(
+            translator
+                .check_languages(&Lang::new("auto"), &Lang::new("de"))
+                .await,
+            Err(Error::TranslationUnsupportedLanguage(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn batch_translates_non_blank_texts_in_one_request() {
+        let server = server_with_languages().await;
+        Mock::given(method("POST"))
+            .and(path("/translate"))
+            .and(body_partial_json(serde_json::json!({
+                "q": ["Bonjour", "Merci"],
+                "source": "auto",
+                "target": "en",
+                "format": "text",
+                "api_key": "secret",
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "translatedText": ["Hello", "Thanks"]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translations = translator(&server)
+            .translate_batch(
+                &["Bonjour", "", "Merci"],
+                &Lang::new("auto"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+            )
+            .await
+            .unwrap();
+
+        assert_eq!(translations, vec!["Hello", "", "Thanks"]);
+    }
+
+    #[tokio::test]
+    async fn detect_returns_the_most_confident_language() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/detect"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!([
+                {"confidence": 12.0, "language": "es"},
+                {"confidence": 90.0, "language": "fr"},
+            ])))
+            .mount(&server)
+            .await;
+
+        let detected = translator(&server).detect("Bonjour").await.unwrap();
+        assert_eq!(detected, Lang::new("fr"));
+    }
+
+    #[tokio::test]
+    async fn rejected_api_key_is_not_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/languages"))
+            .respond_with(ResponseTemplate::new(403))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator =
+            LibreTranslateTranslator::new(server.uri(), None, 3, 0, &HttpClientConfig::default())
+                .unwrap();
+        assert!(matches!(
+            translator
+                .check_languages(&Lang::new("fr"), &Lang::new("en"))
+                .await,
+            Err(Error::TranslationRequest(_))
+        ));
+    }
+}
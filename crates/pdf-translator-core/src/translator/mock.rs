@@ -0,0 +1,33 @@
+use async_trait::async_trait;
+
+use super::traits::{Translator, TranslatorInfo};
+use crate::config::{Lang, TranslatorCacheIdentity};
+use crate::error::Result;
+
+/// Offline translator that prefixes text with the target language.
+///
+/// Useful for dry runs of the PDF pipeline without a translation service.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MockTranslator;
+
+#[async_trait]
+impl Translator for MockTranslator {
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "Mock",
+            requires_api_key: false,
+            supports_auto_detect: true,
+        }
+    }
+
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        TranslatorCacheIdentity::new("mock", "local", "")
+    }
+
+    async fn translate(&self, text: &str, _source: &Lang, target: &Lang) -> Result<String> {
+        if text.trim().is_empty() {
+            return Ok(text.to_string());
+        }
+        Ok(format!("[{target}] {text}"))
+    }
+}
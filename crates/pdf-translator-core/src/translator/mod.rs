@@ -1,22 +1,133 @@
+mod anthropic;
+mod chat;
+mod command;
+mod deepl;
+mod fallback;
+mod http;
+mod libretranslate;
+mod mock;
+mod ollama;
 mod openai;
+mod protected;
+mod rate_limit;
+mod sse;
 mod traits;
 
+pub use anthropic::AnthropicTranslator;
+pub use command::CommandTranslator;
+pub use deepl::{DEEPL_FREE_API_BASE, DEEPL_PRO_API_BASE, DeepLTranslator};
+pub use fallback::{BreakerState, FallbackTranslator};
+pub use libretranslate::{LibreLanguage, LibreTranslateTranslator};
+pub use mock::MockTranslator;
+pub use ollama::OllamaTranslator;
 pub use openai::OpenAiTranslator;
-pub use traits::{Translator, TranslatorInfo};
+pub use protected::ProtectedTranslator;
+pub use rate_limit::RateLimiter;
+pub use traits::{
+    BatchTranslation, PartialTranslation, TokenUsage, TranslationContext, Translator,
+    TranslatorInfo,
+};
 
-use crate::config::TranslatorConfig;
+use crate::config::{AppConfig, BackendConfig, TranslatorConfig};
 use crate::error::Result;
+use crate::protect::Protector;
 use std::sync::Arc;
+use std::time::Duration;
 
-/// Create a translator from configuration
-pub fn create_translator(config: &TranslatorConfig) -> Result<Arc<dyn Translator>> {
-    let translator = OpenAiTranslator::new(
-        config.api_base.clone(),
-        config.api_key.clone(),
-        config.model.clone(),
-        config.retry_count,
-        config.retry_delay_ms,
-    );
-
-    Ok(Arc::new(translator))
+/// Create a translator from configuration, pacing its requests through `rate_limiter`
+pub fn create_translator(
+    config: &TranslatorConfig,
+    rate_limiter: &RateLimiter,
+) -> Result<Arc<dyn Translator>> {
+    config.backend.validate()?;
+
+    let translator: Arc<dyn Translator> = match &config.backend {
+        BackendConfig::OpenAi(openai) => Arc::new(
+            OpenAiTranslator::new(
+                openai.api_base.clone(),
+                openai.api_key.clone(),
+                openai.model.clone(),
+                config.retry_count,
+                config.retry_delay_ms,
+                &config.http,
+            )?
+            .with_prompt(openai.prompt.clone())
+            .with_azure(openai.azure.clone())
+            .with_rate_limiter(rate_limiter.clone()),
+        ),
+        BackendConfig::DeepL(deepl) => Arc::new(
+            DeepLTranslator::new(
+                deepl.clone(),
+                config.retry_count,
+                config.retry_delay_ms,
+                &config.http,
+            )?
+            .with_rate_limiter(rate_limiter.clone()),
+        ),
+        BackendConfig::LibreTranslate(libre) => Arc::new(
+            LibreTranslateTranslator::new(
+                libre.api_base.clone(),
+                libre.api_key.clone(),
+                config.retry_count,
+                config.retry_delay_ms,
+                &config.http,
+            )?
+            .with_rate_limiter(rate_limiter.clone()),
+        ),
+        BackendConfig::Anthropic(anthropic) => Arc::new(
+            AnthropicTranslator::new(
+                anthropic.clone(),
+                config.retry_count,
+                config.retry_delay_ms,
+                &config.http,
+            )?
+            .with_rate_limiter(rate_limiter.clone()),
+        ),
+        BackendConfig::Ollama(ollama) => Arc::new(
+            OllamaTranslator::new(
+                ollama.clone(),
+                config.retry_count,
+                config.retry_delay_ms,
+                &config.http,
+            )?
+            .with_rate_limiter(rate_limiter.clone()),
+        ),
+        BackendConfig::Command(command) => Arc::new(CommandTranslator::new(command.clone())),
+        BackendConfig::Mock => Arc::new(MockTranslator),
+    };
+
+    Ok(translator)
+}
+
+/// Create the configured translator, wrapped in a fallback chain when
+/// `[fallback]` lists additional backends. Every backend shares `rate_limiter`
+/// and masks the spans configured in `[protect]`.
+pub fn create_translator_chain(
+    config: &AppConfig,
+    rate_limiter: &RateLimiter,
+) -> Result<Arc<dyn Translator>> {
+    let protector = Protector::new(&config.protect)?;
+    let protect = |translator: Arc<dyn Translator>| -> Arc<dyn Translator> {
+        if protector.is_empty() {
+            translator
+        } else {
+            Arc::new(ProtectedTranslator::new(translator, protector.clone()))
+        }
+    };
+
+    let primary = protect(create_translator(&config.translator, rate_limiter)?);
+    if config.fallback.backends.is_empty() {
+        return Ok(primary);
+    }
+
+    let mut translators = vec![primary];
+    for backend in &config.fallback.backends {
+        translators.push(protect(create_translator(backend, rate_limiter)?));
+    }
+
+    Ok(Arc::new(FallbackTranslator::new(
+        translators,
+        config.fallback.failure_threshold,
+        Duration::from_secs(config.fallback.cooldown_seconds),
+    )))
 }
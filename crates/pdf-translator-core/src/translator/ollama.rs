@@ -0,0 +1,331 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+use super::chat::{self, ChatBackend, ChatPrompt};
+use super::http::{self, Pacing};
+use super::rate_limit::RateLimiter;
+use super::traits::{
+    BatchTranslation, PartialTranslation, TokenUsage, TranslationContext, Translator,
+    TranslatorInfo,
+};
+use crate::config::{HttpClientConfig, Lang, OllamaConfig, PromptConfig, TranslatorCacheIdentity};
+use crate::error::{Error, Result};
+
+/// Translator for Ollama's native `/api/chat` endpoint.
+///
+/// Unlike Ollama's OpenAI-compatible endpoint, this one accepts `keep_alive`
+/// and model options such as the context window size.
+pub struct OllamaTranslator {
+    client: Client,
+    /// Base URL of the Ollama server (e.g., "http://localhost:11434")
+    pub api_base: String,
+    /// Model name
+    pub model: String,
+    /// How long the model stays loaded after a request
+    pub keep_alive: Option<String>,
+    /// Context window size in tokens
+    pub num_ctx: Option<u32>,
+    /// Number of retry attempts
+    pub retry_count: u32,
+    /// Initial delay between retries in milliseconds, doubled per attempt
+    pub retry_delay_ms: u64,
+    /// Prompt templates and sampling parameters
+    pub prompt: PromptConfig,
+    rate_limiter: RateLimiter,
+}
+
+#[derive(Debug, Serialize)]
+struct ChatRequest {
+    model: String,
+    messages: Vec<Message>,
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    keep_alive: Option<String>,
+    options: ModelOptions,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct Message {
+    role: &'static str,
+    content: String,
+}
+
+#[derive(Debug, Serialize)]
+struct ModelOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_predict: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_ctx: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ChatResponse {
+    message: ResponseMessage,
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: u64,
+    #[serde(default)]
+    eval_count: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct ResponseMessage {
+    content: String,
+}
+
+impl OllamaTranslator {
+    /// Create a new Ollama translator.
+    ///
+    /// Fails if the HTTP client options are invalid.
+    pub fn new(
+        options: OllamaConfig,
+        retry_count: u32,
+        retry_delay_ms: u64,
+        http_config: &HttpClientConfig,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: http::client(http_config)?,
+            api_base: options.api_base,
+            model: options.model,
+            keep_alive: options.keep_alive,
+            num_ctx: options.num_ctx,
+            retry_count,
+            retry_delay_ms,
+            prompt: options.prompt,
+            rate_limiter: RateLimiter::unlimited(),
+        })
+    }
+
+    /// Pace requests through a (possibly shared) rate limiter.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    fn chat_request(&self, prompt: ChatPrompt) -> ChatRequest {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = prompt.system {
+            messages.push(Message {
+                role: "system",
+                content: system,
+            });
+        }
+        messages.push(Message {
+            role: "user",
+            content: prompt.user,
+        });
+
+        ChatRequest {
+            model: self.model.clone(),
+            messages,
+            stream: false,
+            keep_alive: self.keep_alive.clone(),
+            options: ModelOptions {
+                temperature: self.prompt.temperature,
+                top_p: self.prompt.top_p,
+                num_predict: self.prompt.max_tokens,
+                num_ctx: self.num_ctx,
+            },
+            extra: self.prompt.extra_body.clone(),
+        }
+    }
+
+    /// Extract the reply content, adding the reported usage to `usage` even
+    /// when the content itself is rejected.
+    fn parse_reply(body: &[u8], usage: &mut TokenUsage) -> Result<String> {
+        let response: ChatResponse = serde_json::from_slice(body).map_err(|_| {
+            Error::TranslationInvalidResponse("Ollama returned malformed JSON".to_string())
+        })?;
+        *usage += TokenUsage {
+            prompt_tokens: response.prompt_eval_count,
+            completion_tokens: response.eval_count,
+            requests: 0,
+        };
+        if response.done_reason.as_deref() == Some("length") {
+            return Err(Error::TranslationInvalidResponse(
+                "Ollama response was truncated".to_string(),
+            ));
+        }
+
+        let content = response.message.content.trim();
+        if content.is_empty() {
+            return Err(Error::TranslationInvalidResponse(
+                "Ollama response was blank".to_string(),
+            ));
+        }
+        Ok(content.to_string())
+    }
+}
+
+#[async_trait]
+impl ChatBackend for OllamaTranslator {
+    fn prompt_config(&self) -> &PromptConfig {
+        &self.prompt
+    }
+
+    async fn complete(&self, prompt: ChatPrompt, usage: &mut TokenUsage) -> Result<String> {
+        let url = format!("{}/api/chat", self.api_base.trim_end_matches('/'));
+        let pacing = Pacing {
+            retry_count: self.retry_count,
+            retry_delay_ms: self.retry_delay_ms,
+            rate_limiter: &self.rate_limiter,
+            estimated_tokens: prompt.estimated_tokens(self.prompt.max_tokens),
+        };
+        let request = self.chat_request(prompt);
+        chat::complete_with_retry(
+            "Ollama",
+            pacing,
+            || self.client.post(&url).json(&request),
+            Self::parse_reply,
+            usage,
+        )
+        .await
+    }
+}
+
+#[async_trait]
+impl Translator for OllamaTranslator {
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "Ollama",
+            requires_api_key: false,
+            supports_auto_detect: true,
+        }
+    }
+
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        let mut identity = TranslatorCacheIdentity::new(
+            "ollama",
+            http::normalized_endpoint(&self.api_base),
+            &self.model,
+        );
+        // A smaller context window can truncate long prompts and change output.
+        if let Some(num_ctx) = self.num_ctx {
+            identity = identity.with_option("num_ctx", num_ctx.to_string());
+        }
+        self.prompt.apply_to_identity(identity)
+    }
+
+    async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
+        chat::translate_text(self, text, source, target).await
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        Ok(self
+            .translate_batch_traced(texts, source, target, context)
+            .await?
+            .translations)
+    }
+
+    async fn translate_batch_traced(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<BatchTranslation> {
+        let mut usage = TokenUsage::default();
+        let translations = chat::translate_blocks(self, texts, source, target, context, &mut usage)
+            .await
+            .map_err(|error| error.with_usage(usage))?;
+        Ok(BatchTranslation {
+            translations,
+            identity: self.cache_identity(),
+            usage,
+            flagged: Vec::new(),
+        })
+    }
+
+    async fn revise(
+        &self,
+        text: &str,
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+        translated: PartialTranslation,
+        usage: &mut TokenUsage,
+    ) -> Result<String> {
+        chat::revise(self, text, source, target, context, translated.text, usage).await
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn sends_keep_alive_and_model_options() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "llama3",
+                "stream": false,
+                "keep_alive": "10m",
+                "options": {"num_ctx": 8192, "num_predict": 512},
+                "messages": [{"role": "user"}]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "message": {"role": "assistant", "content": "Hello"},
+                "done": true,
+                "done_reason": "stop",
+                "prompt_eval_count": 30,
+                "eval_count": 4
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OllamaTranslator::new(
+            OllamaConfig {
+                api_base: format!("{}/", server.uri()),
+                model: "llama3".to_string(),
+                keep_alive: Some("10m".to_string()),
+                num_ctx: Some(8192),
+                prompt: PromptConfig {
+                    max_tokens: Some(512),
+                    ..PromptConfig::default()
+                },
+            },
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap();
+
+        let batch = translator
+            .translate_batch_traced(
+                &["Bonjour"],
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(batch.translations, vec!["Hello"]);
+        assert_eq!(batch.usage.total_tokens(), 34);
+        assert!(
+            batch
+                .identity
+                .options()
+                .any(|option| option == ("num_ctx", "8192"))
+        );
+    }
+}
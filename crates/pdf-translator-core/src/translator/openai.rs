@@ -1,11 +1,20 @@
 use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
-use std::time::Duration;
-use tracing::{debug, error, warn};
+use std::collections::VecDeque;
 
-use super::traits::{Translator, TranslatorInfo};
-use crate::config::{Lang, TranslatorCacheIdentity};
+use super::chat::{self, ChatBackend, ChatPrompt};
+use super::http::{self, MAX_SUCCESS_BODY_BYTES, Pacing};
+use super::rate_limit::RateLimiter;
+use super::sse::SseDecoder;
+use super::traits::{
+    BatchTranslation, PartialTranslation, TokenUsage, TranslationContext, Translator,
+    TranslatorInfo,
+};
+use crate::config::{
+    AzureOpenAiConfig, HttpClientConfig, Lang, PromptConfig, TranslatorCacheIdentity,
+};
 use crate::error::{Error, Result};
 
 /// Default number of retry attempts
@@ -13,11 +22,6 @@ pub const DEFAULT_RETRY_COUNT: u32 = 3;
 /// Default delay between retries in milliseconds
 pub const DEFAULT_RETRY_DELAY_MS: u64 = 1000;
 
-const MAX_SUCCESS_BODY_BYTES: usize = 1024 * 1024;
-const MAX_ERROR_BODY_BYTES: usize = 64 * 1024;
-const MAX_RETRY_AFTER_SECONDS: u64 = 300;
-const DEFAULT_RATE_LIMIT_DELAY_SECONDS: u64 = 5;
-
 /// OpenAI-compatible API translator
 /// Works with: llama.cpp server, Ollama, DeepSeek, OpenAI, etc.
 pub struct OpenAiTranslator {
@@ -30,8 +34,13 @@ pub struct OpenAiTranslator {
     pub model: String,
     /// Number of retry attempts
     pub retry_count: u32,
-    /// Delay between retries in milliseconds
+    /// Initial delay between retries in milliseconds, doubled per attempt
     pub retry_delay_ms: u64,
+    /// Prompt templates and sampling parameters
+    pub prompt: PromptConfig,
+    /// Azure OpenAI deployment, if `api_base` is an Azure resource
+    pub azure: Option<AzureOpenAiConfig>,
+    rate_limiter: RateLimiter,
 }
 
 #[derive(Debug, Serialize)]
@@ -42,6 +51,20 @@ struct ChatRequest {
     temperature: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     max_tokens: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<StreamOptions>,
+    #[serde(flatten)]
+    extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Asks for a final chunk carrying token usage when streaming.
+#[derive(Debug, Serialize)]
+struct StreamOptions {
+    include_usage: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -53,6 +76,8 @@ struct Message {
 #[derive(Debug, Deserialize)]
 struct ChatResponse {
     choices: Vec<Choice>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -66,239 +91,367 @@ struct ResponseMessage {
     content: String,
 }
 
+/// One `chat.completion.chunk` event of a streamed response.
+#[derive(Debug, Deserialize)]
+struct StreamChunk {
+    #[serde(default)]
+    choices: Vec<StreamChoice>,
+    #[serde(default)]
+    usage: Option<TokenUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StreamChoice {
+    #[serde(default)]
+    delta: StreamDelta,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct StreamDelta {
+    content: Option<String>,
+}
+
 impl OpenAiTranslator {
     /// Create a new OpenAI translator with all options.
     ///
-    /// # Panics
-    /// Panics if the HTTP client cannot be created, which should only happen
-    /// in extreme circumstances (e.g., TLS backend unavailable on the system).
-    #[allow(clippy::expect_used)]
+    /// Fails if the HTTP client options are invalid.
     pub fn new(
         api_base: String,
         api_key: Option<String>,
         model: String,
         retry_count: u32,
         retry_delay_ms: u64,
-    ) -> Self {
-        let client = Client::builder()
-            .timeout(Duration::from_secs(60))
-            .build()
-            .expect("Failed to create HTTP client");
-
-        Self {
-            client,
+        http_config: &HttpClientConfig,
+    ) -> Result<Self> {
+        Ok(Self {
+            client: http::client(http_config)?,
             api_base,
             api_key,
             model,
             retry_count,
             retry_delay_ms,
-        }
+            prompt: PromptConfig::default(),
+            azure: None,
+            rate_limiter: RateLimiter::unlimited(),
+        })
     }
 
-    /// Create a new OpenAI translator with default retry settings.
-    ///
-    /// # Panics
-    /// Panics if the HTTP client cannot be created.
-    pub fn with_defaults(api_base: String, api_key: Option<String>, model: String) -> Self {
+    /// Use custom prompt templates and sampling parameters.
+    #[must_use]
+    pub fn with_prompt(mut self, prompt: PromptConfig) -> Self {
+        self.prompt = prompt;
+        self
+    }
+
+    /// Address an Azure OpenAI deployment instead of a `/chat/completions` root.
+    #[must_use]
+    pub fn with_azure(mut self, azure: Option<AzureOpenAiConfig>) -> Self {
+        self.azure = azure;
+        self
+    }
+
+    /// Pace requests through a (possibly shared) rate limiter.
+    #[must_use]
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+
+    /// Create a new OpenAI translator with default retry and HTTP settings.
+    pub fn with_defaults(api_base: String, api_key: Option<String>, model: String) -> Result<Self> {
         Self::new(
             api_base,
             api_key,
             model,
             DEFAULT_RETRY_COUNT,
             DEFAULT_RETRY_DELAY_MS,
+            &HttpClientConfig::default(),
         )
     }
 
-    fn normalized_endpoint(&self) -> String {
-        let trimmed = self.api_base.trim();
-        if let Ok(mut url) = reqwest::Url::parse(trimmed) {
-            let path = url.path().trim_end_matches('/').to_string();
-            url.set_path(if path.is_empty() { "/" } else { &path });
-            let _ = url.set_username("");
-            let _ = url.set_password(None);
-            url.set_query(None);
-            url.set_fragment(None);
-            return url.as_str().trim_end_matches('/').to_string();
-        }
-
-        trimmed.trim_end_matches('/').to_string()
+    /// Chat completions URL: `{api_base}/chat/completions`, or the deployment
+    /// path with its `api-version` query on Azure.
+    fn completions_url(&self) -> String {
+        let api_base = self.api_base.trim_end_matches('/');
+        self.azure.as_ref().map_or_else(
+            || format!("{api_base}/chat/completions"),
+            |azure| {
+                format!(
+                    "{api_base}/openai/deployments/{}/chat/completions?api-version={}",
+                    urlencoding::encode(azure.deployment.trim()),
+                    urlencoding::encode(azure.api_version.trim()),
+                )
+            },
+        )
     }
 
-    async fn read_body_limited(mut response: reqwest::Response, limit: usize) -> Result<Vec<u8>> {
-        let limit_u64 = u64::try_from(limit).map_err(|_| {
-            Error::TranslationInvalidResponse("response size limit is invalid".to_string())
-        })?;
-        if response
-            .content_length()
-            .is_some_and(|length| length > limit_u64)
-        {
-            return Err(Error::TranslationInvalidResponse(
-                "translation API response exceeded the size limit".to_string(),
-            ));
-        }
-
-        let mut body = Vec::new();
-        while let Some(chunk) = response.chunk().await.map_err(|_| {
-            Error::TranslationRequest("failed to read translation API response".to_string())
-        })? {
-            let new_len = body.len().checked_add(chunk.len()).ok_or_else(|| {
-                Error::TranslationInvalidResponse(
-                    "translation API response exceeded the size limit".to_string(),
-                )
-            })?;
-            if new_len > limit {
-                return Err(Error::TranslationInvalidResponse(
-                    "translation API response exceeded the size limit".to_string(),
-                ));
-            }
-            body.extend_from_slice(&chunk);
+    /// POST `request` with the credentials the endpoint expects: Azure takes
+    /// an `api-key` header, everything else a bearer token.
+    fn post(&self, url: &str, request: &ChatRequest) -> reqwest::RequestBuilder {
+        let req = self.client.post(url).json(request);
+        match (&self.api_key, &self.azure) {
+            (Some(key), Some(_)) => req.header("api-key", key),
+            (Some(key), None) => req.header("Authorization", format!("Bearer {key}")),
+            (None, _) => req,
         }
-        Ok(body)
     }
 
-    fn parse_completion(body: &[u8]) -> Result<String> {
+    /// Extract the completion content, adding the reported usage to `usage`
+    /// even when the content itself is rejected.
+    fn parse_completion(body: &[u8], usage: &mut TokenUsage) -> Result<String> {
         let response: ChatResponse = serde_json::from_slice(body).map_err(|_| {
             Error::TranslationInvalidResponse("translation API returned malformed JSON".to_string())
         })?;
+        if let Some(reported) = response.usage {
+            *usage += reported;
+        }
         let choice = response.choices.first().ok_or_else(|| {
             Error::TranslationInvalidResponse(
                 "translation API response contained no choices".to_string(),
             )
         })?;
-        match choice.finish_reason.as_deref() {
-            Some("stop") => {}
-            Some("length") => {
-                return Err(Error::TranslationInvalidResponse(
-                    "translation API response was truncated".to_string(),
-                ));
-            }
-            Some("content_filter") => {
-                return Err(Error::TranslationInvalidResponse(
-                    "translation API response was content-filtered".to_string(),
-                ));
-            }
-            _ => {
-                return Err(Error::TranslationInvalidResponse(
-                    "translation API response was incomplete".to_string(),
-                ));
-            }
-        }
+        Self::check_finish_reason(choice.finish_reason.as_deref())?;
 
-        let translated = choice
-            .message
-            .content
-            .trim()
-            .trim_start_matches('"')
-            .trim_end_matches('"')
-            .trim();
-        if translated.is_empty() {
+        let content = choice.message.content.trim();
+        if content.is_empty() {
             return Err(Error::TranslationInvalidResponse(
                 "translation API response was blank".to_string(),
             ));
         }
-        Ok(translated.to_string())
+        Ok(content.to_string())
     }
 
-    /// Create translation prompt
-    fn create_prompt(text: &str, source: &Lang, target: &Lang) -> String {
-        let source_hint = if source.as_str() == "auto" {
-            String::new()
-        } else {
-            format!(" from {}", language_name(source))
+    /// Reject completions that did not end normally.
+    fn check_finish_reason(finish_reason: Option<&str>) -> Result<()> {
+        match finish_reason {
+            Some("stop") => Ok(()),
+            Some("length") => Err(Error::TranslationInvalidResponse(
+                "translation API response was truncated".to_string(),
+            )),
+            Some("content_filter") => Err(Error::TranslationInvalidResponse(
+                "translation API response was content-filtered".to_string(),
+            )),
+            _ => Err(Error::TranslationInvalidResponse(
+                "translation API response was incomplete".to_string(),
+            )),
+        }
+    }
+
+    /// Chat messages for `prompt`, system message first.
+    fn messages(prompt: ChatPrompt) -> Vec<Message> {
+        let mut messages = Vec::with_capacity(2);
+        if let Some(system) = prompt.system {
+            messages.push(Message {
+                role: "system".to_string(),
+                content: system,
+            });
+        }
+        messages.push(Message {
+            role: "user".to_string(),
+            content: prompt.user,
+        });
+        messages
+    }
+
+    fn chat_request(&self, messages: Vec<Message>, stream: bool) -> ChatRequest {
+        ChatRequest {
+            model: self.model.clone(),
+            messages,
+            temperature: self.prompt.temperature,
+            max_tokens: self.prompt.max_tokens,
+            top_p: self.prompt.top_p,
+            stream,
+            stream_options: stream.then_some(StreamOptions {
+                include_usage: true,
+            }),
+            extra: self.prompt.extra_body.clone(),
+        }
+    }
+
+    /// Start a streamed completion, retrying until the response headers arrive.
+    ///
+    /// Every attempt waits for the rate limiter; the stream settles the last
+    /// reservation with the usage reported at its end.
+    async fn open_stream(&self, prompt: ChatPrompt) -> Result<CompletionStream> {
+        let url = self.completions_url();
+        let estimated_tokens = prompt.estimated_tokens(self.prompt.max_tokens);
+        let request = self.chat_request(Self::messages(prompt), true);
+        let pacing = Pacing {
+            retry_count: self.retry_count,
+            retry_delay_ms: self.retry_delay_ms,
+            rate_limiter: &self.rate_limiter,
+            estimated_tokens,
         };
-        format!(
-            "Translate the following text{} into {}. Output only the translation, no explanations.\n\nText: \"{}\"",
-            source_hint,
-            language_name(target),
-            text
+        let response = http::send_for_response(
+            "Translation API",
+            pacing,
+            || self.post(&url, &request),
+            |_| None,
         )
+        .await?;
+
+        Ok(CompletionStream {
+            response,
+            decoder: SseDecoder::new(),
+            pending: VecDeque::new(),
+            content: String::new(),
+            usage: TokenUsage {
+                requests: 1,
+                ..TokenUsage::default()
+            },
+            received: 0,
+            finish_reason: None,
+            eof: false,
+            done: false,
+            rate_limiter: self.rate_limiter.clone(),
+            estimated_tokens,
+        })
     }
+}
 
-    /// Make API request with retry logic
-    async fn request_with_retry(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
-        let url = format!("{}/chat/completions", self.api_base.trim_end_matches('/'));
-        let prompt = Self::create_prompt(text, source, target);
-        let request = ChatRequest {
-            model: self.model.clone(),
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: prompt,
-            }],
-            temperature: Some(0.3),
-            max_tokens: None,
+#[async_trait]
+impl ChatBackend for OpenAiTranslator {
+    fn prompt_config(&self) -> &PromptConfig {
+        &self.prompt
+    }
+
+    async fn complete(&self, prompt: ChatPrompt, usage: &mut TokenUsage) -> Result<String> {
+        let url = self.completions_url();
+        let pacing = Pacing {
+            retry_count: self.retry_count,
+            retry_delay_ms: self.retry_delay_ms,
+            rate_limiter: &self.rate_limiter,
+            estimated_tokens: prompt.estimated_tokens(self.prompt.max_tokens),
         };
-        let attempts = self.retry_count.max(1);
-        let mut last_error = None;
-
-        for attempt in 0..attempts {
-            debug!(
-                "Translation request attempt {}/{} to {}",
-                attempt + 1,
-                attempts,
-                url
-            );
-
-            let mut req = self.client.post(&url).json(&request);
-            if let Some(key) = &self.api_key {
-                req = req.header("Authorization", format!("Bearer {key}"));
+        let request = self.chat_request(Self::messages(prompt), false);
+        chat::complete_with_retry(
+            "Translation API",
+            pacing,
+            || self.post(&url, &request),
+            Self::parse_completion,
+            usage,
+        )
+        .await
+    }
+}
+
+/// Reads a streamed chat completion, accumulating content deltas.
+struct CompletionStream {
+    response: reqwest::Response,
+    decoder: SseDecoder,
+    pending: VecDeque<String>,
+    content: String,
+    usage: TokenUsage,
+    received: usize,
+    finish_reason: Option<String>,
+    eof: bool,
+    done: bool,
+    rate_limiter: RateLimiter,
+    estimated_tokens: u64,
+}
+
+impl CompletionStream {
+    /// Advance to the next partial translation, or the final one once the stream ends.
+    ///
+    /// A failure mid-stream carries the usage billed for the request.
+    async fn next_partial(mut self) -> Result<Option<(PartialTranslation, Self)>> {
+        match self.advance().await {
+            Ok(partial) => Ok(partial.map(|partial| (partial, self))),
+            Err(error) => Err(error.with_usage(self.billed())),
+        }
+    }
+
+    /// Usage billed for the request: as reported so far, or else the estimate
+    /// it was paced with, counted as prompt tokens.
+    const fn billed(&self) -> TokenUsage {
+        if self.usage.total_tokens() > 0 {
+            return self.usage;
+        }
+        TokenUsage {
+            prompt_tokens: self.estimated_tokens,
+            ..self.usage
+        }
+    }
+
+    async fn advance(&mut self) -> Result<Option<PartialTranslation>> {
+        loop {
+            if self.done {
+                return Ok(None);
             }
 
-            match req.send().await {
-                Ok(response) => {
-                    let status = response.status();
-                    if status.is_success() {
-                        match Self::read_body_limited(response, MAX_SUCCESS_BODY_BYTES).await {
-                            Ok(body) => match Self::parse_completion(&body) {
-                                Ok(translated) => return Ok(translated),
-                                Err(error) => last_error = Some(error),
-                            },
-                            Err(error) => last_error = Some(error),
-                        }
-                    } else if status.as_u16() == 429 {
-                        let retry_after = response
-                            .headers()
-                            .get("retry-after")
-                            .and_then(|value| value.to_str().ok())
-                            .and_then(|value| value.parse::<u64>().ok())
-                            .map(|seconds| seconds.min(MAX_RETRY_AFTER_SECONDS));
-                        let _ = Self::read_body_limited(response, MAX_ERROR_BODY_BYTES).await;
-                        warn!("Translation API rate limited the request");
-                        last_error = Some(Error::TranslationRateLimited { retry_after });
-
-                        if attempt + 1 < attempts {
-                            let delay = retry_after
-                                .unwrap_or(DEFAULT_RATE_LIMIT_DELAY_SECONDS)
-                                .min(MAX_RETRY_AFTER_SECONDS);
-                            tokio::time::sleep(Duration::from_secs(delay)).await;
-                        }
-                        continue;
-                    } else {
-                        let _ = Self::read_body_limited(response, MAX_ERROR_BODY_BYTES).await;
-                        warn!("Translation API returned HTTP {}", status);
-                        last_error = Some(Error::TranslationRequest(format!(
-                            "upstream returned HTTP {status}"
-                        )));
-                    }
+            while let Some(data) = self.pending.pop_front() {
+                if data.trim() == "[DONE]" {
+                    return self.finish();
+                }
+                let chunk: StreamChunk = serde_json::from_str(&data).map_err(|_| {
+                    Error::TranslationInvalidResponse(
+                        "translation API streamed malformed JSON".to_string(),
+                    )
+                })?;
+                if let Some(reported) = chunk.usage {
+                    self.usage = TokenUsage {
+                        requests: self.usage.requests,
+                        ..reported
+                    };
+                }
+                let Some(choice) = chunk.choices.into_iter().next() else {
+                    continue;
+                };
+                if choice.finish_reason.is_some() {
+                    self.finish_reason = choice.finish_reason;
                 }
-                Err(error) => {
-                    warn!("Translation request failed: {}", error);
-                    last_error = Some(if error.is_timeout() {
-                        Error::TranslationTimeout
-                    } else {
-                        Error::TranslationRequest(
-                            "translation API request could not be completed".to_string(),
-                        )
-                    });
+                if let Some(delta) = choice.delta.content.filter(|delta| !delta.is_empty()) {
+                    self.content.push_str(&delta);
+                    let partial = PartialTranslation {
+                        text: self
+                            .content
+                            .trim_start()
+                            .trim_start_matches('"')
+                            .to_string(),
+                        usage: self.usage,
+                        flagged: false,
+                        identity: None,
+                    };
+                    return Ok(Some(partial));
                 }
             }
 
-            if attempt + 1 < attempts {
-                tokio::time::sleep(Duration::from_millis(self.retry_delay_ms)).await;
+            if self.eof {
+                return self.finish();
             }
+            let chunk = self.response.chunk().await.map_err(|_| {
+                Error::TranslationRequest("failed to read translation API response".to_string())
+            })?;
+            let Some(bytes) = chunk else {
+                self.eof = true;
+                self.pending.extend(self.decoder.finish());
+                continue;
+            };
+            self.received = self.received.saturating_add(bytes.len());
+            if self.received > MAX_SUCCESS_BODY_BYTES {
+                return Err(Error::TranslationInvalidResponse(
+                    "translation API response exceeded the size limit".to_string(),
+                ));
+            }
+            self.pending.extend(self.decoder.push(&bytes));
         }
+    }
 
-        error!("Translation failed after {} attempts", attempts);
-        Err(last_error.unwrap_or(Error::TranslationMaxRetriesExceeded))
+    fn finish(&mut self) -> Result<Option<PartialTranslation>> {
+        self.done = true;
+        if self.usage.total_tokens() > 0 {
+            self.rate_limiter
+                .settle(self.estimated_tokens, self.usage.total_tokens());
+        }
+        OpenAiTranslator::check_finish_reason(self.finish_reason.as_deref())?;
+        let translation = PartialTranslation {
+            text: chat::clean_translation(&self.content)?,
+            usage: self.usage,
+            flagged: false,
+            identity: None,
+        };
+        Ok(Some(translation))
     }
 }
 
@@ -313,25 +466,86 @@ impl Translator for OpenAiTranslator {
     }
 
     fn cache_identity(&self) -> TranslatorCacheIdentity {
-        TranslatorCacheIdentity::new(
-            "openai-compatible",
-            self.normalized_endpoint(),
-            self.model.clone(),
-        )
+        let endpoint = http::normalized_endpoint(&self.api_base);
+        let identity = match &self.azure {
+            Some(azure) => TranslatorCacheIdentity::new("azure-openai", endpoint, &self.model)
+                .with_option("azure.deployment", azure.deployment.trim())
+                .with_option("azure.api_version", azure.api_version.trim()),
+            None => TranslatorCacheIdentity::new("openai-compatible", endpoint, &self.model),
+        };
+        self.prompt.apply_to_identity(identity)
     }
 
     async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
-        // Skip empty text
-        if text.trim().is_empty() {
-            return Ok(text.to_string());
-        }
+        chat::translate_text(self, text, source, target).await
+    }
 
-        // Skip if source and target are the same
-        if source.as_str() == target.as_str() && source.as_str() != "auto" {
-            return Ok(text.to_string());
+    fn translate_stream<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a Lang,
+        target: &'a Lang,
+        context: &'a TranslationContext,
+    ) -> BoxStream<'a, Result<PartialTranslation>> {
+        if text.trim().is_empty() || chat::is_same_language(source, target) {
+            return stream::once(async move { Ok(text.to_string().into()) }).boxed();
         }
 
-        self.request_with_retry(text, source, target).await
+        let glossary = context.glossary.prompt_instructions(&[text]);
+        let prompt = ChatPrompt::single(&self.prompt, text, source, target, &glossary);
+        stream::once(self.open_stream(prompt))
+            .map(|opened| match opened {
+                Ok(completion) => {
+                    stream::try_unfold(completion, CompletionStream::next_partial).boxed()
+                }
+                Err(error) => stream::once(async move { Err(error) }).boxed(),
+            })
+            .flatten()
+            .boxed()
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        Ok(self
+            .translate_batch_traced(texts, source, target, context)
+            .await?
+            .translations)
+    }
+
+    async fn translate_batch_traced(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<BatchTranslation> {
+        let mut usage = TokenUsage::default();
+        let translations = chat::translate_blocks(self, texts, source, target, context, &mut usage)
+            .await
+            .map_err(|error| error.with_usage(usage))?;
+        Ok(BatchTranslation {
+            translations,
+            identity: self.cache_identity(),
+            usage,
+            flagged: Vec::new(),
+        })
+    }
+
+    async fn revise(
+        &self,
+        text: &str,
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+        translated: PartialTranslation,
+        usage: &mut TokenUsage,
+    ) -> Result<String> {
+        chat::revise(self, text, source, target, context, translated.text, usage).await
     }
 
     fn is_available(&self) -> bool {
@@ -340,36 +554,471 @@ impl Translator for OpenAiTranslator {
     }
 }
 
-/// Convert a language code to a human-readable prompt value when known.
-fn language_name(lang: &Lang) -> &str {
-    match lang.as_str() {
-        "en" => "English",
-        "zh-CN" => "Simplified Chinese",
-        "zh-TW" => "Traditional Chinese",
-        "ja" => "Japanese",
-        "ko" => "Korean",
-        "es" => "Spanish",
-        "fr" => "French",
-        "de" => "German",
-        "it" => "Italian",
-        "pt" => "Portuguese",
-        "ru" => "Russian",
-        "ar" => "Arabic",
-        "hi" => "Hindi",
-        "th" => "Thai",
-        "vi" => "Vietnamese",
-        _ => lang.as_str(),
-    }
-}
-
 #[cfg(test)]
+#[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use crate::config::QualityConfig;
+    use crate::quality::QualityValidator;
+    use futures::TryStreamExt;
+    use std::fmt::Write;
+    use wiremock::matchers::{
+        body_partial_json, body_string_contains, header, method, path, query_param,
+    };
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn sse_body(deltas: &[&str], finish_reason: &str) -> String {
+        let mut body = String::new();
+        for delta in deltas {
+            let chunk = serde_json::json!({"choices": [{"delta": {"content": delta}}]});
+            let _ = write!(body, "data: {chunk}\n\n");
+        }
+        let last = serde_json::json!({"choices": [{"delta": {}, "finish_reason": finish_reason}]});
+        let _ = write!(body, "data: {last}\n\ndata: [DONE]\n\n");
+        body
+    }
+
+    async fn stream_from(body: String) -> Result<Vec<PartialTranslation>> {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({"stream": true})))
+            .respond_with(ResponseTemplate::new(200).set_body_raw(body, "text/event-stream"))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAiTranslator::new(
+            server.uri(),
+            None,
+            "test".to_string(),
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap();
+        let context = TranslationContext::default();
+        translator
+            .translate_stream("Bonjour", &Lang::new("fr"), &Lang::new("en"), &context)
+            .try_collect()
+            .await
+    }
+
+    #[tokio::test]
+    async fn stream_yields_growing_partials_then_cleaned_translation() {
+        let partials: Vec<String> = stream_from(sse_body(&["\"Hel", "lo", "\""], "stop"))
+            .await
+            .unwrap()
+            .into_iter()
+            .map(|partial| partial.text)
+            .collect();
+        assert_eq!(partials, vec!["Hel", "Hello", "Hello\"", "Hello"]);
+    }
+
+    #[tokio::test]
+    async fn stream_reports_usage_with_the_final_translation() {
+        let mut body = sse_body(&["Hello"], "stop");
+        let usage = serde_json::json!({
+            "choices": [],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15}
+        });
+        body = body.replace("data: [DONE]", &format!("data: {usage}\n\ndata: [DONE]"));
+
+        let partials = stream_from(body).await.unwrap();
+        let last = partials.last().unwrap();
+        assert_eq!(last.text, "Hello");
+        assert_eq!(last.usage.total_tokens(), 15);
+    }
+
+    #[tokio::test]
+    async fn failed_streams_carry_the_usage_billed_for_them() {
+        let mut body = sse_body(&["Hello"], "length");
+        let usage = serde_json::json!({
+            "choices": [],
+            "usage": {"prompt_tokens": 12, "completion_tokens": 3, "total_tokens": 15}
+        });
+        body = body.replace("data: [DONE]", &format!("data: {usage}\n\ndata: [DONE]"));
+
+        let error = stream_from(body).await.unwrap_err();
+        assert!(matches!(
+            error.cause(),
+            Error::TranslationInvalidResponse(_)
+        ));
+        assert_eq!(
+            error.billed_usage(),
+            TokenUsage {
+                prompt_tokens: 12,
+                completion_tokens: 3,
+                requests: 1,
+            }
+        );
+
+        let error = stream_from(sse_body(&["Hello"], "length"))
+            .await
+            .unwrap_err();
+        assert_eq!(error.billed_usage().requests, 1);
+        assert!(error.billed_usage().total_tokens() > 0);
+    }
+
+    #[tokio::test]
+    async fn batch_usage_sums_every_billed_response() {
+        let server = MockServer::start().await;
+        let usage = serde_json::json!({"prompt_tokens": 40, "completion_tokens": 10});
+        // The batch response omits a segment, so that block is re-asked on its own.
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("Segments:"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"content": "{\"1\": \"One\"}"}, "finish_reason": "stop"}],
+                "usage": usage
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"content": "Two"}, "finish_reason": "stop"}],
+                "usage": {"prompt_tokens": 5, "completion_tokens": 1}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAiTranslator::new(
+            server.uri(),
+            None,
+            "test".to_string(),
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap();
+        let batch = translator
+            .translate_batch_traced(
+                &["Un", "Deux"],
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(batch.translations, vec!["One", "Two"]);
+        assert_eq!(
+            batch.usage,
+            TokenUsage {
+                prompt_tokens: 45,
+                completion_tokens: 11,
+                requests: 2,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_batches_carry_the_usage_billed_before_the_failure() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("Premier"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"content": "First"}, "finish_reason": "stop"}],
+                "usage": {"prompt_tokens": 20, "completion_tokens": 4}
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(400))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAiTranslator::new(
+            server.uri(),
+            None,
+            "test".to_string(),
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap();
+        // The blank block splits the batch into two requests.
+        let error = translator
+            .translate_batch_traced(
+                &["Premier", "", "Second"],
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+            )
+            .await
+            .unwrap_err();
+        assert!(matches!(error.cause(), Error::TranslationRequest(_)));
+        assert_eq!(
+            error.billed_usage(),
+            TokenUsage {
+                prompt_tokens: 20,
+                completion_tokens: 4,
+                requests: 2,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn failed_quality_checks_are_re_asked_with_feedback() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("returned untranslated"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "message": {"content": "The contract is signed by both parties."},
+                    "finish_reason": "stop"
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "message": {"content": "Le contrat est signé par les deux parties."},
+                    "finish_reason": "stop"
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAiTranslator::new(
+            server.uri(),
+            None,
+            "test".to_string(),
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap();
+        let context = TranslationContext::default()
+            .with_quality(QualityValidator::new(&QualityConfig::default()));
+        let batch = translator
+            .translate_batch_traced(
+                &["Le contrat est signé par les deux parties."],
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &context,
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            batch.translations,
+            vec!["The contract is signed by both parties."]
+        );
+        assert_eq!(batch.usage.requests, 2);
+    }
+
+    #[tokio::test]
+    async fn streamed_translations_are_revised_with_a_plain_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_string_contains("returned untranslated"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "message": {"content": "The contract is signed by both parties."},
+                    "finish_reason": "stop"
+                }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAiTranslator::new(
+            server.uri(),
+            None,
+            "test".to_string(),
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap();
+        let context = TranslationContext::default()
+            .with_quality(QualityValidator::new(&QualityConfig::default()));
+        let source = "Le contrat est signé par les deux parties.";
+        let mut usage = TokenUsage::default();
+        let revised = translator
+            .revise(
+                source,
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &context,
+                source.to_string().into(),
+                &mut usage,
+            )
+            .await
+            .unwrap();
+        assert_eq!(revised, "The contract is signed by both parties.");
+        assert_eq!(usage.requests, 1);
+    }
+
+    #[tokio::test]
+    async fn custom_prompt_and_sampling_reach_the_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({
+                "messages": [
+                    {"role": "system", "content": "You translate into English."},
+                    {"role": "user", "content": "French: Bonjour"}
+                ],
+                "temperature": 0.0,
+                "max_tokens": 256,
+                "top_p": 0.5,
+                "repeat_penalty": 1.1
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"content": "Hello"}, "finish_reason": "stop"}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let mut extra_body = serde_json::Map::new();
+        extra_body.insert("repeat_penalty".to_string(), serde_json::json!(1.1));
+        let translator = OpenAiTranslator::new(
+            server.uri(),
+            None,
+            "test".to_string(),
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap()
+        .with_prompt(PromptConfig {
+            system: Some("You translate into {target}.".to_string()),
+            user: Some("{source}: {text}".to_string()),
+            temperature: Some(0.0),
+            max_tokens: Some(256),
+            top_p: Some(0.5),
+            extra_body,
+        });
+
+        let translated = translator
+            .translate("Bonjour", &Lang::new("fr"), &Lang::new("en"))
+            .await
+            .unwrap();
+        assert_eq!(translated, "Hello");
+    }
 
     #[test]
-    fn test_language_name() {
-        assert_eq!(language_name(&Lang::new("en")), "English");
-        assert_eq!(language_name(&Lang::new("zh-CN")), "Simplified Chinese");
-        assert_eq!(language_name(&Lang::new("unknown")), "unknown");
+    fn prompt_settings_change_the_cache_identity() {
+        let base =
+            OpenAiTranslator::with_defaults("http://x".to_string(), None, "m".to_string()).unwrap();
+        let default_identity = base.cache_identity();
+        assert_eq!(default_identity.options().count(), 0);
+
+        let templated = base.with_prompt(PromptConfig {
+            user: Some("{text}".to_string()),
+            ..PromptConfig::default()
+        });
+        assert_ne!(templated.cache_identity(), default_identity);
+    }
+
+    #[tokio::test]
+    async fn azure_mode_uses_deployment_path_and_api_key_header() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/openai/deployments/team-gpt4o/chat/completions"))
+            .and(query_param("api-version", "2024-10-21"))
+            .and(header("api-key", "azure-secret"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"content": "Hello"}, "finish_reason": "stop"}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAiTranslator::new(
+            format!("{}/", server.uri()),
+            Some("azure-secret".to_string()),
+            "gpt-4o".to_string(),
+            1,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap()
+        .with_azure(Some(AzureOpenAiConfig {
+            deployment: "team-gpt4o".to_string(),
+            api_version: "2024-10-21".to_string(),
+        }));
+
+        let translated = translator
+            .translate("Bonjour", &Lang::new("fr"), &Lang::new("en"))
+            .await
+            .unwrap();
+        assert_eq!(translated, "Hello");
+
+        let identity = translator.cache_identity();
+        assert_eq!(identity.backend(), "azure-openai");
+        let options: Vec<_> = identity.options().collect();
+        assert_eq!(
+            options,
+            [
+                ("azure.api_version", "2024-10-21"),
+                ("azure.deployment", "team-gpt4o")
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn truncated_stream_is_an_error() {
+        let error = stream_from(sse_body(&["Hel"], "length")).await.unwrap_err();
+        assert!(matches!(
+            error.cause(),
+            Error::TranslationInvalidResponse(_)
+        ));
+    }
+
+    #[tokio::test]
+    async fn only_transient_failures_are_retried() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("Bonjour"))
+            .respond_with(ResponseTemplate::new(401))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("Salut"))
+            .respond_with(ResponseTemplate::new(503).insert_header("retry-after", "0"))
+            .up_to_n_times(1)
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(body_string_contains("Salut"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{"message": {"content": "Hi"}, "finish_reason": "stop"}]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let translator = OpenAiTranslator::new(
+            server.uri(),
+            None,
+            "test".to_string(),
+            3,
+            0,
+            &HttpClientConfig::default(),
+        )
+        .unwrap();
+        let (fr, en) = (Lang::new("fr"), Lang::new("en"));
+        assert!(matches!(
+            translator.translate("Bonjour", &fr, &en).await,
+            Err(Error::TranslationRequest(_))
+        ));
+        assert_eq!(translator.translate("Salut", &fr, &en).await.unwrap(), "Hi");
     }
 }
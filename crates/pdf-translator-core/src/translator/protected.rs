@@ -0,0 +1,300 @@
+use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt, TryStreamExt};
+use std::sync::Arc;
+use tracing::warn;
+
+use super::traits::{
+    BatchTranslation, PartialTranslation, TokenUsage, TranslationContext, Translator,
+    TranslatorInfo,
+};
+use crate::config::{Lang, TranslatorCacheIdentity};
+use crate::error::{Error, Result};
+use crate::protect::{Masked, Protector};
+
+/// Cache identity option recording the protected patterns.
+const IDENTITY_OPTION: &str = "protect";
+
+/// Translator that masks protected spans before handing blocks to another
+/// translator and restores them in the result.
+///
+/// A single [`Translator::translate`] that loses a placeholder fails with
+/// [`Error::TranslationInvalidResponse`]. In a batch, only the block that lost
+/// one is asked again on its own; if that fails too, the block keeps its
+/// source text and is reported in [`BatchTranslation::flagged`], so one block
+/// cannot fail the rest of the page and protected spans are never sent as is.
+pub struct ProtectedTranslator {
+    inner: Arc<dyn Translator>,
+    protector: Protector,
+}
+
+impl ProtectedTranslator {
+    pub fn new(inner: Arc<dyn Translator>, protector: Protector) -> Self {
+        Self { inner, protector }
+    }
+
+    fn identity(&self, identity: TranslatorCacheIdentity) -> TranslatorCacheIdentity {
+        identity.with_option(IDENTITY_OPTION, self.protector.fingerprint())
+    }
+
+    /// Translate a block on its own after its batch translation lost a
+    /// placeholder, or `None` if the answer still loses one.
+    async fn resend(
+        &self,
+        masked: &Masked,
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+        usage: &mut TokenUsage,
+    ) -> Result<Option<String>> {
+        let retry = self
+            .inner
+            .translate_batch_traced(&[masked.text.as_str()], source, target, context)
+            .await?;
+        *usage += retry.usage;
+        let error = match retry.translations.first().map(|text| masked.restore(text)) {
+            Some(Ok(restored)) => return Ok(Some(restored)),
+            Some(Err(error)) => error,
+            None => Error::TranslationInvalidResponse("translator returned no translation".into()),
+        };
+        warn!("Keeping the source text of a block: {error}");
+        Ok(None)
+    }
+}
+
+#[async_trait]
+impl Translator for ProtectedTranslator {
+    fn info(&self) -> TranslatorInfo {
+        self.inner.info()
+    }
+
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        self.identity(self.inner.cache_identity())
+    }
+
+    async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String> {
+        let masked = self.protector.mask(text);
+        if masked.is_empty() {
+            return self.inner.translate(text, source, target).await;
+        }
+        let translated = self.inner.translate(&masked.text, source, target).await?;
+        masked.restore(&translated)
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        Ok(self
+            .translate_batch_traced(texts, source, target, context)
+            .await?
+            .translations)
+    }
+
+    async fn translate_batch_traced(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<BatchTranslation> {
+        let masked: Vec<Masked> = texts.iter().map(|text| self.protector.mask(text)).collect();
+        let masked_texts: Vec<&str> = masked.iter().map(|masked| masked.text.as_str()).collect();
+        let batch = self
+            .inner
+            .translate_batch_traced(&masked_texts, source, target, context)
+            .await?;
+        if batch.translations.len() != masked.len() {
+            // Leave the count mismatch for the caller to report.
+            return Ok(BatchTranslation {
+                identity: self.identity(batch.identity),
+                ..batch
+            });
+        }
+
+        let mut usage = batch.usage;
+        let mut translations = Vec::with_capacity(masked.len());
+        let mut flagged = batch.flagged;
+        for (index, ((text, masked), translated)) in texts
+            .iter()
+            .zip(&masked)
+            .zip(&batch.translations)
+            .enumerate()
+        {
+            if let Ok(restored) = masked.restore(translated) {
+                translations.push(restored);
+                continue;
+            }
+            match self
+                .resend(masked, source, target, context, &mut usage)
+                .await
+            {
+                Ok(Some(resent)) => translations.push(resent),
+                Ok(None) => {
+                    translations.push((*text).to_string());
+                    flagged.push(index);
+                }
+                Err(error) => return Err(error.with_usage(usage)),
+            }
+        }
+        flagged.sort_unstable();
+        flagged.dedup();
+        Ok(BatchTranslation {
+            translations,
+            identity: self.identity(batch.identity),
+            usage,
+            flagged,
+        })
+    }
+
+    /// Stream blocks without protected spans from the inner translator.
+    ///
+    /// Partial output could show placeholders or lose them only at the end, so
+    /// blocks with protected spans arrive whole, in a single item.
+    fn translate_stream<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a Lang,
+        target: &'a Lang,
+        context: &'a TranslationContext,
+    ) -> BoxStream<'a, Result<PartialTranslation>> {
+        if self.protector.mask(text).is_empty() {
+            return self
+                .inner
+                .translate_stream(text, source, target, context)
+                .map_ok(move |partial| PartialTranslation {
+                    identity: partial.identity.map(|identity| self.identity(identity)),
+                    ..partial
+                })
+                .boxed();
+        }
+
+        stream::once(async move {
+            let mut batch = self
+                .translate_batch_traced(&[text], source, target, context)
+                .await?;
+            let text = batch.translations.pop().ok_or_else(|| {
+                Error::TranslationInvalidResponse("translator returned no translation".into())
+            })?;
+            Ok::<_, Error>(PartialTranslation {
+                text,
+                usage: batch.usage,
+                flagged: !batch.flagged.is_empty(),
+                identity: Some(batch.identity),
+            })
+        })
+        .boxed()
+    }
+
+    /// Revise blocks without protected spans through the inner translator;
+    /// the others were revised by the batch they were translated in.
+    async fn revise(
+        &self,
+        text: &str,
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+        translated: PartialTranslation,
+        usage: &mut TokenUsage,
+    ) -> Result<String> {
+        if !self.protector.mask(text).is_empty() {
+            return Ok(translated.text);
+        }
+        let translated = PartialTranslation {
+            identity: translated
+                .identity
+                .map(|identity| identity.without_option(IDENTITY_OPTION)),
+            ..translated
+        };
+        self.inner
+            .revise(text, source, target, context, translated, usage)
+            .await
+    }
+
+    async fn check_languages(&self, source: &Lang, target: &Lang) -> Result<()> {
+        self.inner.check_languages(source, target).await
+    }
+
+    fn is_available(&self) -> bool {
+        self.inner.is_available()
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unwrap_used)]
+mod tests {
+    use super::*;
+    use crate::config::ProtectConfig;
+    use crate::translator::MockTranslator;
+
+    /// Translator that drops everything after the first word.
+    struct Truncating;
+
+    #[async_trait]
+    impl Translator for Truncating {
+        fn info(&self) -> TranslatorInfo {
+            MockTranslator.info()
+        }
+
+        fn cache_identity(&self) -> TranslatorCacheIdentity {
+            MockTranslator.cache_identity()
+        }
+
+        async fn translate(&self, text: &str, _source: &Lang, _target: &Lang) -> Result<String> {
+            Ok(text
+                .split_whitespace()
+                .next()
+                .unwrap_or_default()
+                .to_string())
+        }
+    }
+
+    fn protected(inner: Arc<dyn Translator>) -> ProtectedTranslator {
+        ProtectedTranslator::new(inner, Protector::new(&ProtectConfig::default()).unwrap())
+    }
+
+    #[tokio::test]
+    async fn spans_survive_and_identity_records_the_patterns() {
+        let translator = protected(Arc::new(MockTranslator));
+        let batch = translator
+            .translate_batch_traced(
+                &["Voir https://example.com/a_b", "Bonjour"],
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(
+            batch.translations,
+            ["[en] Voir https://example.com/a_b", "[en] Bonjour"]
+        );
+        assert!(batch.identity.options().any(|(key, _)| key == "protect"));
+    }
+
+    #[tokio::test]
+    async fn blocks_losing_placeholders_are_resent_alone_then_flagged() {
+        let batch = protected(Arc::new(Truncating))
+            .translate_batch_traced(
+                &["Voir Art. 12.3", "Bonjour"],
+                &Lang::new("fr"),
+                &Lang::new("en"),
+                &TranslationContext::default(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(batch.translations, ["Voir Art. 12.3", "Bonjour"]);
+        assert_eq!(batch.flagged, [0]);
+        assert_eq!(batch.usage.requests, 2);
+    }
+
+    #[tokio::test]
+    async fn lost_placeholders_are_an_error() {
+        let result = protected(Arc::new(Truncating))
+            .translate("Voir Art. 12.3", &Lang::new("fr"), &Lang::new("en"))
+            .await;
+        assert!(matches!(result, Err(Error::TranslationInvalidResponse(_))));
+    }
+}
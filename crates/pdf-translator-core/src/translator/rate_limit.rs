@@ -0,0 +1,190 @@
+//! Client-side request and token rate limiting shared by translator backends.
+
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+use tokio::time::Instant;
+use tracing::debug;
+
+use crate::config::RateLimitConfig;
+
+/// Token bucket refilled continuously up to one minute's allowance.
+///
+/// Reservations are taken up front and may drive the balance negative; the
+/// caller then waits until the refill covers its share. Concurrent callers
+/// therefore queue in reservation order instead of retrying in a loop.
+#[derive(Debug)]
+struct Bucket {
+    capacity: f64,
+    per_second: f64,
+    available: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    fn per_minute(limit: u32, now: Instant) -> Self {
+        let capacity = f64::from(limit);
+        Self {
+            capacity,
+            per_second: capacity / 60.0,
+            available: capacity,
+            updated: now,
+        }
+    }
+
+    fn refill(&mut self, now: Instant) {
+        let elapsed = now.saturating_duration_since(self.updated).as_secs_f64();
+        self.available = elapsed
+            .mul_add(self.per_second, self.available)
+            .min(self.capacity);
+        self.updated = now;
+    }
+
+    /// Take `amount` and return how long the caller must wait for it.
+    fn reserve(&mut self, amount: f64, now: Instant) -> Duration {
+        self.refill(now);
+        self.available -= amount;
+        if self.available >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-self.available / self.per_second)
+        }
+    }
+
+    /// Return (or take) the difference between a reservation and actual use.
+    fn adjust(&mut self, refund: f64, now: Instant) {
+        self.refill(now);
+        self.available = (self.available + refund).min(self.capacity);
+    }
+}
+
+#[derive(Debug)]
+struct Buckets {
+    requests: Option<Bucket>,
+    tokens: Option<Bucket>,
+}
+
+impl Buckets {
+    fn reserve(&mut self, tokens: u64, now: Instant) -> Duration {
+        let request_wait = self
+            .requests
+            .as_mut()
+            .map_or(Duration::ZERO, |bucket| bucket.reserve(1.0, now));
+        let token_wait = self
+            .tokens
+            .as_mut()
+            .map_or(Duration::ZERO, |bucket| bucket.reserve(as_f64(tokens), now));
+        request_wait.max(token_wait)
+    }
+}
+
+/// Request and token rate limiter shared by clones of the same handle.
+///
+/// Every translator built with one limiter draws from the same buckets, so
+/// concurrent pages, sessions, and batch jobs pace themselves together instead
+/// of tripping the upstream's rate limit.
+#[derive(Debug, Clone, Default)]
+pub struct RateLimiter {
+    buckets: Option<Arc<Mutex<Buckets>>>,
+}
+
+impl RateLimiter {
+    /// Create a limiter from configuration; unset limits are not enforced
+    pub fn new(config: &RateLimitConfig) -> Self {
+        if config.requests_per_minute.is_none() && config.tokens_per_minute.is_none() {
+            return Self::unlimited();
+        }
+        let now = Instant::now();
+        let buckets = Buckets {
+            requests: config
+                .requests_per_minute
+                .map(|limit| Bucket::per_minute(limit, now)),
+            tokens: config
+                .tokens_per_minute
+                .map(|limit| Bucket::per_minute(limit, now)),
+        };
+        Self {
+            buckets: Some(Arc::new(Mutex::new(buckets))),
+        }
+    }
+
+    /// A limiter that never waits
+    pub const fn unlimited() -> Self {
+        Self { buckets: None }
+    }
+
+    /// Whether this limiter enforces any limit
+    pub const fn is_limited(&self) -> bool {
+        self.buckets.is_some()
+    }
+
+    /// Wait until one request of about `estimated_tokens` tokens may be sent.
+    pub async fn acquire(&self, estimated_tokens: u64) {
+        let Some(buckets) = &self.buckets else {
+            return;
+        };
+        let wait = buckets
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .reserve(estimated_tokens, Instant::now());
+        if !wait.is_zero() {
+            debug!("Rate limiter delaying request by {:?}", wait);
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// Correct a reservation once the upstream reported the tokens actually used.
+    pub fn settle(&self, estimated_tokens: u64, actual_tokens: u64) {
+        let Some(buckets) = &self.buckets else {
+            return;
+        };
+        let mut buckets = buckets.lock().unwrap_or_else(PoisonError::into_inner);
+        if let Some(bucket) = buckets.tokens.as_mut() {
+            bucket.adjust(
+                as_f64(estimated_tokens) - as_f64(actual_tokens),
+                Instant::now(),
+            );
+        }
+    }
+}
+
+#[allow(clippy::cast_precision_loss)] // Token counts stay far below 2^52.
+const fn as_f64(tokens: u64) -> f64 {
+    tokens as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bucket_allows_a_burst_then_paces_at_the_refill_rate() {
+        let start = Instant::now();
+        let mut bucket = Bucket::per_minute(60, start);
+
+        assert_eq!(bucket.reserve(60.0, start), Duration::ZERO);
+        assert_eq!(bucket.reserve(1.0, start), Duration::from_secs(1));
+        // A later caller queues behind the reservation already waiting.
+        assert_eq!(bucket.reserve(1.0, start), Duration::from_secs(2));
+
+        let later = start + Duration::from_secs(90);
+        assert_eq!(bucket.reserve(1.0, later), Duration::ZERO);
+    }
+
+    #[test]
+    fn settling_refunds_overestimated_tokens() {
+        let start = Instant::now();
+        let mut bucket = Bucket::per_minute(600, start);
+
+        assert_eq!(bucket.reserve(900.0, start), Duration::from_secs(30));
+        bucket.adjust(900.0 - 600.0, start);
+        assert_eq!(bucket.reserve(60.0, start), Duration::from_secs(6));
+    }
+
+    #[tokio::test]
+    async fn unlimited_limiter_never_waits() {
+        let limiter = RateLimiter::new(&RateLimitConfig::default());
+        assert!(!limiter.is_limited());
+        limiter.acquire(u64::MAX).await;
+        limiter.settle(u64::MAX, 0);
+    }
+}
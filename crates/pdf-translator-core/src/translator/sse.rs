@@ -0,0 +1,99 @@
+//! Incremental decoder for `text/event-stream` response bodies.
+
+/// Splits a server-sent event stream into the `data` payload of each event.
+///
+/// Bytes may arrive in arbitrary chunks; incomplete lines are buffered until
+/// the rest arrives. Comments, `event`, `id`, and `retry` fields are ignored.
+#[derive(Debug, Default)]
+pub struct SseDecoder {
+    line: Vec<u8>,
+    data: Option<String>,
+}
+
+impl SseDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a chunk of the body, returning the payloads of every event it completed.
+    pub fn push(&mut self, chunk: &[u8]) -> Vec<String> {
+        let mut events = Vec::new();
+        for &byte in chunk {
+            if byte == b'\n' {
+                let mut line = std::mem::take(&mut self.line);
+                if line.last() == Some(&b'\r') {
+                    line.pop();
+                }
+                if let Some(event) = self.process_line(&line) {
+                    events.push(event);
+                }
+            } else {
+                self.line.push(byte);
+            }
+        }
+        events
+    }
+
+    /// Flush an event left unterminated when the body ended.
+    pub fn finish(&mut self) -> Option<String> {
+        let line = std::mem::take(&mut self.line);
+        if !line.is_empty() {
+            // A trailing line without a newline still belongs to the last event.
+            let _ = self.process_line(&line);
+        }
+        self.data.take()
+    }
+
+    fn process_line(&mut self, line: &[u8]) -> Option<String> {
+        if line.is_empty() {
+            return self.data.take();
+        }
+        if line.starts_with(b":") {
+            return None;
+        }
+
+        let line = String::from_utf8_lossy(line);
+        let (field, value) = line.split_once(':').unwrap_or((&line, ""));
+        if field == "data" {
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match &mut self.data {
+                Some(data) => {
+                    data.push('\n');
+                    data.push_str(value);
+                }
+                None => self.data = Some(value.to_string()),
+            }
+        }
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_split_across_chunks_are_reassembled() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"data: {\"a\"").is_empty());
+        assert_eq!(
+            decoder.push(b":1}\r\n\r\ndata: [DONE]\n\n"),
+            vec!["{\"a\":1}".to_string(), "[DONE]".to_string()]
+        );
+    }
+
+    #[test]
+    fn comments_and_other_fields_are_ignored() {
+        let mut decoder = SseDecoder::new();
+        let events = decoder.push(b": keep-alive\n\nevent: message\nid: 7\ndata: a\ndata: b\n\n");
+        assert_eq!(events, vec!["a\nb".to_string()]);
+    }
+
+    #[test]
+    fn unterminated_final_event_is_flushed() {
+        let mut decoder = SseDecoder::new();
+        assert!(decoder.push(b"data: last").is_empty());
+        assert_eq!(decoder.finish(), Some("last".to_string()));
+        assert_eq!(decoder.finish(), None);
+    }
+}
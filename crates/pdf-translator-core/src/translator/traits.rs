@@ -1,6 +1,12 @@
+use super::chat::BYTES_PER_TOKEN_ESTIMATE;
 use crate::config::{Lang, TranslatorCacheIdentity};
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::glossary::Glossary;
+use crate::quality::QualityValidator;
 use async_trait::async_trait;
+use futures::stream::{self, BoxStream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
 
 /// Information about a translator backend
 #[derive(Debug, Clone)]
@@ -13,6 +19,129 @@ pub struct TranslatorInfo {
     pub supports_auto_detect: bool,
 }
 
+/// Document-level settings shared by every block translated for a document.
+#[derive(Debug, Clone, Default)]
+pub struct TranslationContext {
+    /// Terminology the translation must follow
+    pub glossary: Arc<Glossary>,
+    /// Checks a translated block must pass before it is accepted
+    pub quality: QualityValidator,
+}
+
+impl TranslationContext {
+    pub fn new(glossary: Glossary) -> Self {
+        Self {
+            glossary: Arc::new(glossary),
+            quality: QualityValidator::default(),
+        }
+    }
+
+    /// Validate translations with `quality`.
+    #[must_use]
+    pub fn with_quality(mut self, quality: QualityValidator) -> Self {
+        self.quality = quality;
+        self
+    }
+}
+
+/// Tokens billed by a backend, as reported in its responses.
+///
+/// Backends that do not report usage leave every count at zero.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TokenUsage {
+    /// Tokens sent in prompts
+    pub prompt_tokens: u64,
+    /// Tokens generated in completions
+    pub completion_tokens: u64,
+    /// Requests sent to the backend, counted by the translator itself
+    #[serde(skip)]
+    pub requests: u64,
+}
+
+impl TokenUsage {
+    pub const fn total_tokens(&self) -> u64 {
+        self.prompt_tokens.saturating_add(self.completion_tokens)
+    }
+
+    pub const fn is_empty(&self) -> bool {
+        self.prompt_tokens == 0 && self.completion_tokens == 0 && self.requests == 0
+    }
+
+    /// Usage to reserve before translating `texts`: one request, and their
+    /// size in tokens for the prompt and again for the completion.
+    pub fn estimate(texts: &[&str]) -> Self {
+        let bytes: usize = texts.iter().map(|text| text.len()).sum();
+        let tokens = u64::try_from(bytes)
+            .unwrap_or(u64::MAX)
+            .div_ceil(BYTES_PER_TOKEN_ESTIMATE);
+        Self {
+            prompt_tokens: tokens,
+            completion_tokens: tokens,
+            requests: 1,
+        }
+    }
+}
+
+impl std::ops::AddAssign for TokenUsage {
+    fn add_assign(&mut self, other: Self) {
+        self.prompt_tokens = self.prompt_tokens.saturating_add(other.prompt_tokens);
+        self.completion_tokens = self
+            .completion_tokens
+            .saturating_add(other.completion_tokens);
+        self.requests = self.requests.saturating_add(other.requests);
+    }
+}
+
+impl std::ops::SubAssign for TokenUsage {
+    fn sub_assign(&mut self, other: Self) {
+        self.prompt_tokens = self.prompt_tokens.saturating_sub(other.prompt_tokens);
+        self.completion_tokens = self
+            .completion_tokens
+            .saturating_sub(other.completion_tokens);
+        self.requests = self.requests.saturating_sub(other.requests);
+    }
+}
+
+/// Batch translations together with the identity of the backend that produced them.
+#[derive(Debug, Clone)]
+pub struct BatchTranslation {
+    /// One translation per input block, in order
+    pub translations: Vec<String>,
+    /// Cache identity of the backend that actually translated the batch
+    pub identity: TranslatorCacheIdentity,
+    /// Tokens billed for the batch, including retried and discarded responses
+    pub usage: TokenUsage,
+    /// Indexes of blocks that failed validation and hold their source text;
+    /// they must be neither cached nor remembered
+    pub flagged: Vec<usize>,
+}
+
+/// Translation of one block received so far while streaming.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PartialTranslation {
+    /// Whole translation received so far
+    pub text: String,
+    /// Tokens billed so far; usually only reported with the final item
+    pub usage: TokenUsage,
+    /// The block failed validation and `text` is its source text
+    pub flagged: bool,
+    /// Cache identity of the backend that produced the translation, when the
+    /// translator picks among several; `None` means its own cache identity
+    pub identity: Option<TranslatorCacheIdentity>,
+}
+
+impl From<String> for PartialTranslation {
+    fn from(text: String) -> Self {
+        Self {
+            text,
+            usage: TokenUsage::default(),
+            flagged: false,
+            identity: None,
+        }
+    }
+}
+
 /// Trait for translation backends
 #[async_trait]
 pub trait Translator: Send + Sync {
@@ -30,6 +159,114 @@ pub trait Translator: Send + Sync {
     /// Translate text from source language to target language
     async fn translate(&self, text: &str, source: &Lang, target: &Lang) -> Result<String>;
 
+    /// Translate several text blocks, returning one translation per input in order.
+    ///
+    /// Backends that can translate multiple blocks per request should override
+    /// this; the default issues one `translate` call per block and ignores
+    /// `context`.
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        _context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        let mut translations = Vec::with_capacity(texts.len());
+        for text in texts {
+            translations.push(self.translate(text, source, target).await?);
+        }
+        Ok(translations)
+    }
+
+    /// Translate a batch and report which backend produced it.
+    ///
+    /// Translators that delegate to other backends (such as a fallback chain)
+    /// override this so results are cached under the backend actually used.
+    /// The default reports no tokens and one request per call, failed or not,
+    /// so request budgets still hold for backends that do not track their usage.
+    async fn translate_batch_traced(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<BatchTranslation> {
+        let usage = TokenUsage {
+            requests: u64::from(!texts.is_empty()),
+            ..TokenUsage::default()
+        };
+        let translations = self
+            .translate_batch(texts, source, target, context)
+            .await
+            .map_err(|error| error.with_usage(usage))?;
+        Ok(BatchTranslation {
+            translations,
+            identity: self.cache_identity(),
+            usage,
+            flagged: Vec::new(),
+        })
+    }
+
+    /// Translate one block, yielding partial translations as they arrive.
+    ///
+    /// Each item is the whole translation received so far, and the last item
+    /// is the complete translation with the block's token usage. Backends that
+    /// cannot stream yield the block's [`Self::translate_batch_traced`] once,
+    /// so its usage is reported even when it fails.
+    fn translate_stream<'a>(
+        &'a self,
+        text: &'a str,
+        source: &'a Lang,
+        target: &'a Lang,
+        context: &'a TranslationContext,
+    ) -> BoxStream<'a, Result<PartialTranslation>> {
+        stream::once(async move {
+            let batch = self
+                .translate_batch_traced(&[text], source, target, context)
+                .await?;
+            let usage = batch.usage;
+            let flagged = !batch.flagged.is_empty();
+            let text = batch.translations.into_iter().next().ok_or_else(|| {
+                Error::TranslationInvalidResponse("translator returned no translation".into())
+                    .with_usage(usage)
+            })?;
+            Ok(PartialTranslation {
+                text,
+                usage,
+                flagged,
+                identity: None,
+            })
+        })
+        .boxed()
+    }
+
+    /// Ask again for `translated`, the final item of the stream of `text`,
+    /// which failed the quality checks in `context`, adding the billed usage
+    /// to `usage`.
+    ///
+    /// Streamed blocks are checked only once their stream ends, so this is how
+    /// they get the re-asks batches already receive. Translators choosing among
+    /// several backends ask the one named by `translated.identity`. Backends
+    /// that cannot take feedback keep the translation.
+    async fn revise(
+        &self,
+        _text: &str,
+        _source: &Lang,
+        _target: &Lang,
+        _context: &TranslationContext,
+        translated: PartialTranslation,
+        _usage: &mut TokenUsage,
+    ) -> Result<String> {
+        Ok(translated.text)
+    }
+
+    /// Verify the backend supports this language pair before any work starts.
+    ///
+    /// Backends without a language list accept every pair.
+    async fn check_languages(&self, _source: &Lang, _target: &Lang) -> Result<()> {
+        Ok(())
+    }
+
     /// Check if the translator is available (e.g., API key configured)
     fn is_available(&self) -> bool {
         true
@@ -20,6 +20,25 @@ pub fn cache_dir() -> Option<PathBuf> {
         .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
 }
 
+/// Get the user's data directory following XDG conventions.
+///
+/// Returns `$XDG_DATA_HOME` if set, otherwise `$HOME/.local/share`.
+pub fn data_dir() -> Option<PathBuf> {
+    std::env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local").join("share"))
+        })
+}
+
+/// Get the default translation memory file.
+pub fn translation_memory_path() -> PathBuf {
+    data_dir()
+        .unwrap_or_else(|| PathBuf::from(".local/share"))
+        .join("pdf-translator")
+        .join("translation-memory.jsonl")
+}
+
 /// Get the default translation cache path.
 pub fn translation_cache_path() -> PathBuf {
     cache_dir()
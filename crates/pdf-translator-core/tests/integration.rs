@@ -11,10 +11,14 @@
 use async_trait::async_trait;
 use lopdf::{Dictionary, Document as LoDocument, Object, Stream};
 use pdf_translator_core::{
-    AppConfig, CacheKey, Error, Lang, OverlayOptions, PdfDocument, PdfOverlay, PdfTranslator,
-    Result, Translator, TranslatorCacheIdentity, translator::TranslatorInfo,
+    AppConfig, BatchTranslation, BlockStatus, BreakerState, CacheKey, Error, FallbackTranslator,
+    Lang, OverlayOptions, PdfDocument, PdfOverlay, PdfTranslator, ProtectedTranslator, Result,
+    TokenUsage, TranslationContext, Translator, TranslatorCacheIdentity,
+    translator::TranslatorInfo,
 };
 use std::sync::Arc;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Duration;
 
 // =============================================================================
 // Mock Translator for Testing
@@ -72,6 +76,116 @@ impl Translator for MockTranslator {
     }
 }
 
+/// A translator that only supports whole-page batches and records how it was called.
+#[derive(Default)]
+struct BatchOnlyTranslator {
+    batch_calls: AtomicUsize,
+    blocks_seen: AtomicUsize,
+    glossary_terms: AtomicUsize,
+}
+
+#[async_trait]
+impl Translator for BatchOnlyTranslator {
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        TranslatorCacheIdentity::new("batch-mock", "local", "deterministic")
+    }
+
+    async fn translate(&self, _text: &str, _source: &Lang, _target: &Lang) -> Result<String> {
+        Err(Error::TranslationRequest(
+            "single-block translation should not be used".to_string(),
+        ))
+    }
+
+    async fn translate_batch(
+        &self,
+        texts: &[&str],
+        _source: &Lang,
+        _target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<Vec<String>> {
+        self.batch_calls.fetch_add(1, Ordering::SeqCst);
+        self.blocks_seen.fetch_add(texts.len(), Ordering::SeqCst);
+        self.glossary_terms
+            .store(context.glossary.len(), Ordering::SeqCst);
+        Ok(texts.iter().map(|text| format!("[BATCH] {text}")).collect())
+    }
+
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "batch-mock",
+            requires_api_key: false,
+            supports_auto_detect: false,
+        }
+    }
+}
+
+/// A translator that drops every protected-span placeholder.
+#[derive(Default)]
+struct PlaceholderDroppingTranslator {
+    calls: AtomicUsize,
+}
+
+#[async_trait]
+impl Translator for PlaceholderDroppingTranslator {
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        TranslatorCacheIdentity::new("dropping-mock", "local", "deterministic")
+    }
+
+    async fn translate(&self, _text: &str, _source: &Lang, _target: &Lang) -> Result<String> {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        Ok("[DROPPED]".to_string())
+    }
+
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "dropping-mock",
+            requires_api_key: false,
+            supports_auto_detect: false,
+        }
+    }
+}
+
+/// A translator that reports a fixed token usage for every batch.
+struct MeteredTranslator;
+
+#[async_trait]
+impl Translator for MeteredTranslator {
+    fn cache_identity(&self) -> TranslatorCacheIdentity {
+        TranslatorCacheIdentity::new("metered-mock", "local", "metered")
+    }
+
+    async fn translate(&self, text: &str, _source: &Lang, _target: &Lang) -> Result<String> {
+        Ok(format!("[METERED] {text}"))
+    }
+
+    async fn translate_batch_traced(
+        &self,
+        texts: &[&str],
+        source: &Lang,
+        target: &Lang,
+        context: &TranslationContext,
+    ) -> Result<BatchTranslation> {
+        Ok(BatchTranslation {
+            translations: self.translate_batch(texts, source, target, context).await?,
+            identity: self.cache_identity(),
+            usage: TokenUsage {
+                prompt_tokens: 100,
+                completion_tokens: 50,
+                requests: 1,
+            },
+            flagged: Vec::new(),
+        })
+    }
+
+    fn info(&self) -> TranslatorInfo {
+        TranslatorInfo {
+            name: "metered-mock",
+            requires_api_key: false,
+            supports_auto_detect: false,
+        }
+    }
+}
+
 // =============================================================================
 // Test Fixtures
 // =============================================================================
@@ -83,13 +197,23 @@ fn load_test_pdf() -> PdfDocument {
 }
 
 /// Create a minimal test configuration
+///
+/// The fixture is written in English, so it is translated into German;
+/// blocks already in the target language would not reach the translator.
 fn test_config() -> AppConfig {
     AppConfig {
+        source_lang: Lang::new("en"),
+        target_lang: Lang::new("de"),
         cache: pdf_translator_core::config::CacheConfig {
             memory_enabled: true,
             disk_enabled: false,
             ..Default::default()
         },
+        // The mocks answer with the English source text.
+        quality: pdf_translator_core::config::QualityConfig {
+            untranslated: false,
+            ..Default::default()
+        },
         ..Default::default()
     }
 }
@@ -226,6 +350,285 @@ async fn test_translation_error_handling() {
     }
 }
 
+#[tokio::test]
+async fn test_page_blocks_are_translated_in_one_batch() {
+    let doc = load_test_pdf();
+    let translator = Arc::new(BatchOnlyTranslator::default());
+    let pdf_translator = PdfTranslator::with_translator(translator.clone(), test_config())
+        .expect("Should create translator");
+
+    pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Batch translation should succeed");
+
+    let blocks = pdf_translator_core::pdf::TextExtractor::new(&doc)
+        .extract_page_blocks(0)
+        .expect("Text extraction should succeed")
+        .into_iter()
+        .filter(|block| !block.text.trim().is_empty())
+        .count();
+    assert_eq!(translator.batch_calls.load(Ordering::SeqCst), 1);
+    assert_eq!(translator.blocks_seen.load(Ordering::SeqCst), blocks);
+}
+
+#[tokio::test]
+async fn test_blocks_already_in_target_language_are_not_sent() {
+    let doc = load_test_pdf();
+    let translator = Arc::new(BatchOnlyTranslator::default());
+    let config = AppConfig {
+        target_lang: Lang::new("en"),
+        ..test_config()
+    };
+    let pdf_translator = PdfTranslator::with_translator(translator.clone(), config)
+        .expect("Should create translator");
+
+    pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Translation should succeed");
+
+    let english = pdf_translator_core::pdf::TextExtractor::new(&doc)
+        .extract_page_blocks(0)
+        .expect("Text extraction should succeed")
+        .into_iter()
+        .filter(|block| {
+            block
+                .language
+                .as_ref()
+                .is_some_and(|lang| lang.as_str() == "en")
+        })
+        .count();
+    assert!(
+        english > 0,
+        "Fixture paragraphs should be detected as English"
+    );
+    let total = pdf_translator_core::pdf::TextExtractor::new(&doc)
+        .extract_page_blocks(0)
+        .expect("Text extraction should succeed")
+        .into_iter()
+        .filter(|block| !block.text.trim().is_empty())
+        .count();
+    assert_eq!(
+        translator.blocks_seen.load(Ordering::SeqCst),
+        total - english
+    );
+}
+
+#[tokio::test]
+async fn test_translation_memory_is_reused_across_translators() {
+    let doc = load_test_pdf();
+    let dir = tempfile::tempdir().expect("Should create temp dir");
+    let config = AppConfig {
+        translation_memory: pdf_translator_core::config::TranslationMemoryConfig {
+            enabled: true,
+            path: Some(dir.path().join("memory.jsonl")),
+            ..Default::default()
+        },
+        ..test_config()
+    };
+
+    let first = Arc::new(BatchOnlyTranslator::default());
+    let page = PdfTranslator::with_translator(first.clone(), config.clone())
+        .expect("Should create translator")
+        .translate_page(&doc, 0)
+        .await
+        .expect("First translation should succeed");
+    assert_eq!(page.memory.hits(), 0);
+    assert_eq!(
+        first.blocks_seen.load(Ordering::SeqCst),
+        page.memory.lookups
+    );
+
+    // A new translator starts with an empty page cache, like another document.
+    let second = Arc::new(BatchOnlyTranslator::default());
+    let page = PdfTranslator::with_translator(second.clone(), config)
+        .expect("Should create translator")
+        .translate_page(&doc, 0)
+        .await
+        .expect("Second translation should succeed");
+    assert!(!page.from_cache);
+    assert!(page.memory.exact > 0, "Blocks should come from the memory");
+    assert_eq!(
+        second.blocks_seen.load(Ordering::SeqCst),
+        page.memory.lookups - page.memory.hits()
+    );
+}
+
+#[tokio::test]
+async fn test_streaming_page_reports_previews_and_caches_result() {
+    let doc = load_test_pdf();
+    let pdf_translator =
+        PdfTranslator::with_translator(Arc::new(MockTranslator::new()), test_config())
+            .expect("Should create translator");
+
+    let mut previews = Vec::new();
+    let streamed = pdf_translator
+        .translate_page_streaming(&doc, 0, false, |preview| previews.push(preview))
+        .await
+        .expect("Streaming translation should succeed");
+    assert!(!streamed.from_cache);
+    assert!(
+        previews
+            .iter()
+            .all(|preview| preview.block < preview.block_count
+                && preview.text.starts_with("[TRANSLATED]"))
+    );
+
+    let cached = pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Cached translation should succeed");
+    assert!(cached.from_cache, "Streamed page should be cached");
+    assert_eq!(cached.pdf_bytes, streamed.pdf_bytes);
+}
+
+#[tokio::test]
+async fn test_configured_glossary_reaches_translator() {
+    let dir = tempfile::tempdir().expect("Should create temp dir");
+    let glossary_path = dir.path().join("glossary.csv");
+    std::fs::write(&glossary_path, "contrat,agreement\nACME,\n").expect("Should write glossary");
+
+    let doc = load_test_pdf();
+    let mut config = test_config();
+    config.glossary = Some(glossary_path);
+    let translator = Arc::new(BatchOnlyTranslator::default());
+    let pdf_translator = PdfTranslator::with_translator(translator.clone(), config)
+        .expect("Should create translator");
+
+    pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Translation should succeed");
+    assert_eq!(translator.glossary_terms.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn test_missing_glossary_is_a_config_error() {
+    let mut config = test_config();
+    config.glossary = Some("/nonexistent/glossary.toml".into());
+
+    let result = PdfTranslator::with_translator(Arc::new(MockTranslator::new()), config);
+    assert!(matches!(result, Err(Error::ConfigLoad(_))));
+}
+
+#[tokio::test]
+async fn test_concurrent_pages_keep_document_order() {
+    let doc = load_test_pdf();
+    let mut config = test_config();
+    config.max_concurrent_pages = 4;
+    config.max_in_flight_requests = 2;
+    let pdf_translator = PdfTranslator::with_translator(Arc::new(MockTranslator::new()), config)
+        .expect("Should create translator");
+
+    let pages: Vec<usize> = (0..doc.page_count()).rev().collect();
+    let mut completed = 0;
+    let translated = pdf_translator
+        .translate_pages(&doc, &pages, |_| completed += 1)
+        .await
+        .expect("Concurrent translation should succeed");
+
+    assert_eq!(completed, pages.len());
+    let order: Vec<usize> = translated.iter().map(|page| page.page_num).collect();
+    assert_eq!(order, pages);
+}
+
+#[tokio::test]
+async fn test_page_usage_and_cost_are_reported() {
+    let doc = load_test_pdf();
+    let mut config = test_config();
+    config.pricing.models.insert(
+        "metered".to_string(),
+        pdf_translator_core::ModelPrice {
+            prompt_per_million: 1_000.0,
+            completion_per_million: 2_000.0,
+        },
+    );
+    let pdf_translator = PdfTranslator::with_translator(Arc::new(MeteredTranslator), config)
+        .expect("Should create translator");
+
+    let page = pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Translation should succeed");
+    assert_eq!(page.usage.total_tokens(), 150);
+    let cost = page.cost.expect("Priced model should have a cost");
+    assert!((cost - 0.2).abs() < 1e-9);
+
+    let cached = pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Cached translation should succeed");
+    assert!(cached.usage.is_empty());
+    assert_eq!(
+        pdf_translator.document_usage(&doc).usage.total_tokens(),
+        150
+    );
+}
+
+#[tokio::test]
+async fn test_budget_stops_translation_but_keeps_cached_pages() {
+    let doc = load_test_pdf();
+    let mut config = test_config();
+    config.budget.max_tokens_per_document = Some(150);
+    let pdf_translator = PdfTranslator::with_translator(Arc::new(MeteredTranslator), config)
+        .expect("Should create translator");
+
+    pdf_translator
+        .translate_page_force(&doc, 0, true)
+        .await
+        .expect("First page fits the budget");
+    let over_budget = pdf_translator.translate_page_force(&doc, 0, true).await;
+    assert!(matches!(
+        over_budget,
+        Err(Error::TranslationBudgetExceeded(_))
+    ));
+
+    let cached = pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Cached pages stay available past the budget");
+    assert!(cached.from_cache);
+}
+
+#[tokio::test]
+async fn test_budget_holds_across_translators_sharing_a_cache() {
+    let doc = load_test_pdf();
+    let mut config = test_config();
+    config.budget.max_requests_per_document = Some(1);
+    let cache =
+        pdf_translator_core::TranslationCache::new(&config.cache).expect("Should create cache");
+    let first = PdfTranslator::with_translator_and_cache(
+        Arc::new(MeteredTranslator),
+        config.clone(),
+        cache.clone(),
+    )
+    .expect("Should create translator");
+    let second =
+        PdfTranslator::with_translator_and_cache(Arc::new(MeteredTranslator), config, cache)
+            .expect("Should create translator");
+
+    first
+        .translate_page_force(&doc, 0, true)
+        .await
+        .expect("First request fits the budget");
+    assert_eq!(second.document_usage(&doc).usage.requests, 1);
+    let over_budget = second.translate_page_force(&doc, 0, true).await;
+    assert!(matches!(
+        over_budget,
+        Err(Error::TranslationBudgetExceeded(_))
+    ));
+}
+
+#[test]
+fn test_cost_budget_without_prices_is_a_config_error() {
+    let mut config = test_config();
+    config.budget.max_cost = Some(1.0);
+
+    let result = PdfTranslator::with_translator(Arc::new(MockTranslator::new()), config);
+    assert!(matches!(result, Err(Error::ConfigInvalid { .. })));
+}
+
 // =============================================================================
 // Cache Tests
 // =============================================================================
@@ -263,6 +666,130 @@ async fn test_cache_hit_on_second_translation() {
     );
 }
 
+#[tokio::test]
+async fn test_color_change_rerenders_from_block_cache() {
+    let doc = load_test_pdf();
+    let dir = tempfile::tempdir().expect("Should create temp dir");
+    let config = AppConfig {
+        cache: pdf_translator_core::config::CacheConfig {
+            memory_enabled: false,
+            disk_enabled: true,
+            disk_path: Some(dir.path().to_path_buf()),
+            ..Default::default()
+        },
+        ..test_config()
+    };
+
+    let first = Arc::new(BatchOnlyTranslator::default());
+    let page = PdfTranslator::with_translator(first.clone(), config.clone())
+        .expect("Should create translator")
+        .translate_page(&doc, 0)
+        .await
+        .expect("First translation should succeed");
+    assert!(first.blocks_seen.load(Ordering::SeqCst) > 0);
+
+    // A new colour misses the page cache but not the translated blocks.
+    let recolored = AppConfig {
+        text_color: pdf_translator_core::TextColor::blue(),
+        ..config
+    };
+    let second = Arc::new(BatchOnlyTranslator::default());
+    let repainted = PdfTranslator::with_translator(second.clone(), recolored)
+        .expect("Should create translator")
+        .translate_page(&doc, 0)
+        .await
+        .expect("Re-rendering should succeed");
+    assert!(!repainted.from_cache);
+    assert_eq!(second.batch_calls.load(Ordering::SeqCst), 0);
+    assert_ne!(page.pdf_bytes, repainted.pdf_bytes);
+    assert_eq!(
+        page.blocks
+            .iter()
+            .map(|block| &block.translation)
+            .collect::<Vec<_>>(),
+        repainted
+            .blocks
+            .iter()
+            .map(|block| &block.translation)
+            .collect::<Vec<_>>()
+    );
+}
+
+#[tokio::test]
+async fn test_blocks_losing_placeholders_are_flagged_and_not_cached() {
+    let doc = load_test_pdf();
+    let config = test_config();
+    // Every word is protected, and the inner translator drops all of them.
+    let protector =
+        pdf_translator_core::Protector::new(&pdf_translator_core::config::ProtectConfig {
+            builtin: false,
+            patterns: vec![r"\w+".to_string()],
+            ..Default::default()
+        })
+        .expect("Should compile patterns");
+    let inner = Arc::new(PlaceholderDroppingTranslator::default());
+    let translator = Arc::new(ProtectedTranslator::new(inner.clone(), protector));
+    let pdf_translator =
+        PdfTranslator::with_translator(translator, config).expect("Should create translator");
+
+    let first = pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Flagged blocks should not fail the page");
+    let flagged: Vec<_> = first
+        .blocks
+        .iter()
+        .filter(|block| block.status == BlockStatus::Flagged)
+        .collect();
+    assert!(!flagged.is_empty());
+    assert!(flagged.iter().all(|block| block.translation == block.text));
+
+    let calls = inner.calls.load(Ordering::SeqCst);
+    let second = pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Second translation should succeed");
+    assert!(
+        !second.from_cache,
+        "Pages with flagged blocks are not cached"
+    );
+    assert!(
+        inner.calls.load(Ordering::SeqCst) > calls,
+        "Flagged blocks are not cached"
+    );
+}
+
+#[tokio::test]
+async fn test_fallback_result_is_cached_under_backend_used() {
+    let doc = load_test_pdf();
+    let config = test_config();
+    let translator = Arc::new(FallbackTranslator::new(
+        vec![
+            Arc::new(MockTranslator::failing()),
+            Arc::new(MockTranslator::new()),
+        ],
+        1,
+        Duration::from_mins(1),
+    ));
+
+    let pdf_translator = PdfTranslator::with_translator(translator.clone(), config)
+        .expect("Should create translator");
+
+    let first = pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Fallback backend should translate the page");
+    assert!(!first.from_cache);
+    assert_eq!(translator.health()[0].1, BreakerState::Open);
+
+    // The open primary is skipped, so the lookup uses the fallback's identity.
+    let second = pdf_translator
+        .translate_page(&doc, 0)
+        .await
+        .expect("Second translation should succeed");
+    assert!(second.from_cache, "Fallback result should be cached");
+}
+
 #[tokio::test]
 async fn test_force_bypasses_cache() {
     let doc = load_test_pdf();
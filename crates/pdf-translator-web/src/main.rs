@@ -13,7 +13,11 @@ use axum::{
     extract::DefaultBodyLimit,
     routing::{get, post},
 };
-use clap::Parser;
+use clap::{Parser, ValueEnum};
+use pdf_translator_core::{
+    AppConfig, AzureOpenAiConfig, BackendConfig, DEFAULT_AZURE_API_VERSION, ModelPrice,
+    TranslatorBackend,
+};
 use std::net::{IpAddr, SocketAddr};
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -51,6 +55,34 @@ fn resolve_static_dir(explicit_path: Option<&str>) -> PathBuf {
     PathBuf::from(concat!(env!("CARGO_MANIFEST_DIR"), "/static"))
 }
 
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum BackendOption {
+    #[value(name = "openai")]
+    OpenAi,
+    #[value(name = "deepl")]
+    DeepL,
+    #[value(name = "libretranslate")]
+    LibreTranslate,
+    Anthropic,
+    Ollama,
+    Command,
+    Mock,
+}
+
+impl From<BackendOption> for TranslatorBackend {
+    fn from(opt: BackendOption) -> Self {
+        match opt {
+            BackendOption::OpenAi => Self::OpenAi,
+            BackendOption::DeepL => Self::DeepL,
+            BackendOption::LibreTranslate => Self::LibreTranslate,
+            BackendOption::Anthropic => Self::Anthropic,
+            BackendOption::Ollama => Self::Ollama,
+            BackendOption::Command => Self::Command,
+            BackendOption::Mock => Self::Mock,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 #[command(name = "pdf-translator-web")]
 #[command(author, version, about = "PDF Translator Web Server", long_about = None)]
@@ -63,17 +95,82 @@ struct Args {
     #[arg(short, long, default_value = "3000")]
     port: u16,
 
-    /// OpenAI API base URL
-    #[arg(
-        long,
-        env = "OPENAI_API_BASE",
-        default_value = "http://localhost:8080/v1"
-    )]
-    api_base: String,
+    /// Config file path, in the CLI's format (backends, fallback chain, pricing, ...)
+    #[arg(short, long)]
+    config: Option<PathBuf>,
+
+    /// Translation backend (default: from config, otherwise openai)
+    #[arg(long, value_enum)]
+    backend: Option<BackendOption>,
+
+    /// OpenAI API base URL (default: http://localhost:8080/v1)
+    #[arg(long, env = "OPENAI_API_BASE")]
+    api_base: Option<String>,
+
+    /// Model name for OpenAI-compatible API (default: default_model)
+    #[arg(long, env = "OPENAI_MODEL")]
+    model: Option<String>,
+
+    /// Azure OpenAI deployment name (switches --api-base to Azure's URL layout)
+    #[arg(long, env = "AZURE_OPENAI_DEPLOYMENT")]
+    azure_deployment: Option<String>,
+
+    /// Azure OpenAI API version
+    #[arg(long, env = "AZURE_OPENAI_API_VERSION")]
+    azure_api_version: Option<String>,
+
+    /// LibreTranslate server URL
+    #[arg(long, env = "LIBRETRANSLATE_URL")]
+    libretranslate_url: Option<String>,
+
+    /// Model name for the Anthropic API
+    #[arg(long, env = "ANTHROPIC_MODEL")]
+    anthropic_model: Option<String>,
+
+    /// Ollama server URL
+    #[arg(long, env = "OLLAMA_URL")]
+    ollama_url: Option<String>,
 
-    /// Model name for OpenAI-compatible API
-    #[arg(long, env = "OPENAI_MODEL", default_value = "default_model")]
-    model: String,
+    /// Model name for Ollama
+    #[arg(long, env = "OLLAMA_MODEL")]
+    ollama_model: Option<String>,
+
+    /// Price per million prompt tokens, for cost estimates
+    #[arg(long, env = "PROMPT_PRICE_PER_MILLION")]
+    prompt_price: Option<f64>,
+
+    /// Price per million completion tokens, for cost estimates
+    #[arg(long, env = "COMPLETION_PRICE_PER_MILLION")]
+    completion_price: Option<f64>,
+
+    /// Currency of the token prices (default: USD)
+    #[arg(long, env = "PRICE_CURRENCY")]
+    price_currency: Option<String>,
+
+    /// Stop translating a document after this many tokens
+    #[arg(long, env = "MAX_TOKENS_PER_DOCUMENT")]
+    max_tokens_per_document: Option<u64>,
+
+    /// Stop translating a document after this many translator requests
+    #[arg(long, env = "MAX_REQUESTS_PER_DOCUMENT")]
+    max_requests_per_document: Option<u64>,
+
+    /// Stop translating a document once its estimated cost reaches this amount
+    #[arg(long, env = "MAX_COST_PER_DOCUMENT")]
+    max_cost: Option<f64>,
+
+    /// Requests per minute sent upstream, shared by all sessions and batch jobs
+    #[arg(long, env = "REQUESTS_PER_MINUTE")]
+    requests_per_minute: Option<u32>,
+
+    /// Estimated tokens per minute sent upstream, shared by all sessions and batch jobs
+    #[arg(long, env = "TOKENS_PER_MINUTE")]
+    tokens_per_minute: Option<u32>,
+
+    /// Reuse and store translated blocks in the translation memory, shared by all
+    /// sessions (off by default)
+    #[arg(long, env = "TRANSLATION_MEMORY")]
+    translation_memory: bool,
 
     /// Verbose output
     #[arg(short, long, action = clap::ArgAction::Count)]
@@ -88,6 +185,112 @@ struct Args {
     clear_cache: bool,
 }
 
+/// Build the server configuration: the `--config` file (or the defaults),
+/// overridden by the flags and environment variables that are set.
+fn app_config(args: &Args) -> Result<AppConfig> {
+    let mut config = match &args.config {
+        Some(path) => AppConfig::from_file(path).context("Failed to load config file")?,
+        None => AppConfig::default(),
+    };
+
+    if let Some(backend) = args.backend.map(TranslatorBackend::from)
+        && config.translator.backend.kind() != backend
+    {
+        config.translator.backend = BackendConfig::default_for(backend);
+    }
+    // Credentials only reach the backend they belong to.
+    let env_key = |name: &str| std::env::var(name).ok();
+    match &mut config.translator.backend {
+        BackendConfig::OpenAi(openai) => {
+            if let Some(api_base) = &args.api_base {
+                openai.api_base.clone_from(api_base);
+            }
+            if let Some(api_key) = env_key("OPENAI_API_KEY") {
+                openai.api_key = Some(api_key);
+            }
+            if let Some(model) = &args.model {
+                openai.model.clone_from(model);
+            }
+            if let Some(deployment) = &args.azure_deployment {
+                let api_version = openai.azure.take().map_or_else(
+                    || DEFAULT_AZURE_API_VERSION.to_string(),
+                    |azure| azure.api_version,
+                );
+                openai.azure = Some(AzureOpenAiConfig {
+                    deployment: deployment.clone(),
+                    api_version,
+                });
+            }
+            if let (Some(api_version), Some(azure)) = (&args.azure_api_version, &mut openai.azure) {
+                azure.api_version.clone_from(api_version);
+            }
+        }
+        BackendConfig::DeepL(deepl) => {
+            if let Some(api_key) = env_key("DEEPL_API_KEY") {
+                deepl.api_key = Some(api_key);
+            }
+        }
+        BackendConfig::LibreTranslate(libre) => {
+            if let Some(api_base) = &args.libretranslate_url {
+                libre.api_base.clone_from(api_base);
+            }
+            if let Some(api_key) = env_key("LIBRETRANSLATE_API_KEY") {
+                libre.api_key = Some(api_key);
+            }
+        }
+        BackendConfig::Anthropic(anthropic) => {
+            if let Some(api_key) = env_key("ANTHROPIC_API_KEY") {
+                anthropic.api_key = Some(api_key);
+            }
+            if let Some(model) = &args.anthropic_model {
+                anthropic.model.clone_from(model);
+            }
+        }
+        BackendConfig::Ollama(ollama) => {
+            if let Some(api_base) = &args.ollama_url {
+                ollama.api_base.clone_from(api_base);
+            }
+            if let Some(model) = &args.ollama_model {
+                ollama.model.clone_from(model);
+            }
+        }
+        BackendConfig::Command(_) | BackendConfig::Mock => {}
+    }
+
+    if let Some(currency) = &args.price_currency {
+        config.pricing.currency.clone_from(currency);
+    }
+    if args.prompt_price.is_some() || args.completion_price.is_some() {
+        let model = match &config.translator.backend {
+            BackendConfig::OpenAi(openai) => &openai.model,
+            BackendConfig::Anthropic(anthropic) => &anthropic.model,
+            BackendConfig::Ollama(ollama) => &ollama.model,
+            _ => anyhow::bail!("Token prices need a backend with a model"),
+        };
+        config.pricing.models.insert(
+            model.clone(),
+            ModelPrice {
+                prompt_per_million: args.prompt_price.unwrap_or_default(),
+                completion_per_million: args.completion_price.unwrap_or_default(),
+            },
+        );
+    }
+
+    let budget = &mut config.budget;
+    budget.max_tokens_per_document = args
+        .max_tokens_per_document
+        .or(budget.max_tokens_per_document);
+    budget.max_requests_per_document = args
+        .max_requests_per_document
+        .or(budget.max_requests_per_document);
+    budget.max_cost = args.max_cost.or(budget.max_cost);
+    let rate_limit = &mut config.rate_limit;
+    rate_limit.requests_per_minute = args.requests_per_minute.or(rate_limit.requests_per_minute);
+    rate_limit.tokens_per_minute = args.tokens_per_minute.or(rate_limit.tokens_per_minute);
+    config.translation_memory.enabled |= args.translation_memory;
+    Ok(config)
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         if let Err(error) = tokio::signal::ctrl_c().await {
@@ -130,8 +333,6 @@ async fn main() -> Result<()> {
         anyhow::bail!("refusing to bind non-loopback address {}", args.host);
     }
 
-    let api_key = std::env::var("OPENAI_API_KEY").ok();
-
     // Setup logging with per-crate filtering
     // font_kit emits many "Error loading font from handle: Parse" warnings for
     // system fonts it can't parse - these are expected and noisy
@@ -157,11 +358,18 @@ async fn main() -> Result<()> {
         }
     }
 
+    let config = app_config(&args)?;
+    config
+        .budget
+        .validate(&config.pricing)
+        .context("Invalid budget settings")?;
+    config
+        .rate_limit
+        .validate()
+        .context("Invalid rate limit settings")?;
+
     // Create application state (opens cache - fails fast if locked)
-    let state = Arc::new(
-        AppState::new(args.api_base, api_key, args.model)
-            .context("Failed to initialize application state")?,
-    );
+    let state = Arc::new(AppState::new(config).context("Failed to initialize application state")?);
 
     // Spawn background task for session cleanup (runs every 5 minutes)
     let cleanup_state = Arc::downgrade(&state);
@@ -198,6 +406,14 @@ async fn main() -> Result<()> {
             "/api/translate/{session_id}/{page}",
             post(routes::translate_page),
         )
+        .route(
+            "/api/translate-preview/{session_id}/{page}",
+            post(routes::translate_preview),
+        )
+        .route(
+            "/api/translate-stream/{session_id}/{page}",
+            get(routes::translate_stream),
+        )
         .route(
             "/api/prefetch/{session_id}/{page}",
             post(routes::prefetch_page),
@@ -134,8 +134,7 @@ pub async fn start_translate_all(
                             .with_session_mut(|current| {
                                 current.release_claim(&claim);
                                 if current.job_is_current(&job_clone) {
-                                    job_clone
-                                        .set_error(format!("Failed at page {}: {error}", page + 1));
+                                    job_clone.set_error(batch_error_message(page, &error));
                                 }
                             })
                             .await;
@@ -198,6 +197,7 @@ pub async fn start_translate_all(
                             session
                                 .with_session_mut(|current| {
                                     if current.job_is_current(&job_clone) && job_clone.is_active() {
+                                        job_clone.add_usage(&translated);
                                         job_clone.increment();
                                         true
                                     } else {
@@ -252,6 +252,24 @@ pub async fn start_translate_all(
         .or_internal_error()
 }
 
+/// Progress message for a page that stopped the batch.
+///
+/// Pages translated before a budget stop stay stored, but the document's
+/// budget is shared by every request and is not reset while the server runs.
+fn batch_error_message(page: usize, error: &pdf_translator_core::Error) -> String {
+    if matches!(
+        error,
+        pdf_translator_core::Error::TranslationBudgetExceeded(_)
+    ) {
+        format!(
+            "Stopped before page {}: {error}. The document's budget is used up, so the remaining pages can't be translated.",
+            page + 1
+        )
+    } else {
+        format!("Failed at page {}: {error}", page + 1)
+    }
+}
+
 #[allow(tail_expr_drop_order)]
 pub async fn translate_all_stream(
     State(state): State<Arc<AppState>>,
@@ -278,10 +296,11 @@ pub async fn translate_all_stream(
             if current != last_current || done {
                 last_current = current;
                 let message = error.unwrap_or_else(|| {
+                    let usage = job.usage().describe(&state.config.pricing);
                     if done {
-                        format!("Completed {current} of {page_count} pages")
+                        format!("Completed {current} of {page_count} pages, {usage}")
                     } else {
-                        format!("Translating page {} of {page_count}...", current + 1)
+                        format!("Translating page {} of {page_count}... {usage}", current + 1)
                     }
                 });
                 let template = ProgressTemplate::new(
@@ -15,7 +15,7 @@ pub use batch::{start_translate_all, translate_all_stream};
 pub use download::download_pdf;
 pub use pages::{index, view_page, view_page_redirect};
 pub use settings::{toggle_auto_translate, update_settings};
-pub use translate::{prefetch_page, translate_page};
+pub use translate::{prefetch_page, translate_page, translate_preview, translate_stream};
 pub use upload::upload_pdf;
 pub use viewer::{get_page_image, get_page_view, get_page_view_query, set_view_mode};
 
@@ -55,6 +55,7 @@ pub async fn view_page(
         is_translated,
         has_translations,
         current_source,
+        detected_source,
         current_target,
         current_color,
         default_view_mode,
@@ -67,6 +68,9 @@ pub async fn view_page(
                 s.page_store.has_page(page),
                 !s.page_store.is_empty(),
                 s.settings.current_source().to_string(),
+                s.detected_source
+                    .as_ref()
+                    .map_or_else(String::new, ToString::to_string),
                 s.settings.current_target().to_string(),
                 s.settings.current_color().to_string(),
                 s.settings.view_mode,
@@ -92,6 +96,7 @@ pub async fn view_page(
         is_translated,
         has_translations,
         current_source,
+        detected_source,
         current_target,
         current_color,
         view_mode,
@@ -5,16 +5,60 @@ use axum::{
     body::Body,
     extract::{Form, Path, State},
     http::{StatusCode, header},
-    response::Response,
+    response::{
+        Response,
+        sse::{Event, KeepAlive, Sse},
+    },
 };
+use futures::stream::Stream;
+use pdf_translator_core::{BlockPreview, PageRenderer, PdfDocument, TranslatedPage};
+use std::convert::Infallible;
 use std::sync::Arc;
+use tokio::sync::watch;
 use tracing::{debug, error};
 
-use super::TranslateForm;
+use super::{TranslateForm, page_index};
 use crate::helpers::{OptionExt, ResultExt, RouteResult, validate_page};
-use crate::page_store::PageStore;
-use crate::state::{AppState, PageClaim};
-use crate::templates::TranslateResultTemplate;
+use crate::page_store::{PageStore, StagedPage};
+use crate::state::{AppState, PageClaim, PageStream, SessionRef, SessionSettings};
+use crate::templates::{
+    PreviewBlock, StreamPreviewTemplate, TranslateResultTemplate, TranslateStreamTemplate,
+};
+
+/// A page that is either already translated or claimed for translation.
+enum PageTranslation {
+    Stored(u64),
+    Claimed(Box<(PageClaim, SessionSettings, PdfDocument, StagedPage)>),
+}
+
+/// Reuse the stored translation unless `force` is set, otherwise claim the page.
+async fn claim_translation(
+    session_ref: &SessionRef<'_>,
+    page: usize,
+    force: bool,
+) -> RouteResult<PageTranslation> {
+    session_ref
+        .with_session_mut(|session| {
+            if !force && let Some(stored) = session.page_store.page_snapshot(page) {
+                return Ok(PageTranslation::Stored(stored.version()));
+            }
+            let claim = session.claim_page(page).ok_or(())?;
+            Ok(PageTranslation::Claimed(Box::new((
+                claim,
+                session.settings.clone(),
+                session.document.clone(),
+                session.page_store.staging_path(page),
+            ))))
+        })
+        .await
+        .or_not_found("Session not found")?
+        .map_err(|()| {
+            (
+                StatusCode::CONFLICT,
+                "This page is already being translated".to_string(),
+            )
+        })
+}
 
 pub async fn translate_page(
     State(state): State<Arc<AppState>>,
@@ -39,39 +83,15 @@ pub async fn translate_page(
         .or_not_found("Session not found")?;
     validate_page(page, page_count)?;
 
-    let (cached_version, claimed) = session_ref
-        .with_session_mut(|session| {
-            if !force_retranslate && let Some(stored) = session.page_store.page_snapshot(page) {
-                return Ok((Some(stored.version()), None));
+    let (claim, settings, document, staged) =
+        match claim_translation(&session_ref, page, force_retranslate).await? {
+            PageTranslation::Stored(version) => {
+                return render_result(&TranslateResultTemplate::success(
+                    session_id, page, version, true,
+                ));
             }
-            let claim = session.claim_page(page).ok_or(())?;
-            Ok((
-                None,
-                Some((
-                    claim,
-                    session.settings.clone(),
-                    session.document.clone(),
-                    session.page_store.staging_path(page),
-                )),
-            ))
-        })
-        .await
-        .or_not_found("Session not found")?
-        .map_err(|()| {
-            (
-                StatusCode::CONFLICT,
-                "This page is already being translated".to_string(),
-            )
-        })?;
-
-    if let Some(version) = cached_version {
-        return render_result(&TranslateResultTemplate::success(
-            session_id, page, version, true,
-        ));
-    }
-    let Some((claim, settings, document, mut staged)) = claimed else {
-        unreachable!("a translation claim accompanies every cache miss");
-    };
+            PageTranslation::Claimed(claimed) => *claimed,
+        };
 
     let translator = match state.create_translator(&settings) {
         Ok(translator) => translator,
@@ -93,29 +113,55 @@ pub async fn translate_page(
         }
     };
 
+    let template = publish_translation(
+        &state,
+        &session_ref,
+        session_id,
+        (page, page_count),
+        &claim,
+        staged,
+        &result,
+    )
+    .await?;
+    render_result(&template)
+}
+
+/// Store a finished page translation and build the panel update for it.
+///
+/// Failures that leave the page untranslated render as an error result;
+/// superseded translations are reported as a conflict.
+async fn publish_translation(
+    state: &Arc<AppState>,
+    session_ref: &SessionRef<'_>,
+    session_id: String,
+    (page, page_count): (usize, usize),
+    claim: &PageClaim,
+    mut staged: StagedPage,
+    result: &TranslatedPage,
+) -> RouteResult<TranslateResultTemplate> {
     let reservation = match state.reserve_output(result.pdf_bytes.len()) {
         Ok(reservation) => reservation,
         Err(error) => {
-            release_claim(&session_ref, &claim).await;
-            return render_error_result(&session_ref, session_id, page, error.to_string()).await;
+            release_claim(session_ref, claim).await;
+            return Ok(error_result(session_ref, session_id, page, error.to_string()).await);
         }
     };
     staged.reserve(reservation);
 
     if let Err(error) = PageStore::write_staged(&staged, &result.pdf_bytes).await {
-        release_claim(&session_ref, &claim).await;
+        release_claim(session_ref, claim).await;
         error!("Failed to durably stage translated page {page}: {error}");
-        return render_error_result(
-            &session_ref,
+        return Ok(error_result(
+            session_ref,
             session_id,
             page,
             format!("Failed to store translated page: {error}"),
         )
-        .await;
+        .await);
     }
 
     let publication = session_ref
-        .commit_claimed_page(&claim, staged, |_| true)
+        .commit_claimed_page(claim, staged, |_| true)
         .await;
 
     match publication {
@@ -135,21 +181,21 @@ pub async fn translate_page(
             }
             for next_page in [page + 1, page + 2] {
                 if next_page < page_count {
-                    let state = Arc::clone(&state);
+                    let state = Arc::clone(state);
                     let session_id = session_id.clone();
                     tokio::spawn(async move {
                         prefetch_page_internal(&state, &session_id, next_page).await;
                     });
                 }
             }
-            render_result(&TranslateResultTemplate::success(
+            Ok(TranslateResultTemplate::success(
                 session_id,
                 page,
                 version,
                 result.from_cache,
             ))
         }
-        Err(error) => render_error_result(&session_ref, session_id, page, error.to_string()).await,
+        Err(error) => Ok(error_result(session_ref, session_id, page, error.to_string()).await),
         Ok(None) => Err((
             StatusCode::CONFLICT,
             "Translation was superseded by updated settings".to_string(),
@@ -158,11 +204,20 @@ pub async fn translate_page(
 }
 
 async fn render_error_result(
-    session_ref: &crate::state::SessionRef<'_>,
+    session_ref: &SessionRef<'_>,
     session_id: String,
     page: usize,
     message: String,
 ) -> RouteResult<Response> {
+    render_result(&error_result(session_ref, session_id, page, message).await)
+}
+
+async fn error_result(
+    session_ref: &SessionRef<'_>,
+    session_id: String,
+    page: usize,
+    message: String,
+) -> TranslateResultTemplate {
     let (is_translated, version) = session_ref
         .with_session(|session| {
             session
@@ -172,17 +227,14 @@ async fn render_error_result(
         })
         .await
         .unwrap_or((false, 0));
-    render_result(&TranslateResultTemplate::error(
-        session_id,
-        page,
-        is_translated,
-        version,
-        message,
-    ))
+    TranslateResultTemplate::error(session_id, page, is_translated, version, message)
 }
 
 fn render_result(template: &TranslateResultTemplate) -> RouteResult<Response> {
-    let html = template.render().or_internal_error()?;
+    html_response(template.render().or_internal_error()?)
+}
+
+fn html_response(html: String) -> RouteResult<Response> {
     Response::builder()
         .status(StatusCode::OK)
         .header(header::CONTENT_TYPE, "text/html; charset=utf-8")
@@ -190,7 +242,7 @@ fn render_result(template: &TranslateResultTemplate) -> RouteResult<Response> {
         .or_internal_error()
 }
 
-async fn release_claim(session_ref: &crate::state::SessionRef<'_>, claim: &PageClaim) {
+async fn release_claim(session_ref: &SessionRef<'_>, claim: &PageClaim) {
     session_ref
         .with_session_mut(|session| {
             session.release_claim(claim);
@@ -259,3 +311,277 @@ pub async fn prefetch_page(
     });
     StatusCode::NO_CONTENT
 }
+
+/// Start a streaming translation of a page and render the preview shell watching it.
+///
+/// The translation runs as a job kept in the session, so the shell's
+/// EventSource only watches it and reconnecting never starts billed work
+/// again. A page that is already stored renders its result straight away.
+pub async fn translate_preview(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, url_page)): Path<(String, usize)>,
+    Form(form): Form<TranslateForm>,
+) -> RouteResult<Response> {
+    let page = page_index(url_page)?;
+    let force = form.force.is_some();
+    let session_ref = state
+        .get_session(&session_id)
+        .await
+        .or_not_found("Session not found")?;
+    let page_count = session_ref
+        .with_session(|session| session.document.page_count())
+        .await
+        .or_not_found("Session not found")?;
+    validate_page(page, page_count)?;
+
+    let running = session_ref
+        .with_session(|session| {
+            session
+                .page_streams
+                .get(&page)
+                .is_some_and(|stream| stream.borrow().complete.is_none())
+        })
+        .await
+        .or_not_found("Session not found")?;
+    if !running {
+        let claimed = match claim_translation(&session_ref, page, force).await? {
+            PageTranslation::Stored(version) => {
+                return render_result(&TranslateResultTemplate::success(
+                    session_id, page, version, true,
+                ));
+            }
+            PageTranslation::Claimed(claimed) => *claimed,
+        };
+        let (updates, watcher) = watch::channel(PageStream::default());
+        session_ref
+            .with_session_mut(|session| {
+                session.page_streams.insert(page, watcher);
+            })
+            .await;
+        let state = Arc::clone(&state);
+        let session_id = session_id.clone();
+        tokio::spawn(async move {
+            run_page_stream(
+                &state,
+                session_id,
+                (page, page_count),
+                claimed,
+                force,
+                &updates,
+            )
+            .await;
+        });
+    }
+
+    let template = TranslateStreamTemplate { session_id, page };
+    html_response(template.render().or_internal_error()?)
+}
+
+enum StreamStep {
+    Preview(bool),
+    Done(pdf_translator_core::Result<TranslatedPage>),
+}
+
+/// Translate a claimed page, publishing the block layer of each partial
+/// translation and then the final panel update to `updates`.
+async fn run_page_stream(
+    state: &Arc<AppState>,
+    session_id: String,
+    (page, page_count): (usize, usize),
+    (claim, settings, document, staged): (PageClaim, SessionSettings, PdfDocument, StagedPage),
+    force: bool,
+    updates: &watch::Sender<PageStream>,
+) {
+    let Some(session_ref) = state.get_session(&session_id).await else {
+        return;
+    };
+    let translator = match state.create_translator(&settings) {
+        Ok(translator) => translator,
+        Err(error) => {
+            release_claim(&session_ref, &claim).await;
+            let template = error_result(&session_ref, session_id, page, error.to_string()).await;
+            send_complete(updates, &template);
+            return;
+        }
+    };
+
+    let page_box = page_box(&document, page).await;
+    let (sender, mut previews) = watch::channel(Vec::<Option<BlockPreview>>::new());
+    let mut blocks = Vec::new();
+    let on_preview = move |preview: BlockPreview| {
+        if blocks.len() < preview.block_count {
+            blocks.resize(preview.block_count, None);
+        }
+        let index = preview.block;
+        if let Some(slot) = blocks.get_mut(index) {
+            *slot = Some(preview);
+        }
+        sender.send_replace(blocks.clone());
+    };
+    let translation = translator.translate_page_streaming(&document, page, force, on_preview);
+    tokio::pin!(translation);
+
+    let result = loop {
+        let step = tokio::select! {
+            result = &mut translation => StreamStep::Done(result),
+            changed = previews.changed() => StreamStep::Preview(changed.is_ok()),
+        };
+        match step {
+            StreamStep::Done(result) => break result,
+            StreamStep::Preview(true) => {
+                let html = preview_html(&previews.borrow_and_update(), page_box);
+                if let Some(html) = html {
+                    updates.send_modify(|stream| stream.preview = Some(html));
+                }
+            }
+            StreamStep::Preview(false) => break (&mut translation).await,
+        }
+    };
+
+    let template = match result {
+        Ok(result) => {
+            let published = publish_translation(
+                state,
+                &session_ref,
+                session_id.clone(),
+                (page, page_count),
+                &claim,
+                staged,
+                &result,
+            )
+            .await;
+            match published {
+                Ok(template) => template,
+                Err((_, message)) => error_result(&session_ref, session_id, page, message).await,
+            }
+        }
+        Err(error) => {
+            release_claim(&session_ref, &claim).await;
+            error!("Streaming translation failed for page {page}: {error}");
+            error_result(&session_ref, session_id, page, error.to_string()).await
+        }
+    };
+    send_complete(updates, &template);
+}
+
+/// Watch a page's streaming translation as server-sent events.
+///
+/// `preview` events carry the block layer drawn over the original page; a
+/// single `complete` event carries the same panel update as [`translate_page`].
+/// Every exit ends with `complete`, which closes the EventSource instead of
+/// letting it reconnect; watching never starts a translation.
+#[allow(tail_expr_drop_order)]
+pub async fn translate_stream(
+    State(state): State<Arc<AppState>>,
+    Path((session_id, url_page)): Path<(String, usize)>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let stream = async_stream::stream! {
+        let page = url_page.saturating_sub(1);
+        let updates = match state.get_session(&session_id).await {
+            Some(session_ref) if url_page > 0 => session_ref
+                .with_session(|session| session.page_streams.get(&page).cloned())
+                .await
+                .flatten(),
+            _ => None,
+        };
+        let Some(mut updates) = updates else {
+            let template = stream_error_result(
+                &state,
+                session_id,
+                page,
+                "No translation is running for this page",
+            )
+            .await;
+            yield Ok(complete_event(complete_html(&template)));
+            return;
+        };
+
+        loop {
+            let current = updates.borrow_and_update().clone();
+            if let Some(html) = current.complete {
+                yield Ok(complete_event(html));
+                return;
+            }
+            if let Some(html) = current.preview {
+                yield Ok(Event::default().event("preview").data(html));
+            }
+            if updates.changed().await.is_err() {
+                let template = stream_error_result(
+                    &state,
+                    session_id,
+                    page,
+                    "The translation stopped before finishing",
+                )
+                .await;
+                yield Ok(complete_event(complete_html(&template)));
+                return;
+            }
+        }
+    };
+    Sse::new(stream).keep_alive(KeepAlive::default())
+}
+
+/// Panel update for a stream watcher left without a translation to watch.
+async fn stream_error_result(
+    state: &AppState,
+    session_id: String,
+    page: usize,
+    message: &str,
+) -> TranslateResultTemplate {
+    match state.get_session(&session_id).await {
+        Some(session_ref) => {
+            error_result(&session_ref, session_id, page, message.to_string()).await
+        }
+        None => TranslateResultTemplate::error(session_id, page, false, 0, message.to_string()),
+    }
+}
+
+fn send_complete(updates: &watch::Sender<PageStream>, template: &TranslateResultTemplate) {
+    let html = complete_html(template);
+    updates.send_modify(|stream| stream.complete = Some(html));
+}
+
+fn complete_html(template: &TranslateResultTemplate) -> String {
+    template.render().unwrap_or_else(|error| {
+        error!("Failed to render translation result: {error}");
+        String::new()
+    })
+}
+
+fn complete_event(html: String) -> Event {
+    Event::default().event("complete").data(html)
+}
+
+/// Page width and height in PDF points, used to position preview blocks.
+async fn page_box(document: &PdfDocument, page: usize) -> Option<(f64, f64)> {
+    let document = document.clone();
+    let size = tokio::task::spawn_blocking(move || {
+        PageRenderer::with_scale(&document, 1.0).page_size(page)
+    })
+    .await
+    .ok()?
+    .ok()?;
+    Some((f64::from(size.width), f64::from(size.height)))
+}
+
+fn preview_html(blocks: &[Option<BlockPreview>], page_box: Option<(f64, f64)>) -> Option<String> {
+    let (width, height) = page_box.filter(|(width, height)| *width > 0.0 && *height > 0.0)?;
+    let blocks = blocks
+        .iter()
+        .flatten()
+        .map(|preview| {
+            let bbox = preview.bbox;
+            PreviewBlock {
+                style: format!(
+                    "left: {:.2}%; top: {:.2}%; width: {:.2}%; min-height: {:.2}%",
+                    f64::from(bbox.x0) * 100.0 / width,
+                    f64::from(bbox.y0) * 100.0 / height,
+                    f64::from(bbox.width()) * 100.0 / width,
+                    f64::from(bbox.height()) * 100.0 / height,
+                ),
+                text: preview.text.clone(),
+            }
+        })
+        .collect();
+    StreamPreviewTemplate { blocks }.render().ok()
+}
@@ -9,13 +9,17 @@ use axum::{
 use axum_extra::extract::Multipart;
 use axum_extra::extract::multipart::MultipartError;
 use pdf_translator_core::PdfDocument;
+use pdf_translator_core::pdf::TextExtractor;
 use std::sync::Arc;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::UPLOAD_BODY_LIMIT;
 use crate::helpers::{ResultExt, RouteResult};
 use crate::state::{AppState, CreateSessionError};
 
+/// Pages sampled to detect the document's language.
+const LANGUAGE_SAMPLE_PAGES: usize = 3;
+
 fn public_multipart_error(error: &MultipartError) -> (StatusCode, String) {
     let status = error.status();
     error!("Failed to read multipart upload: {error}");
@@ -61,46 +65,61 @@ pub async fn upload_pdf(
             // PdfDocument retains a Vec, so make the single required copy before moving parsing
             // off the async runtime.
             let data = data.to_vec();
-            let doc = tokio::task::spawn_blocking(move || PdfDocument::from_bytes(data))
-                .await
-                .map_err(|e| {
-                    error!("PDF parsing task panicked: {}", e);
-                    (
-                        StatusCode::INTERNAL_SERVER_ERROR,
-                        "PDF parsing failed".to_string(),
-                    )
-                })?
-                .map_err(|e| {
-                    error!("Failed to parse PDF: {}", e);
-                    (StatusCode::BAD_REQUEST, "Invalid PDF document".to_string())
-                })?;
+            let (doc, detected_language) = tokio::task::spawn_blocking(move || {
+                let doc = PdfDocument::from_bytes(data)?;
+                // Detection only preselects the source; text that cannot be
+                // extracted leaves the default in place.
+                let detected = TextExtractor::new(&doc)
+                    .detect_document_language(LANGUAGE_SAMPLE_PAGES)
+                    .unwrap_or_else(|e| {
+                        warn!("Language detection failed: {}", e);
+                        None
+                    });
+                Ok::<_, pdf_translator_core::Error>((doc, detected))
+            })
+            .await
+            .map_err(|e| {
+                error!("PDF parsing task panicked: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "PDF parsing failed".to_string(),
+                )
+            })?
+            .map_err(|e| {
+                error!("Failed to parse PDF: {}", e);
+                (StatusCode::BAD_REQUEST, "Invalid PDF document".to_string())
+            })?;
 
             let page_count = doc.page_count();
-            let session_id =
-                state
-                    .create_session(doc, filename.clone())
-                    .await
-                    .map_err(|error| {
-                        error!("Failed to create session: {error}");
-                        match error {
-                            CreateSessionError::SessionLimit => (
-                                StatusCode::TOO_MANY_REQUESTS,
-                                "Too many active sessions".to_string(),
-                            ),
-                            CreateSessionError::StorageLimit => (
-                                StatusCode::PAYLOAD_TOO_LARGE,
-                                "PDF storage limit exceeded".to_string(),
-                            ),
-                            CreateSessionError::PageStore(_) => (
-                                StatusCode::INTERNAL_SERVER_ERROR,
-                                "Failed to create session".to_string(),
-                            ),
-                        }
-                    })?;
+            let session_id = state
+                .create_session(doc, filename.clone(), detected_language.as_ref())
+                .await
+                .map_err(|error| {
+                    error!("Failed to create session: {error}");
+                    match error {
+                        CreateSessionError::SessionLimit => (
+                            StatusCode::TOO_MANY_REQUESTS,
+                            "Too many active sessions".to_string(),
+                        ),
+                        CreateSessionError::StorageLimit => (
+                            StatusCode::PAYLOAD_TOO_LARGE,
+                            "PDF storage limit exceeded".to_string(),
+                        ),
+                        CreateSessionError::PageStore(_) => (
+                            StatusCode::INTERNAL_SERVER_ERROR,
+                            "Failed to create session".to_string(),
+                        ),
+                    }
+                })?;
 
             info!(
-                "Created session {} for {} ({} pages)",
-                session_id, filename, page_count
+                "Created session {} for {} ({} pages, language {})",
+                session_id,
+                filename,
+                page_count,
+                detected_language
+                    .as_ref()
+                    .map_or("unknown", pdf_translator_core::Lang::as_str)
             );
 
             // POST-Redirect-GET pattern
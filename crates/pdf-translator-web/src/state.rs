@@ -1,14 +1,16 @@
 use anyhow::Result;
+use pdf_translator_core::language::same_language;
 use pdf_translator_core::{
     AppConfig, DEFAULT_SOURCE_LANG, DEFAULT_TARGET_LANG, DEFAULT_TEXT_COLOR, Lang, PdfDocument,
-    PdfTranslator, TextColor, TranslationCache, TranslatorConfig,
+    PdfTranslator, RateLimiter, TextColor, TranslatedPage, TranslationCache, Translator,
+    UsageSummary, create_translator_chain, source_languages,
 };
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock as StdRwLock, Weak};
 use std::time::{Duration, Instant};
 use thiserror::Error;
-use tokio::sync::RwLock;
+use tokio::sync::{RwLock, watch};
 use uuid::Uuid;
 
 use crate::page_store::{OutputBudget, OutputReservation, PageStore, StagedPage};
@@ -57,14 +59,27 @@ impl PageClaim {
 pub struct Session {
     pub document: PdfDocument,
     pub original_filename: String,
+    /// Source language option matching the document's detected language
+    pub detected_source: Option<Lang>,
     pub page_store: PageStore,
     pub settings: SessionSettings,
     pub last_activity: Instant,
     pub settings_generation: u64,
     pub translate_job: Option<Arc<TranslateJob>>,
+    /// Streaming page translations by page, watched by the preview's SSE
+    pub page_streams: HashMap<usize, watch::Receiver<PageStream>>,
     in_flight: HashMap<usize, Weak<ClaimLease>>,
 }
 
+/// Latest state of a streaming page translation, shared with its SSE watchers.
+#[derive(Clone, Debug, Default)]
+pub struct PageStream {
+    /// Block layer of the partial translation, once a block has arrived
+    pub preview: Option<String>,
+    /// Final panel update, once the translation has finished
+    pub complete: Option<String>,
+}
+
 impl Session {
     pub fn claim_page(&mut self, page: usize) -> Option<PageClaim> {
         if self
@@ -122,6 +137,7 @@ impl Session {
     pub fn invalidate_translations(&mut self) {
         self.settings_generation = self.settings_generation.wrapping_add(1);
         self.page_store.clear();
+        self.page_streams.clear();
         self.in_flight.clear();
         if let Some(job) = self.translate_job.take() {
             job.set_error("Translation cancelled because settings changed".to_string());
@@ -143,15 +159,17 @@ pub struct TranslateJob {
     pub current: AtomicUsize,
     state: AtomicU8,
     error: StdRwLock<Option<String>>,
+    usage: StdRwLock<UsageSummary>,
 }
 
 impl TranslateJob {
-    pub const fn new(generation: u64) -> Self {
+    pub fn new(generation: u64) -> Self {
         Self {
             generation,
             current: AtomicUsize::new(0),
             state: AtomicU8::new(TranslateJobState::Running as u8),
             error: StdRwLock::new(None),
+            usage: StdRwLock::new(UsageSummary::default()),
         }
     }
 
@@ -213,6 +231,21 @@ impl TranslateJob {
             .unwrap_or_else(std::sync::PoisonError::into_inner)
             .clone()
     }
+
+    pub fn add_usage(&self, page: &TranslatedPage) {
+        self.usage
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .add_page(page);
+    }
+
+    /// Tokens and estimated cost of the pages translated so far.
+    pub fn usage(&self) -> UsageSummary {
+        *self
+            .usage
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
@@ -268,6 +301,14 @@ impl SessionSettings {
     }
 }
 
+/// Source language option naming the same language as `detected`, if offered.
+pub fn source_option_for(detected: &Lang) -> Option<Lang> {
+    source_languages()
+        .into_iter()
+        .find(|option| same_language(&Lang::new(option.code), detected))
+        .map(|option| Lang::new(option.code))
+}
+
 impl Default for SessionSettings {
     fn default() -> Self {
         Self {
@@ -285,28 +326,33 @@ pub struct AppState {
     output_budget: Arc<OutputBudget>,
     pub config: AppConfig,
     cache: TranslationCache,
+    /// Backend chain shared by every session, so fallback breakers and the
+    /// rate limit see all upstream traffic
+    translator: Arc<dyn Translator>,
 }
 
 impl AppState {
-    pub fn new(api_base: String, api_key: Option<String>, model: String) -> Result<Self> {
-        let config = AppConfig {
-            translator: TranslatorConfig::new(api_base, api_key, model),
-            ..Default::default()
-        };
-        let cache = TranslationCache::new(&config.cache)
+    pub fn new(config: AppConfig) -> Result<Self> {
+        let cache = TranslationCache::from_app_config(&config)
             .map_err(|e| anyhow::anyhow!("Failed to initialize translation cache: {e}"))?;
+        let translator = create_translator_chain(&config, &RateLimiter::new(&config.rate_limit))
+            .map_err(|e| anyhow::anyhow!("Failed to create translator: {e}"))?;
         Ok(Self {
             sessions: RwLock::new(HashMap::new()),
             output_budget: Arc::new(OutputBudget::new(MAX_RETAINED_TRANSLATED_BYTES)),
             config,
             cache,
+            translator,
         })
     }
 
+    /// Create a session for an uploaded document, preselecting its detected
+    /// language as the source when that language is offered.
     pub async fn create_session(
         &self,
         doc: PdfDocument,
         filename: String,
+        detected_language: Option<&Lang>,
     ) -> std::result::Result<String, CreateSessionError> {
         let source_bytes = doc.bytes().len();
         let mut sessions = self.sessions.write().await;
@@ -327,6 +373,11 @@ impl AppState {
         }
 
         let page_store = PageStore::new().map_err(CreateSessionError::PageStore)?;
+        let detected_source = detected_language.and_then(source_option_for);
+        let mut settings = SessionSettings::default();
+        if let Some(source) = &detected_source {
+            settings.source_lang = source.clone();
+        }
         let id = Uuid::new_v4();
         let now = Instant::now();
         sessions.insert(
@@ -334,11 +385,13 @@ impl AppState {
             Session {
                 document: doc,
                 original_filename: filename,
+                detected_source,
                 page_store,
-                settings: SessionSettings::default(),
+                settings,
                 last_activity: now,
                 settings_generation: 0,
                 translate_job: None,
+                page_streams: HashMap::new(),
                 in_flight: HashMap::new(),
             },
         );
@@ -371,8 +424,12 @@ impl AppState {
         config.source_lang = settings.source_lang.clone();
         config.target_lang = settings.target_lang.clone();
         config.text_color = settings.text_color;
-        PdfTranslator::with_cache(config, self.cache.clone())
-            .map_err(|e| anyhow::anyhow!("Failed to create translator: {e}"))
+        PdfTranslator::with_translator_and_cache(
+            Arc::clone(&self.translator),
+            config,
+            self.cache.clone(),
+        )
+        .map_err(|e| anyhow::anyhow!("Failed to create translator: {e}"))
     }
 
     pub async fn cleanup_old_sessions(&self) {
@@ -453,6 +510,7 @@ impl SessionRef<'_> {
 #[allow(clippy::unwrap_used)]
 mod tests {
     use super::*;
+    use pdf_translator_core::MockTranslator;
 
     fn test_document() -> PdfDocument {
         PdfDocument::from_bytes(
@@ -465,11 +523,13 @@ mod tests {
         Session {
             document: test_document(),
             original_filename: "test.pdf".to_string(),
+            detected_source: None,
             page_store: PageStore::new().unwrap(),
             settings: SessionSettings::default(),
             last_activity: Instant::now(),
             settings_generation: 0,
             translate_job: None,
+            page_streams: HashMap::new(),
             in_flight: HashMap::new(),
         }
     }
@@ -489,6 +549,7 @@ mod tests {
             output_budget: Arc::new(OutputBudget::new(MAX_RETAINED_TRANSLATED_BYTES)),
             config,
             cache,
+            translator: Arc::new(MockTranslator),
         }
     }
 
@@ -522,16 +583,44 @@ mod tests {
         let document = test_document();
         for index in 0..MAX_SESSIONS {
             state
-                .create_session(document.clone(), format!("{index}.pdf"))
+                .create_session(document.clone(), format!("{index}.pdf"), None)
                 .await
                 .unwrap();
         }
 
         assert!(matches!(
             state
-                .create_session(document, "overflow.pdf".to_string())
+                .create_session(document, "overflow.pdf".to_string(), None)
                 .await,
             Err(CreateSessionError::SessionLimit)
         ));
     }
+
+    #[tokio::test]
+    async fn detected_language_preselects_an_offered_source() {
+        let state = test_state();
+        let id = state
+            .create_session(
+                test_document(),
+                "en.pdf".to_string(),
+                Some(&Lang::new("en")),
+            )
+            .await
+            .unwrap();
+        let (source, detected) = state
+            .get_session(&id)
+            .await
+            .unwrap()
+            .with_session(|s| (s.settings.source_lang.clone(), s.detected_source.clone()))
+            .await
+            .unwrap();
+        assert_eq!(source, Lang::new("en"));
+        assert_eq!(detected, Some(Lang::new("en")));
+
+        assert_eq!(
+            source_option_for(&Lang::new("zh")),
+            Some(Lang::new("zh-CN"))
+        );
+        assert_eq!(source_option_for(&Lang::new("nl")), None);
+    }
 }
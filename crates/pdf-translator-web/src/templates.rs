@@ -49,6 +49,8 @@ pub struct AppTemplate {
     pub source_languages: Vec<LanguageOption>,
     pub target_languages: Vec<LanguageOption>,
     pub current_source: String,
+    /// Source option matching the detected document language (empty if none)
+    pub detected_source: String,
     pub current_target: String,
     pub current_color: String,
     /// CSS class for the viewer div (derived from view_mode)
@@ -75,6 +77,7 @@ impl AppTemplate {
         is_translated: bool,
         has_translations: bool,
         current_source: String,
+        detected_source: String,
         current_target: String,
         current_color: String,
         view_mode: ViewMode,
@@ -90,6 +93,7 @@ impl AppTemplate {
             source_languages: source_languages(),
             target_languages: target_languages(),
             current_source,
+            detected_source,
             current_target,
             current_color,
             viewer_class: view_mode.viewer_class(),
@@ -243,6 +247,27 @@ impl TranslateResultTemplate {
     }
 }
 
+/// Streaming translation shell - original page with an SSE-driven preview layer.
+#[derive(Template, WebTemplate)]
+#[template(path = "partials/translate_stream.html")]
+pub struct TranslateStreamTemplate {
+    pub session_id: String,
+    pub page: usize,
+}
+
+/// One partially translated block, positioned as percentages of the page box.
+pub struct PreviewBlock {
+    pub style: String,
+    pub text: String,
+}
+
+/// Preview layer content sent with each streaming `preview` event.
+#[derive(Template, WebTemplate)]
+#[template(path = "partials/stream_preview.html")]
+pub struct StreamPreviewTemplate {
+    pub blocks: Vec<PreviewBlock>,
+}
+
 /// Settings cleared fragment - shows placeholder after settings change.
 #[derive(Template, WebTemplate, Default)]
 #[template(path = "partials/settings_cleared.html")]
@@ -449,5 +474,18 @@ mod tests {
         assert!(error.contains("force"));
         assert!(error.contains("bad"));
         assert!(!error.contains("<bad>"));
+        assert!(error.contains("hx-post=\"/api/translate-preview/session/1\""));
+    }
+
+    #[test]
+    fn stream_preview_only_watches_the_started_translation() {
+        let html = TranslateStreamTemplate {
+            session_id: "session".to_string(),
+            page: 0,
+        }
+        .render()
+        .unwrap();
+        assert!(html.contains("sse-connect=\"/api/translate-stream/session/1\""));
+        assert!(html.contains("sse-close=\"complete\""));
     }
 }